@@ -0,0 +1,141 @@
+//! Compare the storage families a transposer source can keep its steps in.
+//!
+//! `forward` only ever polls later times, so steps are rarely cloned.
+//! `rewind` polls every time in reverse after running forward, so every poll recomputes from a checkpoint.
+
+#![feature(test)]
+
+extern crate test;
+
+use cozal::adapters::TransposeStorage;
+use cozal::sources::transposer::no_input_transposer::NoInputTransposerSource;
+use cozal::traits::SourceContext;
+use cozal::{Source, SourcePoll};
+use test::Bencher;
+use transposer::context::{HandleScheduleContext, InitContext, InterpolateContext};
+use transposer::schedule_storage::{DefaultStorage, StdStorage, StorageFamily};
+use transposer::step::NoInputManager;
+use transposer::Transposer;
+use util::dummy_waker::DummyWaker;
+
+const STEPS: usize = 200;
+
+// keeps a full schedule, so the maps have something in them to clone.
+#[derive(Clone)]
+struct BusyTransposer {
+    total: u64,
+}
+
+impl Transposer for BusyTransposer {
+    type Time = usize;
+
+    type OutputState = u64;
+
+    type Scheduled = u64;
+
+    type OutputEvent = ();
+
+    type InputStateManager = NoInputManager;
+
+    async fn init(&mut self, cx: &mut impl InitContext<Self>) {
+        for i in 0..64 {
+            cx.schedule_event(i + 1, i as u64).unwrap();
+        }
+    }
+
+    async fn handle_scheduled(
+        &mut self,
+        payload: Self::Scheduled,
+        cx: &mut impl HandleScheduleContext<Self>,
+    ) {
+        self.total += payload;
+        cx.schedule_event(cx.current_time() + 64, payload + 1)
+            .unwrap();
+    }
+
+    async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {
+        self.total
+    }
+}
+
+fn poll<S: StorageFamily>(
+    source: &mut NoInputTransposerSource<BusyTransposer, S>,
+    time: usize,
+) -> u64 {
+    let cx = SourceContext {
+        channel:           0,
+        one_channel_waker: DummyWaker::dummy(),
+        all_channel_waker: DummyWaker::dummy(),
+    };
+
+    loop {
+        match source.poll(time, cx.clone()).ok().unwrap() {
+            SourcePoll::Ready {
+                state, ..
+            } => return state,
+            SourcePoll::Interrupt {
+                ..
+            } => {},
+            SourcePoll::Pending => panic!(),
+        }
+    }
+}
+
+fn forward<S: StorageFamily>(b: &mut Bencher) {
+    b.iter(|| {
+        let mut source = NoInputTransposerSource::<_, S>::new_with_storage(
+            BusyTransposer {
+                total: 0
+            },
+            0,
+            [0; 32],
+        );
+
+        (1..=STEPS).map(|t| poll(&mut source, t)).last()
+    })
+}
+
+fn rewind<S: StorageFamily>(b: &mut Bencher) {
+    b.iter(|| {
+        let mut source = NoInputTransposerSource::<_, S>::new_with_storage(
+            BusyTransposer {
+                total: 0
+            },
+            0,
+            [0; 32],
+        );
+
+        poll(&mut source, STEPS);
+        (1..=STEPS).rev().map(|t| poll(&mut source, t)).last()
+    })
+}
+
+#[bench]
+fn forward_default(b: &mut Bencher) {
+    forward::<DefaultStorage>(b)
+}
+
+#[bench]
+fn forward_rc(b: &mut Bencher) {
+    forward::<TransposeStorage>(b)
+}
+
+#[bench]
+fn forward_std(b: &mut Bencher) {
+    forward::<StdStorage>(b)
+}
+
+#[bench]
+fn rewind_default(b: &mut Bencher) {
+    rewind::<DefaultStorage>(b)
+}
+
+#[bench]
+fn rewind_rc(b: &mut Bencher) {
+    rewind::<TransposeStorage>(b)
+}
+
+#[bench]
+fn rewind_std(b: &mut Bencher) {
+    rewind::<StdStorage>(b)
+}
@@ -1,16 +1,30 @@
 use std::num::NonZeroUsize;
-use std::pin::Pin;
 use std::task::Waker;
 
+use parking_lot::Mutex;
+
 use crate::source_poll::TrySourcePoll;
 use crate::traits::{ConcurrentSource, SourceContext};
 use crate::Source;
 
-pub struct MutexSource<Src: Source>(parking_lot::Mutex<Src>);
+/// A source which can be polled through a shared reference, by locking the source it wraps for every call.
+///
+/// Only one call makes progress at a time. Sources which can do better should implement [`ConcurrentSource`] themselves.
+pub struct MutexSource<Src: Source> {
+    source:      Mutex<Src>,
+    max_channel: NonZeroUsize,
+}
 
 impl<Src: Source> MutexSource<Src> {
     pub fn new(source: Src) -> Self {
-        Self(parking_lot::Mutex::new(source))
+        Self {
+            max_channel: source.max_channel(),
+            source:      Mutex::new(source),
+        }
+    }
+
+    pub fn into_inner(self) -> Src {
+        self.source.into_inner()
     }
 }
 
@@ -24,39 +38,39 @@ impl<Src: Source> Source for MutexSource<Src> {
     type Error = Src::Error;
 
     fn poll(
-        self: Pin<&mut Self>,
+        &mut self,
         time: Self::Time,
         cx: SourceContext,
     ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
-        self.poll_concurrent(time, cx)
+        self.source.get_mut().poll(time, cx)
     }
 
     fn poll_forget(
-        self: Pin<&mut Self>,
+        &mut self,
         time: Self::Time,
         cx: SourceContext,
     ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
-        self.poll_forget_concurrent(time, cx)
+        self.source.get_mut().poll_forget(time, cx)
     }
 
     fn poll_events(
-        self: Pin<&mut Self>,
+        &mut self,
         time: Self::Time,
         all_channel_waker: Waker,
     ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
-        self.poll_events_concurrent(time, all_channel_waker)
+        self.source.get_mut().poll_events(time, all_channel_waker)
     }
 
-    fn release_channel(self: Pin<&mut Self>, channel: usize) {
-        self.release_channel_concurrent(channel)
+    fn release_channel(&mut self, channel: usize) {
+        self.source.get_mut().release_channel(channel)
     }
 
-    fn advance(self: Pin<&mut Self>, time: Self::Time) {
-        self.advance_concurrent(time)
+    fn advance(&mut self, time: Self::Time) {
+        self.source.get_mut().advance(time)
     }
 
     fn max_channel(&self) -> NonZeroUsize {
-        self.0.lock().max_channel()
+        self.max_channel
     }
 }
 
@@ -66,8 +80,7 @@ impl<Src: Source> ConcurrentSource for MutexSource<Src> {
         time: Self::Time,
         cx: SourceContext,
     ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
-        let mut source = self.0.lock();
-        unsafe { Pin::new_unchecked(&mut *source) }.poll(time, cx)
+        self.source.lock().poll(time, cx)
     }
 
     fn poll_forget_concurrent(
@@ -75,8 +88,7 @@ impl<Src: Source> ConcurrentSource for MutexSource<Src> {
         time: Self::Time,
         cx: SourceContext,
     ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
-        let mut source = self.0.lock();
-        unsafe { Pin::new_unchecked(&mut *source) }.poll_forget(time, cx)
+        self.source.lock().poll_forget(time, cx)
     }
 
     fn poll_events_concurrent(
@@ -84,17 +96,57 @@ impl<Src: Source> ConcurrentSource for MutexSource<Src> {
         time: Self::Time,
         all_channel_waker: Waker,
     ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
-        let mut source = self.0.lock();
-        unsafe { Pin::new_unchecked(&mut *source) }.poll_events(time, all_channel_waker)
+        self.source.lock().poll_events(time, all_channel_waker)
     }
 
     fn release_channel_concurrent(&self, channel: usize) {
-        let mut source = self.0.lock();
-        unsafe { Pin::new_unchecked(&mut *source) }.release_channel(channel)
+        self.source.lock().release_channel(channel)
     }
 
     fn advance_concurrent(&self, time: Self::Time) {
-        let mut source = self.0.lock();
-        unsafe { Pin::new_unchecked(&mut *source) }.advance(time)
+        self.source.lock().advance(time)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MutexSource;
+    use crate::source_poll::Interrupt;
+    use crate::sources::transposer::multi_input_transposer::test::{cx, TestSource};
+    use crate::traits::ConcurrentSource;
+    use crate::{Source, SourcePoll};
+
+    #[test]
+    fn shared_polls_test() {
+        let source = TestSource::default();
+        source.insert(2, 10);
+
+        let source = MutexSource::new(source);
+        let (a, b) = (&source, &source);
+
+        assert!(matches!(
+            a.poll_concurrent(5, cx(0)).ok().unwrap(),
+            SourcePoll::Interrupt {
+                time:      2,
+                interrupt: Interrupt::Event(10),
+            }
+        ));
+        assert!(matches!(
+            b.poll_concurrent(5, cx(1)).ok().unwrap(),
+            SourcePoll::Ready {
+                state: 1,
+                ..
+            }
+        ));
+
+        a.release_channel_concurrent(1);
+        b.advance_concurrent(3);
+        assert!(matches!(
+            source.into_inner().poll(5, cx(0)).ok().unwrap(),
+            SourcePoll::Ready {
+                state: 1,
+                ..
+            }
+        ));
     }
 }
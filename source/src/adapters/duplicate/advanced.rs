@@ -35,8 +35,24 @@ impl<T: Ord + Copy> Advanced<T> {
 
         self.decrement_time(prev_time);
         self.increment_time(Some(time));
+        self.index_map.insert(index, Some(time));
 
-        let after = self.current_aggregate_advancement().unwrap();
+        self.risen_advancement(before)
+    }
+
+    // forget a dropped duplicate, returning the new advancement if it was holding it back.
+    pub fn unregister_duplicate(&mut self, index: usize) -> Option<T> {
+        let prev_time = self.index_map.remove(&index)?;
+
+        let before = self.current_aggregate_advancement();
+
+        self.decrement_time(prev_time);
+
+        self.risen_advancement(before)
+    }
+
+    fn risen_advancement(&self, before: Option<T>) -> Option<T> {
+        let after = self.current_aggregate_advancement()?;
 
         match before {
             None => Some(after),
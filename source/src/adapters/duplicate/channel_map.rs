@@ -1,60 +1,59 @@
 use core::num::NonZeroUsize;
 
+// duplicate and channel are mapped to a channel of the original with the cantor pairing function,
+// so every duplicate gets a disjoint set of channels.
 pub fn map(duplicate: usize, channel: usize) -> usize {
-    let s = duplicate + channel;
-
-    // have to be careful not to overflow prematurely
-    if s % 2 == 0 {
-        (s / 2) * (s + 1) + channel
-    } else {
-        s * ((s + 1) / 2) + channel
-    }
+    triangle(duplicate + channel).unwrap() + channel
 }
 
+/// the number of duplicates which can be given at least two channels of the original.
 pub fn max_duplicates(channels: usize) -> usize {
-    #[cfg(target_pointer_width = "16")]
-    let channels_spacious = channels as u32;
-    #[cfg(target_pointer_width = "32")]
-    let channels_spacious = channels as u64;
-    #[cfg(target_pointer_width = "64")]
-    let channels_spacious = channels as u128;
+    let s = max_triangle_root(channels);
 
-    let sqrt = int_sqrt(2 * channels_spacious);
-    let p = if sqrt % 2 == 0 {
-        (sqrt / 2).checked_mul(sqrt + 1)
+    // see max_channel. the last diagonal only has room for its first duplicate if it is full.
+    if triangle(s) == Some(channels) {
+        s.saturating_sub(1)
     } else {
-        sqrt.checked_mul((sqrt + 1) / 2)
-    };
-    match p {
-        Some(m) => {
-            if m <= channels {
-                sqrt
-            } else {
-                sqrt - 1
-            }
-        },
-        None => sqrt - 1,
+        s
     }
 }
 
+/// the largest channel which `duplicate` can use without exceeding `channels`.
 pub fn max_channel(channels: NonZeroUsize, duplicate: usize) -> NonZeroUsize {
-    let max_b = max_duplicates(channels.into()) - duplicate;
+    let channels = channels.get();
+    let s = max_triangle_root(channels);
 
-    let max_b = if max_b > last_b(channels.into()) {
-        max_b - 1
-    } else {
-        max_b
-    };
+    // the channel on the diagonal s is the largest if it fits. otherwise the one on s - 1 does.
+    let channel = s.checked_sub(duplicate).and_then(|channel| {
+        if triangle(s)? + channel <= channels {
+            Some(channel)
+        } else {
+            channel.checked_sub(1)
+        }
+    });
 
-    NonZeroUsize::new(max_b).unwrap()
+    channel
+        .and_then(NonZeroUsize::new)
+        .expect("too many duplicates for the channels of the original source")
 }
 
-fn last_b(n: usize) -> usize {
-    let a = max_duplicates(n);
-    n - if a % 2 == 0 {
-        (a / 2) * (a + 1)
+// the sum of the numbers up to n
+fn triangle(n: usize) -> Option<usize> {
+    // have to be careful not to overflow prematurely
+    if n.is_multiple_of(2) {
+        (n / 2).checked_mul(n + 1)
     } else {
-        a * ((a + 1) / 2)
+        n.checked_mul(n.div_ceil(2))
+    }
+}
+
+// the largest s where triangle(s) <= n
+fn max_triangle_root(n: usize) -> usize {
+    let sqrt = int_sqrt(2 * n as u128);
+
+    match triangle(sqrt) {
+        Some(t) if t <= n => sqrt,
+        _ => sqrt - 1,
     }
 }
 
@@ -1,23 +1,26 @@
 use core::num::NonZeroUsize;
-use core::task::Poll;
-use std::collections::BTreeSet;
-use std::sync::{Arc, RwLock};
+use core::task::Waker;
+use std::collections::{BTreeSet, VecDeque};
+use std::sync::{Arc, Mutex};
 
 use super::original::Original;
 use super::rollback_event::RollbackEvent;
 use super::{channel_map, PollFn};
-use crate::source_poll::SourcePollOk;
+use crate::source_poll::TrySourcePoll;
 use crate::traits::SourceContext;
-use crate::{Source, SourcePoll};
+use crate::Source;
 
 pub struct DuplicateInner<Src: Source>
 where
     Src::Event: Clone,
 {
-    index: usize,
+    pub index: usize,
 
     original: Arc<Original<Src>>,
-    events:   RwLock<Events<Src>>,
+    events:   Mutex<Events<Src>>,
+
+    // the channels of the original this duplicate has polled and not released.
+    channels: Mutex<BTreeSet<usize>>,
 }
 
 impl<Src: Source> DuplicateInner<Src>
@@ -35,10 +38,16 @@ where
     pub fn from_original(original: Arc<Original<Src>>) -> Arc<Self> {
         let index = original.get_next_index();
 
+        assert!(
+            index < channel_map::max_duplicates(original.max_channel().get()),
+            "too many duplicates for the channels of the original source"
+        );
+
         let child = DuplicateInner {
             index,
             original,
-            events: RwLock::new(Events::new()),
+            events: Mutex::new(Events::new()),
+            channels: Mutex::new(BTreeSet::new()),
         };
 
         let child = Arc::new(child);
@@ -48,41 +57,36 @@ where
         child
     }
 
-    pub fn poll<State>(
-        self: &Arc<Self>,
+    pub fn poll(
+        &self,
         poll_time: Src::Time,
         cx: SourceContext,
-        poll_fn: PollFn<Src, State>,
-    ) -> SourcePoll<Src::Time, Src::Event, State, Src::Error> {
+        poll_fn: PollFn<Src, Src::State>,
+    ) -> TrySourcePoll<Src::Time, Src::Event, Src::State, Src::Error> {
         // we need to register our waker right away, even if we don't end up using the context
         let updated_cx = self.get_new_context(cx);
 
-        // emit previously emitted stuff if we have any.
-        if let Some(event) = self.poll_previously_emitted(poll_time) {
-            return Poll::Ready(Ok(event.into()))
-        }
+        self.original.poll(self, poll_time, |source| {
+            poll_fn(source, poll_time, updated_cx)
+        })
+    }
 
-        // poll the underlying source for new info
-        let poll = self
-            .original
-            .poll(poll_time, updated_cx, poll_fn, self.index);
-
-        // adjust the scheduled/ready response to account for previously emitted events.
-        match poll {
-            Poll::Ready(Ok(SourcePollOk::Ready(s))) => Poll::Ready(Ok({
-                match self.events.read().unwrap().first_event_time(None) {
-                    Some(t) => SourcePollOk::Scheduled(s, t),
-                    None => SourcePollOk::Ready(s),
-                }
-            })),
-            Poll::Ready(Ok(SourcePollOk::Scheduled(s, t1))) => Poll::Ready(Ok({
-                match self.events.read().unwrap().first_event_time(Some(t1)) {
-                    Some(t2) => SourcePollOk::Scheduled(s, t1.min(t2)),
-                    None => SourcePollOk::Scheduled(s, t1),
-                }
-            })),
-            other => other,
-        }
+    pub fn poll_events(
+        &self,
+        poll_time: Src::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Src::Time, Src::Event, (), Src::Error> {
+        let all_channel_waker = self.original.get_new_waker(self.index, all_channel_waker);
+
+        self.original.poll(self, poll_time, |source| {
+            source.poll_events(poll_time, all_channel_waker)
+        })
+    }
+
+    pub fn release_channel(&self, channel: usize) {
+        let channel = channel_map::map(self.index, channel);
+        self.channels.lock().unwrap().remove(&channel);
+        self.original.release_channel(channel)
     }
 
     pub fn advance(&self, time: Src::Time) {
@@ -94,21 +98,22 @@ where
     }
 
     pub fn handle_rollback(&self, time: Src::Time) {
-        let mut events_lock = self.events.write().unwrap();
+        self.events.lock().unwrap().purge(time);
+    }
 
-        // throw away all stored events at or after time t
-        let key = RollbackEvent::Search {
-            time,
-        };
-        drop(events_lock.0.split_off(&key));
+    pub fn insert_rollback_event(&self, rollback_event: Arc<RollbackEvent<Src::Time, Src::Event>>) {
+        self.events.lock().unwrap().insert(rollback_event)
     }
 
-    pub fn insert_rollback_event(
+    pub fn pop_previously_emitted(
         &self,
-        rollback_event: Arc<RollbackEvent<Src::Time, Src::Event>>,
-    ) -> bool {
-        let mut events_lock = self.events.write().unwrap();
-        events_lock.insert(rollback_event)
+        poll_time: Src::Time,
+    ) -> Option<RollbackEvent<Src::Time, Src::Event>> {
+        self.events.lock().unwrap().pop(poll_time)
+    }
+
+    pub fn first_stored_time(&self) -> Option<Src::Time> {
+        self.events.lock().unwrap().first_time()
     }
 
     fn get_new_context(&self, cx: SourceContext) -> SourceContext {
@@ -123,6 +128,7 @@ where
 
         // map the channel via the channel_map math.
         let channel = channel_map::map(self.index, channel);
+        self.channels.lock().unwrap().insert(channel);
 
         SourceContext {
             channel,
@@ -130,76 +136,75 @@ where
             all_channel_waker,
         }
     }
+}
 
-    fn poll_previously_emitted(
-        &self,
-        poll_time: Src::Time,
-    ) -> Option<RollbackEvent<Src::Time, Src::Event>> {
-        let events_lock = self.events.read().unwrap();
-        let mut first = events_lock.0.first();
-
-        // ignore events which occur after poll_time. don't ignore rollbacks.
-        if let Some(x) = first {
-            if let RollbackEvent::Event {
-                time, ..
-            } = x.as_ref()
-            {
-                if time > &poll_time {
-                    first = None;
-                }
-            }
-        }
-
-        // record this and drop the read lock.
-        let should_emit_stored = first.is_some();
-        drop(events_lock);
-
-        // be careful with rollbacks. they can't be sorted, so when one is emitted, all the duplicates need to purge cancelled events
-        // rollbacks always sort first. Rolled back events are removed immediately when we first receive the rollback from the original
-        if should_emit_stored {
-            let mut events_lock = self.events.write().unwrap();
+impl<Src: Source> Drop for DuplicateInner<Src>
+where
+    Src::Event: Clone,
+{
+    fn drop(&mut self) {
+        let channels = core::mem::take(self.channels.get_mut().unwrap());
+        self.original.unregister_child(self.index, channels);
+    }
+}
 
-            // this works because the only other writers are other duplicates, which can remove events, but not rollbacks.
-            // additionally, if an event is removed, a rollback must also be inserted at or before it.
-            // we definitely know that whatever is first in this to_emit queue from the last lock is still the thing we want to emit.
-            let first = events_lock.0.pop_first().unwrap();
+// the interrupts other duplicates have pulled from the original, which this one hasn't emitted yet.
+struct Events<Src: Source> {
+    // rollbacks are emitted before anything else, and only the earliest one matters.
+    rollback: Option<Src::Time>,
+    queue:    VecDeque<Arc<RollbackEvent<Src::Time, Src::Event>>>,
+}
 
-            // try to pull the event out of the arc; clone if there are other references
-            let owned = Arc::try_unwrap(first).unwrap_or_else(|a| (*a).clone());
+impl<Src: Source> Events<Src>
+where
+    Src::Event: Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            rollback: None,
+            queue:    VecDeque::new(),
+        }
+    }
 
-            Some(owned)
-        } else {
-            None
+    pub fn insert(&mut self, rollback_event: Arc<RollbackEvent<Src::Time, Src::Event>>) {
+        match *rollback_event {
+            RollbackEvent::Rollback {
+                time,
+            } => {
+                self.purge(time);
+                self.rollback = Some(match self.rollback {
+                    Some(t) => t.min(time),
+                    None => time,
+                });
+            },
+            _ => self.queue.push_back(rollback_event),
         }
     }
-}
 
-struct Events<Src: Source>(BTreeSet<Arc<RollbackEvent<Src::Time, Src::Event>>>);
+    // throw away all stored events at or after time
+    pub fn purge(&mut self, time: Src::Time) {
+        self.queue.retain(|e| e.time() < time);
+    }
 
-impl<Src: Source> Events<Src> {
-    pub fn new() -> Self {
-        Self(BTreeSet::new())
-    }
-
-    pub fn insert(&mut self, rollback_event: Arc<RollbackEvent<Src::Time, Src::Event>>) -> bool {
-        self.0.insert(rollback_event)
-    }
-
-    pub fn first_event_time(&self, cutoff: Option<Src::Time>) -> Option<Src::Time> {
-        for e in self.0.iter() {
-            if let RollbackEvent::Event {
-                time, ..
-            } = **e
-            {
-                if let Some(t) = cutoff {
-                    if time >= t {
-                        break
-                    }
-                    return Some(time)
-                }
-            }
+    pub fn pop(&mut self, poll_time: Src::Time) -> Option<RollbackEvent<Src::Time, Src::Event>> {
+        if let Some(time) = self.rollback.take() {
+            return Some(RollbackEvent::Rollback {
+                time,
+            })
         }
 
-        None
+        // ignore events which occur after poll_time.
+        if self.queue.front()?.time() > poll_time {
+            return None
+        }
+
+        let first = self.queue.pop_front().unwrap();
+
+        // try to pull the event out of the arc; clone if there are other references
+        Some(Arc::try_unwrap(first).unwrap_or_else(|a| (*a).clone()))
+    }
+
+    pub fn first_time(&self) -> Option<Src::Time> {
+        self.queue.front().map(|e| e.time())
     }
 }
@@ -21,6 +21,20 @@ impl EventWakers {
         let waker = Arc::new(waker);
         Waker::from(waker)
     }
+
+    // wake just one duplicate, for when another duplicate has found new information for it.
+    pub fn wake(&self, index: usize) {
+        let waker = self.0.lock().unwrap().remove(&index);
+
+        if let Some(waker) = waker {
+            waker.wake()
+        }
+    }
+
+    // forget the waker of a dropped duplicate.
+    pub fn remove(&self, index: usize) {
+        self.0.lock().unwrap().remove(&index);
+    }
 }
 
 pub struct DuplicateEventWaker {
@@ -31,10 +45,12 @@ impl Wake for DuplicateEventWaker {
     fn wake(self: Arc<Self>) {
         if let Some(event_wakers) = self.event_wakers.upgrade() {
             let mut event_wakers_ref = event_wakers.lock().unwrap();
-            for (_, waker) in event_wakers_ref.split_off(&0).into_iter() {
+            let event_wakers = core::mem::take(&mut *event_wakers_ref);
+            drop(event_wakers_ref);
+
+            for (_, waker) in event_wakers.into_iter() {
                 waker.wake()
             }
-            drop(event_wakers_ref);
         }
     }
 }
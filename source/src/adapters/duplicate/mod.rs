@@ -1,5 +1,4 @@
 use core::num::NonZeroUsize;
-use core::pin::Pin;
 use std::sync::Arc;
 use std::task::Waker;
 
@@ -10,10 +9,19 @@ mod duplicate_waker;
 mod original;
 mod rollback_event;
 
+#[cfg(test)]
+mod test;
+
 use self::duplicate_inner::DuplicateInner;
+use crate::source_poll::TrySourcePoll;
 use crate::traits::SourceContext;
-use crate::{Source, SourcePoll};
+use crate::Source;
 
+/// A source which can be cloned, so one source can feed any number of consumers.
+///
+/// Each clone sees every event, rollback, and finalize of the original, in order,
+/// no matter which clone the original emitted it to.
+/// Clones are given disjoint ranges of the original's channels, so their polls never interfere.
 pub struct Duplicate<Src: Source>
 where
     Src::Event: Clone,
@@ -56,46 +64,49 @@ where
     type Error = Src::Error;
 
     fn poll(
-        self: Pin<&mut Self>,
+        &mut self,
         poll_time: Self::Time,
         cx: SourceContext,
-    ) -> SourcePoll<Self::Time, Self::Event, Self::State, Src::Error> {
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
         self.inner.poll(poll_time, cx, Src::poll)
     }
 
     fn poll_forget(
-        self: Pin<&mut Self>,
+        &mut self,
         poll_time: Self::Time,
         cx: SourceContext,
-    ) -> SourcePoll<Self::Time, Self::Event, Self::State, Src::Error> {
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
         self.inner.poll(poll_time, cx, Src::poll_forget)
     }
 
     fn poll_events(
-        self: Pin<&mut Self>,
+        &mut self,
         poll_time: Self::Time,
         all_channel_waker: Waker,
-    ) -> SourcePoll<Self::Time, Self::Event, (), Src::Error> {
-        self.inner.poll(poll_time, cx, Src::poll_events)
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        self.inner.poll_events(poll_time, all_channel_waker)
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        self.inner.release_channel(channel)
     }
 
-    fn advance(self: Pin<&mut Self>, time: Src::Time) {
+    fn advance(&mut self, time: Src::Time) {
         self.inner.advance(time)
     }
 
     fn max_channel(&self) -> NonZeroUsize {
         self.inner.max_channel()
     }
-
-    fn release_channel(self: Pin<&mut Self>, _channel: usize) {
-        todo!()
-    }
 }
 
-pub type PollFn<Src, State> =
-    fn(
-        Pin<&mut Src>,
-        <Src as Source>::Time,
-        SourceContext,
-    )
-        -> SourcePoll<<Src as Source>::Time, <Src as Source>::Event, State, <Src as Source>::Error>;
+pub type PollFn<Src, State> = fn(
+    &mut Src,
+    <Src as Source>::Time,
+    SourceContext,
+) -> TrySourcePoll<
+    <Src as Source>::Time,
+    <Src as Source>::Event,
+    State,
+    <Src as Source>::Error,
+>;
@@ -1,23 +1,20 @@
 use core::num::NonZeroUsize;
-use core::pin::Pin;
-use core::task::{Poll, Waker};
+use core::task::Waker;
 use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex, RwLock, Weak};
 
 use super::advanced::Advanced;
 use super::duplicate_inner::DuplicateInner;
 use super::duplicate_waker::EventWakers;
-use super::PollFn;
 use crate::adapters::duplicate::rollback_event::RollbackEvent;
-use crate::source_poll::SourcePollOk;
-use crate::traits::SourceContext;
+use crate::source_poll::TrySourcePoll;
 use crate::{Source, SourcePoll};
 
 pub struct Original<Src: Source>
 where
     Src::Event: Clone,
 {
-    pub source: Mutex<Pin<Box<Src>>>,
+    pub source: Mutex<Src>,
     children:   RwLock<BTreeMap<usize, Weak<DuplicateInner<Src>>>>,
     wakers:     EventWakers,
     advanced:   Mutex<Advanced<Src::Time>>,
@@ -29,7 +26,7 @@ where
 {
     pub fn new(source: Src) -> Arc<Self> {
         let original = Original {
-            source:   Mutex::new(Box::pin(source)),
+            source:   Mutex::new(source),
             children: RwLock::new(BTreeMap::new()),
             wakers:   EventWakers::new(),
             advanced: Mutex::new(Advanced::new()),
@@ -53,10 +50,31 @@ where
         self.advanced.lock().unwrap().register_new_duplicate(index);
     }
 
+    // forget a dropped duplicate, releasing the channels it was still using,
+    // and advancing the source if the duplicate was the one holding it back.
+    pub fn unregister_child(&self, index: usize, channels: impl IntoIterator<Item = usize>) {
+        self.children.write().unwrap().remove(&index);
+        self.wakers.remove(index);
+
+        let advance = self.advanced.lock().unwrap().unregister_duplicate(index);
+
+        let mut source_lock = self.source.lock().unwrap();
+        for channel in channels {
+            source_lock.release_channel(channel);
+        }
+        if let Some(t) = advance {
+            source_lock.advance(t);
+        }
+    }
+
     pub fn max_channel(&self) -> NonZeroUsize {
         self.source.lock().unwrap().max_channel()
     }
 
+    pub fn release_channel(&self, channel: usize) {
+        self.source.lock().unwrap().release_channel(channel)
+    }
+
     pub fn get_new_waker(&self, index: usize, event_waker: Waker) -> Waker {
         self.wakers.get_new_waker(index, event_waker)
     }
@@ -64,85 +82,97 @@ where
     pub fn advance(&self, time: Src::Time, index: usize) {
         if let Some(t) = self.advanced.lock().unwrap().advance(time, index) {
             let mut source_lock = self.source.lock().unwrap();
-            source_lock.as_mut().advance(t);
+            source_lock.advance(t);
         }
     }
 
-    pub fn poll<State>(
-        self: &Arc<Self>,
+    pub fn poll<State, F>(
+        &self,
+        from: &DuplicateInner<Src>,
         poll_time: Src::Time,
-        cx: SourceContext,
-        poll_fn: PollFn<Src, State>,
-        from_index: usize,
-    ) -> SourcePoll<Src::Time, Src::Event, State, Src::Error> {
+        poll_fn: F,
+    ) -> TrySourcePoll<Src::Time, Src::Event, State, Src::Error>
+    where
+        F: FnOnce(&mut Src) -> TrySourcePoll<Src::Time, Src::Event, State, Src::Error>,
+    {
+        // everything happens under the source lock, so no duplicate can see the source
+        // emit something newer than what is waiting in its own queue.
         let mut source_lock = self.source.lock().unwrap();
-        let source = source_lock.as_mut();
-        let poll = poll_fn(source, poll_time, cx);
-
-        // Immediately delete all stored events at or after t.
-        if let Poll::Ready(Ok(SourcePollOk::Rollback(t))) = &poll {
-            let children_lock = self.children.read().unwrap();
-            for (_, dup) in children_lock.iter() {
-                if let Some(dup) = dup.upgrade() {
-                    dup.handle_rollback(*t)
-                }
-            }
+
+        // emit previously emitted stuff if we have any.
+        if let Some(event) = from.pop_previously_emitted(poll_time) {
+            return Ok(event.into())
         }
 
-        // Now we can release the lock. Don't move this up.
+        let poll = poll_fn(&mut source_lock)?;
+
+        let (time, interrupt) = match poll {
+            SourcePoll::Ready {
+                state,
+                next_event_at,
+            } => {
+                // account for events which are waiting to be re-emitted.
+                let next_event_at = match (next_event_at, from.first_stored_time()) {
+                    (Some(t1), Some(t2)) => Some(t1.min(t2)),
+                    (t1, t2) => t1.or(t2),
+                };
+
+                return Ok(SourcePoll::Ready {
+                    state,
+                    next_event_at,
+                })
+            },
+            SourcePoll::Interrupt {
+                time,
+                interrupt,
+            } => (time, interrupt),
+            SourcePoll::Pending => return Ok(SourcePoll::Pending),
+        };
+
+        let children = self.distribute_event(RollbackEvent::new(time, &interrupt), from.index);
+
+        // a duplicate dropped while we were handing it the event unregisters itself when the last
+        // reference goes away, which needs the source lock.
         drop(source_lock);
+        drop(children);
 
-        match poll {
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
-            Poll::Ready(Ok(poll_ok)) => Poll::Ready(Ok(match poll_ok {
-                SourcePollOk::Rollback(t) => {
-                    let rollback_event = RollbackEvent::Rollback {
-                        time: t
-                    };
-                    self.distribute_event(rollback_event, from_index);
-
-                    SourcePollOk::Rollback(t)
-                },
-                SourcePollOk::Event(e, t) => {
-                    let rollback_event = RollbackEvent::Event {
-                        event: e.clone(),
-                        time:  t,
-                    };
-                    self.distribute_event(rollback_event, from_index);
-
-                    SourcePollOk::Event(e, t)
-                },
-                SourcePollOk::Finalize(t) => {
-                    let rollback_event = RollbackEvent::Finalize {
-                        time: t
-                    };
-                    self.distribute_event(rollback_event, from_index);
-
-                    SourcePollOk::Finalize(t)
-                },
-                SourcePollOk::Scheduled(s, t) => SourcePollOk::Scheduled(s, t),
-                SourcePollOk::Ready(s) => SourcePollOk::Ready(s),
-            })),
-        }
+        Ok(SourcePoll::Interrupt {
+            time,
+            interrupt,
+        })
     }
 
+    // hand the event to every duplicate, returning the ones it was handed to.
     fn distribute_event(
         &self,
         rollback_event: RollbackEvent<Src::Time, Src::Event>,
         from_index: usize,
-    ) {
+    ) -> Vec<Arc<DuplicateInner<Src>>> {
         let rollback_event = Arc::new(rollback_event);
 
-        let children_lock = self.children.read().unwrap();
-
-        // send it to all but the from_index
-        for (i, dup) in children_lock.iter() {
-            if *i != from_index {
-                if let Some(dup) = dup.upgrade() {
-                    dup.insert_rollback_event(rollback_event.clone());
+        let children: Vec<_> = self
+            .children
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|(i, dup)| Some((*i, dup.upgrade()?)))
+            .collect();
+
+        for (i, dup) in children.iter() {
+            if *i == from_index {
+                // the one we are emitting to still needs to throw away events it hasn't seen yet.
+                if let RollbackEvent::Rollback {
+                    time,
+                } = *rollback_event
+                {
+                    dup.handle_rollback(time);
                 }
+            } else {
+                dup.insert_rollback_event(rollback_event.clone());
+                self.wakers.wake(*i);
             }
         }
+
+        children.into_iter().map(|(_, dup)| dup).collect()
     }
 }
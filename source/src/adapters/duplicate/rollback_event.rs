@@ -1,96 +1,79 @@
-use core::cmp::Ordering;
-
-use crate::source_poll::SourcePollOk;
+use crate::source_poll::Interrupt;
+use crate::SourcePoll;
 
 #[derive(Clone)]
 pub enum RollbackEvent<Time: Ord + Copy, Event> {
     Event { time: Time, event: Event },
+    FinalizedEvent { time: Time, event: Event },
     Rollback { time: Time },
     Finalize { time: Time },
-
-    // never stored; used for searching.
-    Search { time: Time },
 }
 
 impl<Time: Ord + Copy, Event> RollbackEvent<Time, Event> {
-    fn time(&self) -> &Time {
+    pub fn new(time: Time, interrupt: &Interrupt<Event>) -> Self
+    where
+        Event: Clone,
+    {
+        match interrupt {
+            Interrupt::Event(event) => Self::Event {
+                time,
+                event: event.clone(),
+            },
+            Interrupt::FinalizedEvent(event) => Self::FinalizedEvent {
+                time,
+                event: event.clone(),
+            },
+            Interrupt::Rollback => Self::Rollback {
+                time,
+            },
+            Interrupt::Finalize => Self::Finalize {
+                time,
+            },
+        }
+    }
+
+    pub fn time(&self) -> Time {
         match self {
             Self::Event {
                 time, ..
-            } => time,
+            } => *time,
+            Self::FinalizedEvent {
+                time, ..
+            } => *time,
             Self::Rollback {
                 time,
-            } => time,
-            Self::Search {
-                time,
-            } => time,
+            } => *time,
             Self::Finalize {
                 time,
-            } => time,
+            } => *time,
         }
     }
 }
 
-impl<Time: Ord + Copy, Event> Ord for RollbackEvent<Time, Event> {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self, other) {
-            (
-                Self::Rollback {
-                    time: s,
-                },
-                Self::Rollback {
-                    time: o,
-                },
-            ) => s.cmp(o),
-            (
-                _,
-                Self::Rollback {
-                    ..
-                },
-            ) => Ordering::Greater,
-            (
-                Self::Rollback {
-                    ..
-                },
-                _,
-            ) => Ordering::Less,
-            (s, o) => s.time().cmp(o.time()),
-        }
-    }
-}
-
-impl<Time: Ord + Copy, Event> PartialOrd for RollbackEvent<Time, Event> {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl<Time: Ord + Copy, Event> Eq for RollbackEvent<Time, Event> {}
-
-impl<Time: Ord + Copy, Event> PartialEq for RollbackEvent<Time, Event> {
-    fn eq(&self, other: &Self) -> bool {
-        matches!(self.cmp(other), Ordering::Equal)
-    }
-}
-
 impl<Time: Ord + Copy, Event, State> From<RollbackEvent<Time, Event>>
-    for SourcePollOk<Time, Event, State>
+    for SourcePoll<Time, Event, State>
 {
     fn from(rollback_event: RollbackEvent<Time, Event>) -> Self {
-        match rollback_event {
+        let (time, interrupt) = match rollback_event {
             RollbackEvent::Event {
                 time,
                 event,
-            } => SourcePollOk::Event(event, time),
+            } => (time, Interrupt::Event(event)),
+            RollbackEvent::FinalizedEvent {
+                time,
+                event,
+            } => (time, Interrupt::FinalizedEvent(event)),
             RollbackEvent::Rollback {
                 time,
-            } => SourcePollOk::Rollback(time),
+            } => (time, Interrupt::Rollback),
             RollbackEvent::Finalize {
                 time,
-            } => SourcePollOk::Finalize(time),
-            RollbackEvent::Search {
-                ..
-            } => unreachable!(),
+            } => (time, Interrupt::Finalize),
+        };
+
+        SourcePoll::Interrupt {
+            time,
+            interrupt,
         }
     }
 }
@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+
+use super::channel_map;
+use crate::source_poll::Interrupt;
+use crate::sources::transposer::multi_input_transposer::test::{cx, TestSource};
+use crate::traits::SourceExt;
+use crate::{Source, SourcePoll};
+
+#[derive(Debug, PartialEq, Eq)]
+enum Polled {
+    Event(usize, usize),
+    Rollback(usize),
+    Ready(usize),
+}
+
+// poll at time until a state is ready, recording every interrupt.
+fn poll_until_ready<S>(source: &mut S, time: usize) -> Vec<Polled>
+where
+    S: Source<Time = usize, Event = usize, State = usize>,
+{
+    let mut polled = Vec::new();
+    loop {
+        match source.poll(time, cx(0)).ok().unwrap() {
+            SourcePoll::Ready {
+                state, ..
+            } => {
+                polled.push(Polled::Ready(state));
+                return polled
+            },
+            SourcePoll::Interrupt {
+                time,
+                interrupt: Interrupt::Event(e),
+            } => polled.push(Polled::Event(time, e)),
+            SourcePoll::Interrupt {
+                time,
+                interrupt: Interrupt::Rollback,
+            } => polled.push(Polled::Rollback(time)),
+            _ => panic!(),
+        }
+    }
+}
+
+#[test]
+fn duplicates_see_every_event_test() {
+    let source = TestSource::default();
+    source.insert(1, 10);
+    source.insert(3, 30);
+
+    let mut a = source.duplicate();
+    let mut b = a.clone();
+
+    assert_eq!(poll_until_ready(&mut a, 1), vec![
+        Polled::Event(1, 10),
+        Polled::Ready(1)
+    ]);
+
+    // b gets the event a pulled from the source, then pulls the next one itself.
+    assert_eq!(poll_until_ready(&mut b, 5), vec![
+        Polled::Event(1, 10),
+        Polled::Event(3, 30),
+        Polled::Ready(2)
+    ]);
+
+    assert_eq!(poll_until_ready(&mut a, 5), vec![
+        Polled::Event(3, 30),
+        Polled::Ready(2)
+    ]);
+}
+
+#[test]
+fn duplicates_see_rollbacks_test() {
+    let source = TestSource::default();
+    source.insert(1, 10);
+    source.insert(3, 30);
+
+    let mut a = source.clone().duplicate();
+    let mut b = a.clone();
+
+    poll_until_ready(&mut a, 5);
+    assert_eq!(poll_until_ready(&mut b, 1), vec![
+        Polled::Event(1, 10),
+        Polled::Ready(1)
+    ]);
+
+    source.insert(2, 20);
+
+    assert_eq!(poll_until_ready(&mut a, 5), vec![
+        Polled::Rollback(2),
+        Polled::Event(2, 20),
+        Polled::Event(3, 30),
+        Polled::Ready(3)
+    ]);
+
+    // the stale event at 3 b never emitted is thrown away.
+    assert_eq!(poll_until_ready(&mut b, 5), vec![
+        Polled::Rollback(2),
+        Polled::Event(2, 20),
+        Polled::Event(3, 30),
+        Polled::Ready(3)
+    ]);
+}
+
+#[test]
+fn dropped_duplicate_test() {
+    let source = TestSource::default();
+    source.insert(1, 10);
+
+    let mut a = source.clone().duplicate();
+    let mut b = a.clone();
+
+    poll_until_ready(&mut a, 1);
+    poll_until_ready(&mut b, 5);
+
+    // a hasn't advanced, so it holds the source back.
+    b.advance(5);
+    assert_eq!(source.0.borrow().advanced, None);
+
+    drop(a);
+    assert_eq!(source.0.borrow().advanced, Some(5));
+    assert_eq!(source.0.borrow().released, vec![channel_map::map(0, 0)]);
+
+    // b still works on its own.
+    assert_eq!(poll_until_ready(&mut b, 6), vec![Polled::Ready(1)]);
+}
+
+#[test]
+fn channel_map_disjoint_test() {
+    for channels in 1..200 {
+        let channels = NonZeroUsize::new(channels).unwrap();
+        let mut used = HashSet::new();
+
+        let max_duplicates = channel_map::max_duplicates(channels.get());
+        let max_channel =
+            std::panic::catch_unwind(|| channel_map::max_channel(channels, max_duplicates));
+        assert!(max_channel.is_err());
+
+        for duplicate in 0..max_duplicates {
+            let max_channel = channel_map::max_channel(channels, duplicate);
+
+            for channel in 0..=max_channel.get() {
+                let mapped = channel_map::map(duplicate, channel);
+                assert!(mapped <= channels.get());
+                assert!(used.insert(mapped));
+            }
+        }
+    }
+}
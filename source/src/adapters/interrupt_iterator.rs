@@ -1,13 +1,19 @@
-use std::pin::{pin, Pin};
-use std::task::{Context, Poll};
+use std::sync::Arc;
+use std::task::{Wake, Waker};
+use std::thread::{self, Thread};
 use std::time::Instant;
 
-use crate::source_poll::Interrupt;
-use crate::Source;
+use crate::source_poll::{Interrupt, SourcePollErr};
+use crate::{Source, SourcePoll};
 
+/// A blocking version of [`InterruptStream`](super::interrupt_stream::InterruptStream).
+///
+/// `next` parks the current thread until the next interrupt of the source is due, or the source wakes it.
+/// The iterator ends once the source has nothing left scheduled, or returns an error, which can be retrieved with [`take_error`](Self::take_error).
 pub struct InterruptIterator<Src: Source<Time = Instant>> {
     source:               Box<Src>,
     current_polling_time: Option<Instant>,
+    error:                Option<SourcePollErr<Instant, Src::Error>>,
 }
 
 impl<Src: Source<Time = Instant>> InterruptIterator<Src> {
@@ -15,44 +21,155 @@ impl<Src: Source<Time = Instant>> InterruptIterator<Src> {
         Self {
             source:               Box::new(source),
             current_polling_time: None,
+            error:                None,
         }
     }
+
+    /// The error which ended the iterator, if any.
+    pub fn take_error(&mut self) -> Option<SourcePollErr<Instant, Src::Error>> {
+        self.error.take()
+    }
 }
 
 impl<Src: Source<Time = Instant>> Iterator for InterruptIterator<Src> {
     type Item = (Instant, Interrupt<Src::Event>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let poll_time = self.current_polling_time.unwrap_or_else(Instant::now);
-        let poll = this.source.poll_events(poll_time, cx.waker().clone());
-
-        let poll = match poll {
-            Ok(poll) => poll,
-            Err(_) => panic!(),
-        };
-
-        let next_event_at = match poll {
-            crate::SourcePoll::Ready {
-                state: _,
-                next_event_at,
-            } => next_event_at,
-            crate::SourcePoll::Interrupt {
-                time,
-                interrupt,
-            } => return Poll::Ready(Some((time, interrupt))),
-            crate::SourcePoll::Pending => return Poll::Pending,
-        };
-
-        let next_event_at = match next_event_at {
-            Some(next) => next,
-            None => return Poll::Pending,
-        };
-
-        let wait_fut = (this.wait_fn)(next_event_at);
-
-        match pin!(wait_fut).poll(cx) {
-            Poll::Ready(()) => Pin::new(this).poll_next(cx),
-            Poll::Pending => Poll::Pending,
+        if self.error.is_some() {
+            return None
         }
+
+        let waker: Waker = Arc::new(ThreadWaker(thread::current())).into();
+
+        loop {
+            // pending polls must be retried at the same time.
+            let poll_time = self.current_polling_time.take().unwrap_or_else(|| {
+                let now = Instant::now();
+                self.source.advance(now);
+                now
+            });
+
+            let next_event_at = match self.source.poll_events(poll_time, waker.clone()) {
+                Err(err) => {
+                    self.error = Some(err);
+                    return None
+                },
+                Ok(SourcePoll::Interrupt {
+                    time,
+                    interrupt,
+                }) => return Some((time, interrupt)),
+                Ok(SourcePoll::Pending) => {
+                    self.current_polling_time = Some(poll_time);
+                    thread::park();
+                    continue
+                },
+                Ok(SourcePoll::Ready {
+                    next_event_at, ..
+                }) => next_event_at?,
+            };
+
+            // parking can end early, either spuriously or because the source woke us, so just poll again.
+            if let Some(timeout) = next_event_at.checked_duration_since(Instant::now()) {
+                thread::park_timeout(timeout);
+            }
+        }
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroUsize;
+    use std::task::Waker;
+    use std::time::{Duration, Instant};
+
+    use super::InterruptIterator;
+    use crate::source_poll::{Interrupt, TrySourcePoll};
+    use crate::traits::SourceContext;
+    use crate::{Source, SourcePoll};
+
+    // emits each of its events once, as finalized events.
+    struct ScriptedSource {
+        events: Vec<Instant>,
+    }
+
+    impl Source for ScriptedSource {
+        type Time = Instant;
+
+        type Event = usize;
+
+        type State = ();
+
+        type Error = ();
+
+        fn poll(
+            &mut self,
+            time: Self::Time,
+            cx: SourceContext,
+        ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+            self.poll_events(time, cx.all_channel_waker)
+        }
+
+        fn poll_events(
+            &mut self,
+            time: Self::Time,
+            _all_channel_waker: Waker,
+        ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+            Ok(match self.events.first() {
+                Some(event_time) if *event_time <= time => SourcePoll::Interrupt {
+                    time:      self.events.remove(0),
+                    interrupt: Interrupt::FinalizedEvent(self.events.len()),
+                },
+                next => SourcePoll::Ready {
+                    state:         (),
+                    next_event_at: next.copied(),
+                },
+            })
+        }
+
+        fn release_channel(&mut self, _channel: usize) {}
+
+        fn advance(&mut self, _time: Self::Time) {}
+
+        fn max_channel(&self) -> NonZeroUsize {
+            NonZeroUsize::MIN
+        }
+    }
+
+    #[test]
+    fn blocks_until_events_test() {
+        let start = Instant::now();
+        let events = vec![
+            start - Duration::from_secs(1),
+            start,
+            start + Duration::from_millis(20),
+        ];
+
+        let mut iter = InterruptIterator::new(ScriptedSource {
+            events: events.clone(),
+        });
+
+        let mut yielded = Vec::new();
+        for (time, interrupt) in &mut iter {
+            assert!(Instant::now() >= time);
+            match interrupt {
+                Interrupt::FinalizedEvent(remaining) => yielded.push((time, remaining)),
+                _ => panic!(),
+            }
+        }
+
+        assert_eq!(yielded, vec![
+            (events[0], 2),
+            (events[1], 1),
+            (events[2], 0)
+        ]);
+        assert!(iter.take_error().is_none());
     }
 }
@@ -0,0 +1,188 @@
+use std::num::NonZeroUsize;
+use std::task::Waker;
+
+use self::side::{Side, SidePoll};
+use crate::source_poll::{Interrupt, TrySourcePoll};
+use crate::traits::SourceContext;
+use crate::{Source, SourcePoll};
+
+mod side;
+
+#[cfg(test)]
+mod test;
+
+type JoinPoll<A, B, S> = TrySourcePoll<
+    <A as Source>::Time,
+    Either<<A as Source>::Event, <B as Source>::Event>,
+    S,
+    Either<<A as Source>::Error, <B as Source>::Error>,
+>;
+
+/// One of two things, from either the first or the second source of a [`Join`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// A source which combines two sources with the same time.
+///
+/// The states are paired, and the events of both are merged in time order, with ties going to the left source.
+/// An interrupt is only passed on once the other source has been polled past it,
+/// so if either source is pending, the join is pending.
+/// When one source rolls back, the events of the other which the rollback discarded are passed on again.
+pub struct Join<A: Source, B: Source<Time = A::Time>> {
+    left:  Side<A>,
+    right: Side<B>,
+}
+
+impl<A: Source, B: Source<Time = A::Time>> Join<A, B> {
+    pub fn new(left: A, right: B) -> Self {
+        Self {
+            left:  Side::new(left),
+            right: Side::new(right),
+        }
+    }
+}
+
+impl<A: Source, B: Source<Time = A::Time>> Join<A, B>
+where
+    A::Event: Clone,
+    B::Event: Clone,
+{
+    fn poll_internal<SA, SB>(
+        &mut self,
+        time: A::Time,
+        mut poll_left: impl FnMut(&mut A) -> TrySourcePoll<A::Time, A::Event, SA, A::Error>,
+        mut poll_right: impl FnMut(&mut B) -> TrySourcePoll<A::Time, B::Event, SB, B::Error>,
+    ) -> JoinPoll<A, B, (SA, SB)> {
+        let left = self
+            .left
+            .poll(time, &mut poll_left)
+            .map_err(|err| err.map_err(Either::Left))?;
+        let right = self
+            .right
+            .poll(time, &mut poll_right)
+            .map_err(|err| err.map_err(Either::Right))?;
+
+        let take_left = match (left, right) {
+            (SidePoll::Pending, _) | (_, SidePoll::Pending) => return Ok(SourcePoll::Pending),
+            (
+                SidePoll::Ready {
+                    state: left_state,
+                    next_event_at: left_next,
+                },
+                SidePoll::Ready {
+                    state: right_state,
+                    next_event_at: right_next,
+                },
+            ) => {
+                let next_event_at = match (left_next, right_next) {
+                    (Some(l), Some(r)) => Some(l.min(r)),
+                    (l, r) => l.or(r),
+                };
+
+                return Ok(SourcePoll::Ready {
+                    state: (left_state, right_state),
+                    next_event_at,
+                })
+            },
+            (SidePoll::Interrupt(l), SidePoll::Interrupt(r)) => l <= r,
+            (SidePoll::Interrupt(_), _) => true,
+            _ => false,
+        };
+
+        let (time, interrupt) = if take_left {
+            let (time, interrupt) = self.left.pop();
+            if let Interrupt::Rollback = interrupt {
+                self.right.replay(time);
+            }
+            (time, interrupt.map(Either::Left))
+        } else {
+            let (time, interrupt) = self.right.pop();
+            if let Interrupt::Rollback = interrupt {
+                self.left.replay(time);
+            }
+            (time, interrupt.map(Either::Right))
+        };
+
+        if let (Some(left), Some(right)) = (self.left.finalized, self.right.finalized) {
+            let finalized = left.min(right);
+            self.left.finalize(finalized);
+            self.right.finalize(finalized);
+        }
+
+        Ok(SourcePoll::Interrupt {
+            time,
+            interrupt,
+        })
+    }
+}
+
+impl<A: Source, B: Source<Time = A::Time>> Source for Join<A, B>
+where
+    A::Event: Clone,
+    B::Event: Clone,
+{
+    type Time = A::Time;
+
+    type Event = Either<A::Event, B::Event>;
+
+    type State = (A::State, B::State);
+
+    type Error = Either<A::Error, B::Error>;
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_internal(
+            time,
+            |left| left.poll(time, cx.clone()),
+            |right| right.poll(time, cx.clone()),
+        )
+    }
+
+    fn poll_forget(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_internal(
+            time,
+            |left| left.poll_forget(time, cx.clone()),
+            |right| right.poll_forget(time, cx.clone()),
+        )
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        self.poll_internal(
+            time,
+            |left| left.poll_events(time, all_channel_waker.clone()),
+            |right| right.poll_events(time, all_channel_waker.clone()),
+        )
+        .map(|poll| poll.map_state(|((), ())| ()))
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        self.left.source.release_channel(channel);
+        self.right.source.release_channel(channel);
+    }
+
+    fn advance(&mut self, time: Self::Time) {
+        self.left.source.advance(time);
+        self.right.source.advance(time);
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        self.left
+            .source
+            .max_channel()
+            .min(self.right.source.max_channel())
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+
+use crate::source_poll::{Interrupt, SourcePollErr, TrySourcePoll};
+use crate::{Source, SourcePoll};
+
+// one of the joined sources, along with the interrupts it has emitted which haven't been passed on yet.
+pub struct Side<Src: Source> {
+    pub source:     Src,
+    pub interrupts: VecDeque<(Src::Time, Interrupt<Src::Event>)>,
+
+    // the events which have been passed on since both sides were finalized,
+    // which have to be passed on again if the other side rolls back past them.
+    history:       VecDeque<(Src::Time, Src::Event)>,
+    pub finalized: Option<Src::Time>,
+}
+
+pub type SideResult<Src, S> = Result<
+    SidePoll<<Src as Source>::Time, S>,
+    SourcePollErr<<Src as Source>::Time, <Src as Source>::Error>,
+>;
+
+pub enum SidePoll<T, S> {
+    Ready {
+        state:         S,
+        next_event_at: Option<T>,
+    },
+    // the next interrupt in the queue is due, at this time.
+    Interrupt(T),
+    Pending,
+}
+
+impl<Src: Source> Side<Src> {
+    pub fn new(source: Src) -> Self {
+        Self {
+            source,
+            interrupts: VecDeque::new(),
+            history: VecDeque::new(),
+            finalized: None,
+        }
+    }
+
+    pub fn poll<S>(
+        &mut self,
+        time: Src::Time,
+        mut poll_fn: impl FnMut(&mut Src) -> TrySourcePoll<Src::Time, Src::Event, S, Src::Error>,
+    ) -> SideResult<Src, S> {
+        loop {
+            if let Some((t, _)) = self.interrupts.front() {
+                if *t <= time {
+                    return Ok(SidePoll::Interrupt(*t))
+                }
+            }
+
+            match poll_fn(&mut self.source)? {
+                SourcePoll::Ready {
+                    state,
+                    next_event_at,
+                } => {
+                    // queued interrupts are still to come.
+                    let queued_at = self.interrupts.front().map(|(t, _)| *t);
+                    let next_event_at = match (next_event_at, queued_at) {
+                        (Some(a), Some(b)) => Some(a.min(b)),
+                        (a, b) => a.or(b),
+                    };
+
+                    return Ok(SidePoll::Ready {
+                        state,
+                        next_event_at,
+                    })
+                },
+                SourcePoll::Interrupt {
+                    time,
+                    interrupt,
+                } => self.push(time, interrupt),
+                SourcePoll::Pending => return Ok(SidePoll::Pending),
+            }
+        }
+    }
+
+    fn push(&mut self, time: Src::Time, interrupt: Interrupt<Src::Event>) {
+        // a rollback discards the queued interrupts it covers, which were never seen downstream.
+        if let Interrupt::Rollback = interrupt {
+            self.interrupts.retain(|(t, _)| *t < time);
+        }
+
+        self.interrupts.push_back((time, interrupt));
+    }
+
+    // forget the history before `time`, once neither side can roll back past it.
+    pub fn finalize(&mut self, time: Src::Time) {
+        let kept = self.history.partition_point(|(t, _)| *t < time);
+        self.history.drain(..kept);
+    }
+}
+
+impl<Src: Source> Side<Src>
+where
+    Src::Event: Clone,
+{
+    pub fn pop(&mut self) -> (Src::Time, Interrupt<Src::Event>) {
+        let (time, interrupt) = self.interrupts.pop_front().unwrap();
+
+        match &interrupt {
+            Interrupt::Event(event) => self.history.push_back((time, event.clone())),
+            Interrupt::FinalizedEvent(event) => {
+                self.history.push_back((time, event.clone()));
+                self.finalized = Some(time);
+            },
+            Interrupt::Rollback => self.history.retain(|(t, _)| *t < time),
+            Interrupt::Finalize => self.finalized = Some(time),
+        }
+
+        (time, interrupt)
+    }
+
+    // the other side rolled back to `time`, which discarded the events passed on at or after it downstream.
+    pub fn replay(&mut self, time: Src::Time) {
+        let kept = self.history.partition_point(|(t, _)| *t < time);
+
+        for (t, event) in self.history.split_off(kept).into_iter().rev() {
+            self.interrupts.push_front((t, Interrupt::Event(event)));
+        }
+    }
+}
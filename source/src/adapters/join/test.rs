@@ -0,0 +1,80 @@
+use super::Either;
+use crate::source_poll::Interrupt;
+use crate::sources::transposer::multi_input_transposer::test::{cx, TestSource};
+use crate::traits::SourceExt;
+use crate::{Source, SourcePoll};
+
+type Polled<S> = (
+    <S as Source>::State,
+    Option<usize>,
+    Vec<(usize, Interrupt<<S as Source>::Event>)>,
+);
+
+// poll at `time` until ready, recording every interrupt.
+fn poll_until_ready<S: Source<Time = usize>>(source: &mut S, time: usize) -> Polled<S> {
+    let mut interrupts = Vec::new();
+    loop {
+        match source.poll(time, cx(0)).ok().unwrap() {
+            SourcePoll::Ready {
+                state,
+                next_event_at,
+            } => return (state, next_event_at, interrupts),
+            SourcePoll::Interrupt {
+                time,
+                interrupt,
+            } => interrupts.push((time, interrupt)),
+            SourcePoll::Pending => panic!(),
+        }
+    }
+}
+
+fn events<E: Clone>(interrupts: &[(usize, Interrupt<E>)]) -> Vec<(usize, Option<E>)> {
+    interrupts
+        .iter()
+        .map(|(time, interrupt)| match interrupt {
+            Interrupt::Event(e) => (*time, Some(e.clone())),
+            Interrupt::Rollback => (*time, None),
+            _ => panic!(),
+        })
+        .collect()
+}
+
+#[test]
+fn join_merges_in_time_order_test() {
+    let left = TestSource::default();
+    left.insert(1, 10);
+    left.insert(4, 40);
+    left.insert(9, 90);
+
+    let right = TestSource::default();
+    right.insert(2, 20);
+    right.insert(3, 30);
+    right.insert(7, 70);
+
+    let mut join = left.clone().join(right.clone());
+    assert_eq!(join.max_channel().get(), 4);
+
+    let (state, next_event_at, interrupts) = poll_until_ready(&mut join, 5);
+    assert_eq!(state, (2, 2));
+    assert_eq!(next_event_at, Some(7));
+    assert_eq!(events(&interrupts), vec![
+        (1, Some(Either::Left(10))),
+        (2, Some(Either::Right(20))),
+        (3, Some(Either::Right(30))),
+        (4, Some(Either::Left(40))),
+    ]);
+
+    // a rollback on either side comes through in order, and the other side's discarded events are passed on again.
+    right.insert(1, 15);
+    let (state, next_event_at, interrupts) = poll_until_ready(&mut join, 5);
+    assert_eq!(state, (2, 3));
+    assert_eq!(next_event_at, Some(7));
+    assert_eq!(events(&interrupts), vec![
+        (1, None),
+        (1, Some(Either::Left(10))),
+        (1, Some(Either::Right(15))),
+        (2, Some(Either::Right(20))),
+        (3, Some(Either::Right(30))),
+        (4, Some(Either::Left(40))),
+    ]);
+}
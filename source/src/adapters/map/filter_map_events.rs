@@ -0,0 +1,124 @@
+use std::num::NonZeroUsize;
+use std::task::Waker;
+
+use crate::source_poll::{Interrupt, TrySourcePoll};
+use crate::traits::SourceContext;
+use crate::{Source, SourcePoll};
+
+/// A source which maps the events of another source, discarding the ones mapped to `None`.
+///
+/// A discarded [`FinalizedEvent`](Interrupt::FinalizedEvent) still finalizes its time, so it becomes a
+/// [`Finalize`](Interrupt::Finalize). Discarded [`Event`](Interrupt::Event)s are skipped by polling the source again,
+/// so the `next_event_at` after them is still reported.
+pub struct FilterMapEvents<Src, F> {
+    source: Src,
+    f:      F,
+}
+
+impl<Src, F> FilterMapEvents<Src, F> {
+    pub fn new(source: Src, f: F) -> Self {
+        Self {
+            source,
+            f,
+        }
+    }
+}
+
+impl<Src, F, E> FilterMapEvents<Src, F>
+where
+    Src: Source,
+    F: FnMut(Src::Event) -> Option<E>,
+{
+    // keep polling until something survives the filter.
+    fn filter_poll<S>(
+        &mut self,
+        mut poll_fn: impl FnMut(&mut Src) -> TrySourcePoll<Src::Time, Src::Event, S, Src::Error>,
+    ) -> TrySourcePoll<Src::Time, E, S, Src::Error> {
+        loop {
+            let (time, interrupt) = match poll_fn(&mut self.source)? {
+                SourcePoll::Interrupt {
+                    time,
+                    interrupt,
+                } => (time, interrupt),
+                SourcePoll::Ready {
+                    state,
+                    next_event_at,
+                } => {
+                    return Ok(SourcePoll::Ready {
+                        state,
+                        next_event_at,
+                    })
+                },
+                SourcePoll::Pending => return Ok(SourcePoll::Pending),
+            };
+
+            let interrupt = match interrupt {
+                Interrupt::Event(e) => match (self.f)(e) {
+                    Some(e) => Interrupt::Event(e),
+                    None => continue,
+                },
+                Interrupt::FinalizedEvent(e) => match (self.f)(e) {
+                    Some(e) => Interrupt::FinalizedEvent(e),
+                    None => Interrupt::Finalize,
+                },
+                Interrupt::Rollback => Interrupt::Rollback,
+                Interrupt::Finalize => Interrupt::Finalize,
+            };
+
+            return Ok(SourcePoll::Interrupt {
+                time,
+                interrupt,
+            })
+        }
+    }
+}
+
+impl<Src, F, E> Source for FilterMapEvents<Src, F>
+where
+    Src: Source,
+    F: FnMut(Src::Event) -> Option<E>,
+{
+    type Time = Src::Time;
+
+    type Event = E;
+
+    type State = Src::State;
+
+    type Error = Src::Error;
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.filter_poll(|source| source.poll(time, cx.clone()))
+    }
+
+    fn poll_forget(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.filter_poll(|source| source.poll_forget(time, cx.clone()))
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        self.filter_poll(|source| source.poll_events(time, all_channel_waker.clone()))
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        self.source.release_channel(channel)
+    }
+
+    fn advance(&mut self, time: Self::Time) {
+        self.source.advance(time)
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        self.source.max_channel()
+    }
+}
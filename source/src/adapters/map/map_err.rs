@@ -0,0 +1,79 @@
+use std::num::NonZeroUsize;
+use std::task::Waker;
+
+use crate::source_poll::TrySourcePoll;
+use crate::traits::SourceContext;
+use crate::Source;
+
+/// A source which maps the custom errors of another source.
+///
+/// Only [`SpecificError`](crate::source_poll::SourcePollErr::SpecificError)s are mapped. The other errors are passed through.
+pub struct MapErr<Src, F> {
+    source: Src,
+    f:      F,
+}
+
+impl<Src, F> MapErr<Src, F> {
+    pub fn new(source: Src, f: F) -> Self {
+        Self {
+            source,
+            f,
+        }
+    }
+}
+
+impl<Src, F, E> Source for MapErr<Src, F>
+where
+    Src: Source,
+    F: FnMut(Src::Error) -> E,
+{
+    type Time = Src::Time;
+
+    type Event = Src::Event;
+
+    type State = Src::State;
+
+    type Error = E;
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.source
+            .poll(time, cx)
+            .map_err(|err| err.map_err(&mut self.f))
+    }
+
+    fn poll_forget(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.source
+            .poll_forget(time, cx)
+            .map_err(|err| err.map_err(&mut self.f))
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        self.source
+            .poll_events(time, all_channel_waker)
+            .map_err(|err| err.map_err(&mut self.f))
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        self.source.release_channel(channel)
+    }
+
+    fn advance(&mut self, time: Self::Time) {
+        self.source.advance(time)
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        self.source.max_channel()
+    }
+}
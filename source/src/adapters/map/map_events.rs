@@ -0,0 +1,80 @@
+use std::num::NonZeroUsize;
+use std::task::Waker;
+
+use crate::source_poll::TrySourcePoll;
+use crate::traits::SourceContext;
+use crate::Source;
+
+/// A source which maps every event of another source.
+///
+/// Both [`Event`](crate::source_poll::Interrupt::Event)s and
+/// [`FinalizedEvent`](crate::source_poll::Interrupt::FinalizedEvent)s are mapped. Everything else is passed through.
+pub struct MapEvents<Src, F> {
+    source: Src,
+    f:      F,
+}
+
+impl<Src, F> MapEvents<Src, F> {
+    pub fn new(source: Src, f: F) -> Self {
+        Self {
+            source,
+            f,
+        }
+    }
+}
+
+impl<Src, F, E> Source for MapEvents<Src, F>
+where
+    Src: Source,
+    F: FnMut(Src::Event) -> E,
+{
+    type Time = Src::Time;
+
+    type Event = E;
+
+    type State = Src::State;
+
+    type Error = Src::Error;
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.source
+            .poll(time, cx)
+            .map(|poll| poll.map_event(&mut self.f))
+    }
+
+    fn poll_forget(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.source
+            .poll_forget(time, cx)
+            .map(|poll| poll.map_event(&mut self.f))
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        self.source
+            .poll_events(time, all_channel_waker)
+            .map(|poll| poll.map_event(&mut self.f))
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        self.source.release_channel(channel)
+    }
+
+    fn advance(&mut self, time: Self::Time) {
+        self.source.advance(time)
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        self.source.max_channel()
+    }
+}
@@ -0,0 +1,77 @@
+use std::num::NonZeroUsize;
+use std::task::Waker;
+
+use crate::source_poll::TrySourcePoll;
+use crate::traits::SourceContext;
+use crate::Source;
+
+/// A source which maps every state of another source.
+///
+/// The function is only called for states which are actually returned, so `poll_events` never calls it.
+pub struct MapState<Src, F> {
+    source: Src,
+    f:      F,
+}
+
+impl<Src, F> MapState<Src, F> {
+    pub fn new(source: Src, f: F) -> Self {
+        Self {
+            source,
+            f,
+        }
+    }
+}
+
+impl<Src, F, S> Source for MapState<Src, F>
+where
+    Src: Source,
+    F: FnMut(Src::State) -> S,
+{
+    type Time = Src::Time;
+
+    type Event = Src::Event;
+
+    type State = S;
+
+    type Error = Src::Error;
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.source
+            .poll(time, cx)
+            .map(|poll| poll.map_state(&mut self.f))
+    }
+
+    fn poll_forget(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.source
+            .poll_forget(time, cx)
+            .map(|poll| poll.map_state(&mut self.f))
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        self.source.poll_events(time, all_channel_waker)
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        self.source.release_channel(channel)
+    }
+
+    fn advance(&mut self, time: Self::Time) {
+        self.source.advance(time)
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        self.source.max_channel()
+    }
+}
@@ -0,0 +1,12 @@
+pub use self::filter_map_events::FilterMapEvents;
+pub use self::map_err::MapErr;
+pub use self::map_events::MapEvents;
+pub use self::map_state::MapState;
+
+mod filter_map_events;
+mod map_err;
+mod map_events;
+mod map_state;
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,151 @@
+use util::dummy_waker::DummyWaker;
+
+use crate::source_poll::{Interrupt, SourcePollErr};
+use crate::sources::transposer::multi_input_transposer::test::{cx, TestSource};
+use crate::traits::SourceExt;
+use crate::{Source, SourcePoll};
+
+#[test]
+fn map_events_and_state_test() {
+    let source = TestSource::default();
+    source.insert(1, 10);
+    source.insert(3, 20);
+
+    let mut mapped = source
+        .clone()
+        .map_events(|e| e * 2)
+        .map_state(|s| format!("{s} events"));
+
+    assert!(matches!(
+        mapped.poll(5, cx(0)).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      1,
+            interrupt: Interrupt::Event(20),
+        }
+    ));
+    assert!(matches!(
+        mapped.poll(5, cx(0)).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      3,
+            interrupt: Interrupt::Event(40),
+        }
+    ));
+
+    // rollbacks pass through untouched.
+    source.insert(2, 30);
+    assert!(matches!(
+        mapped.poll(5, cx(0)).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      2,
+            interrupt: Interrupt::Rollback,
+        }
+    ));
+    assert!(matches!(
+        mapped.poll(5, cx(0)).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      2,
+            interrupt: Interrupt::Event(60),
+        }
+    ));
+    assert!(matches!(
+        mapped.poll(5, cx(0)).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      3,
+            interrupt: Interrupt::Event(40),
+        }
+    ));
+    match mapped.poll(5, cx(0)).ok().unwrap() {
+        SourcePoll::Ready {
+            state,
+            next_event_at,
+        } => {
+            assert_eq!(state, "3 events");
+            assert_eq!(next_event_at, None);
+        },
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn filter_map_events_test() {
+    let source = TestSource::default();
+    source.insert(1, 11);
+    source.insert(2, 12);
+    source.insert(6, 13);
+    source.insert(8, 14);
+
+    let mut filtered = source
+        .clone()
+        .filter_map_events(|e| (e % 2 == 0).then_some(e / 2));
+
+    // the odd event at 1 is skipped, without hiding the event at 2.
+    assert!(matches!(
+        filtered.poll_events(4, DummyWaker::dummy()).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      2,
+            interrupt: Interrupt::Event(6),
+        }
+    ));
+
+    // nor the time of the event at 8.
+    assert!(matches!(
+        filtered.poll_events(4, DummyWaker::dummy()).ok().unwrap(),
+        SourcePoll::Ready {
+            next_event_at: Some(6),
+            ..
+        }
+    ));
+    assert!(matches!(
+        filtered.poll_events(7, DummyWaker::dummy()).ok().unwrap(),
+        SourcePoll::Ready {
+            next_event_at: Some(8),
+            ..
+        }
+    ));
+
+    // discarded finalized events still finalize.
+    source.0.borrow_mut().finalized = true;
+    source.insert(9, 15);
+    assert!(matches!(
+        filtered.poll_events(10, DummyWaker::dummy()).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      8,
+            interrupt: Interrupt::FinalizedEvent(7),
+        }
+    ));
+    assert!(matches!(
+        filtered.poll_events(10, DummyWaker::dummy()).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      9,
+            interrupt: Interrupt::Finalize,
+        }
+    ));
+}
+
+#[test]
+fn map_err_test() {
+    let err: SourcePollErr<usize, usize> = SourcePollErr::SpecificError(3);
+    assert!(matches!(
+        err.map_err(|e| e + 1),
+        SourcePollErr::SpecificError(4)
+    ));
+
+    let err: SourcePollErr<usize, usize> = SourcePollErr::PollAfterAdvance {
+        advanced: 5
+    };
+    assert!(matches!(
+        err.map_err(|e| e + 1),
+        SourcePollErr::PollAfterAdvance {
+            advanced: 5
+        }
+    ));
+
+    let mut mapped = TestSource::default().map_err(|()| "error");
+    assert!(matches!(
+        mapped.poll(5, cx(0)).ok().unwrap(),
+        SourcePoll::Ready {
+            state: 0,
+            ..
+        }
+    ));
+}
@@ -1,13 +1,25 @@
-// mod duplicate;
-// mod offload;
-// mod multiplex;
-// mod transpose;
-// mod concurrent;
-// pub mod interrupt_iterator;
+mod concurrent;
+mod duplicate;
+pub mod interrupt_iterator;
 pub mod interrupt_stream;
+mod join;
+mod map;
+mod multiplex;
+mod offload;
+mod realtime;
+#[cfg(feature = "serde")]
+mod record;
+mod state_hashes;
+mod transpose;
 
-// pub use self::duplicate::Duplicate;
-// pub use self::concurrent::MutexSource;
-// pub use self::multiplex::Multiplex;
-// pub use self::offload::{offload, OffloadFuture, OffloadSource};
-// pub use self::transpose::Transpose;
+pub use self::concurrent::MutexSource;
+pub use self::duplicate::Duplicate;
+pub use self::join::{Either, Join};
+pub use self::map::{FilterMapEvents, MapErr, MapEvents, MapState};
+pub use self::multiplex::Multiplex;
+pub use self::offload::{offload, OffloadFuture, OffloadSource};
+pub use self::realtime::{realtime, RealtimeEvents, RealtimeStates};
+#[cfg(feature = "serde")]
+pub use self::record::{Record, RecordError};
+pub use self::state_hashes::{HashedEvent, StateHashes};
+pub use self::transpose::{Transpose, TransposeStorage};
@@ -20,7 +20,7 @@ impl AffinityMap {
         match self.output_channels.get(&channel) {
             Some(src_channel) => Some(*src_channel),
             None => {
-                if channel < self.max_src_channels.into() {
+                if channel <= self.max_src_channels.into() {
                     Some(channel)
                 } else {
                     None
@@ -36,4 +36,8 @@ impl AffinityMap {
             self.output_channels.insert(out_channel, src_channel);
         }
     }
+
+    pub fn clear_affiliation(&mut self, out_channel: OutChannelID) {
+        self.output_channels.remove(&out_channel);
+    }
 }
@@ -20,7 +20,6 @@ pub struct Assignment<Time: Copy> {
 pub enum PollType {
     Normal,
     Forget,
-    Events,
 }
 
 impl<Time: Copy> AssignmentMap<Time> {
@@ -35,7 +34,7 @@ impl<Time: Copy> AssignmentMap<Time> {
     pub fn get_unassigned_source_channel(&self) -> Option<SrcChannelID> {
         let mut channel: SrcChannelID = 0;
         loop {
-            if channel >= self.max_src_channels.into() {
+            if channel > self.max_src_channels.into() {
                 break None
             }
 
@@ -53,15 +52,21 @@ impl<Time: Copy> AssignmentMap<Time> {
     }
 
     pub fn get_assigned_output_channel(&self, channel: SrcChannelID) -> Option<OutChannelID> {
-        match self.source_channels.get(&channel) {
-            Some(out_channel) => Some(*out_channel),
-            None => Some(channel),
-        }
+        self.source_channels.get(&channel).copied()
     }
 
     pub fn assign(&mut self, out_channel: OutChannelID, assignment: Assignment<Time>) {
-        self.source_channels
+        // the source channel might be handed over from another output channel.
+        let prev_out_channel = self
+            .source_channels
             .insert(assignment.source_channel, out_channel);
+
+        if let Some(prev_out_channel) = prev_out_channel {
+            if prev_out_channel != out_channel {
+                self.output_channels.remove(&prev_out_channel);
+            }
+        }
+
         self.output_channels.insert(out_channel, assignment);
     }
 
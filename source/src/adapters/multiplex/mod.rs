@@ -1,13 +1,13 @@
 mod affinity_map;
 mod assignment_map;
 
+#[cfg(test)]
+mod test;
+
 use core::num::NonZeroUsize;
-use core::pin::Pin;
-use core::task::{Poll, Waker};
+use core::task::Waker;
 use std::collections::VecDeque;
 
-use pin_project::pin_project;
-
 use self::affinity_map::AffinityMap;
 use self::assignment_map::AssignmentMap;
 use crate::adapters::multiplex::assignment_map::{Assignment, PollType};
@@ -19,9 +19,12 @@ type AsyncWaker = Waker;
 type OutChannelID = usize;
 type SrcChannelID = usize;
 
-#[pin_project]
+/// A source which can be polled on any channel, by sharing the channels of a source which supports only a few.
+///
+/// Each output channel keeps the source channel it was assigned for as long as its poll is pending.
+/// When every source channel is taken, polls wait in line and are woken in order as channels free up.
+/// Output channels go back to the source channel they used last when they can, so progress is reused.
 pub struct Multiplex<Src: Source> {
-    #[pin]
     source:            Src,
     assigned_channels: AssignmentMap<Src::Time>,
     channel_affinity:  AffinityMap,
@@ -40,7 +43,7 @@ impl<Src: Source> Multiplex<Src> {
     }
 
     fn poll_internal<F, S>(
-        self: Pin<&mut Self>,
+        &mut self,
         poll_time: Src::Time,
         mut cx: SourceContext,
         poll_fn: F,
@@ -48,64 +51,56 @@ impl<Src: Source> Multiplex<Src> {
     ) -> TrySourcePoll<Src::Time, Src::Event, S, Src::Error>
     where
         F: Fn(
-            Pin<&mut Src>,
+            &mut Src,
             Src::Time,
             SourceContext,
         ) -> TrySourcePoll<Src::Time, Src::Event, S, Src::Error>,
     {
-        let this = self.project();
-        let source: Pin<&mut Src> = this.source;
-        let assigned_channels: &mut AssignmentMap<Src::Time> = this.assigned_channels;
-        let channel_affinity: &mut AffinityMap = this.channel_affinity;
-        let pending_channels: &mut VecDeque<PendingPoll<Src::Time>> = this.pending_channels;
-
         let out_channel = cx.channel;
         // step one: check for existing assignment, use it or clear it.
-        match assigned_channels.get_assigned_source_channel(out_channel) {
+        match self
+            .assigned_channels
+            .get_assigned_source_channel(out_channel)
+        {
             Some(assignment) => {
                 // full match, use existing assignment
                 if assignment.poll_type == poll_type && assignment.time == poll_time {
                     cx.change_channel(assignment.source_channel);
-                    let result = poll_fn(source, poll_time, cx);
+                    let result = poll_fn(&mut self.source, poll_time, cx);
 
                     // if we're done with the channel assign it to the next pending and wake it.
-                    if result.is_ready() {
-                        if let Some(pending) = pending_channels.pop_front() {
-                            assigned_channels.assign(pending.out_channel, Assignment {
-                                poll_type:      pending.poll_type,
-                                time:           pending.time,
-                                source_channel: assignment.source_channel,
-                            });
-                            pending.waker.wake();
-                        } else {
-                            assigned_channels.unassign(assignment.source_channel);
-                        }
+                    if !is_pending(&result) {
+                        self.free_source_channel(assignment.source_channel);
                     }
                     result
                 // partial match, only use existing assignment if nothing is queued
                 } else {
-                    match pending_channels.pop_front() {
+                    match self.pending_channels.pop_front() {
                         Some(pending) => {
-                            pending.assign_to_channel(assigned_channels, assignment.source_channel);
+                            pending.assign_to_channel(
+                                &mut self.assigned_channels,
+                                assignment.source_channel,
+                            );
 
-                            let new_pending = PendingPoll {
+                            self.enqueue(PendingPoll {
                                 poll_type,
                                 time: poll_time,
                                 out_channel,
                                 waker: cx.one_channel_waker.clone(),
-                            };
-                            pending_channels.push_back(new_pending);
-                            Poll::Pending
+                            });
+                            Ok(SourcePoll::Pending)
                         },
                         None => {
                             cx.change_channel(assignment.source_channel);
-                            let result = poll_fn(source, poll_time, cx);
-                            if result.is_pending() {
-                                assigned_channels.assign(out_channel, Assignment {
+                            let result = poll_fn(&mut self.source, poll_time, cx);
+                            if is_pending(&result) {
+                                self.assigned_channels.assign(out_channel, Assignment {
                                     poll_type,
                                     time: poll_time,
                                     source_channel: assignment.source_channel,
                                 })
+                            } else {
+                                self.assigned_channels.unassign(assignment.source_channel);
                             }
                             result
                         },
@@ -117,9 +112,12 @@ impl<Src: Source> Multiplex<Src> {
                 let mut already_affiliated = false;
 
                 // use affiliated channel if open
-                if let Some(affinity) = channel_affinity.get_affiliated_source_channel(out_channel)
+                if let Some(affinity) = self
+                    .channel_affinity
+                    .get_affiliated_source_channel(out_channel)
                 {
-                    if assigned_channels
+                    if self
+                        .assigned_channels
                         .get_assigned_output_channel(affinity)
                         .is_none()
                     {
@@ -130,19 +128,20 @@ impl<Src: Source> Multiplex<Src> {
 
                 // use any open channel
                 if channel.is_none() {
-                    channel = assigned_channels.get_unassigned_source_channel();
+                    channel = self.assigned_channels.get_unassigned_source_channel();
                 }
 
                 match channel {
                     // poll; affiliate; assign if pending
                     Some(source_channel) => {
                         if !already_affiliated {
-                            channel_affinity.set_affiliation(source_channel, out_channel);
+                            self.channel_affinity
+                                .set_affiliation(source_channel, out_channel);
                         }
                         cx.change_channel(source_channel);
-                        let result = poll_fn(source, poll_time, cx);
-                        if result.is_pending() {
-                            assigned_channels.assign(out_channel, Assignment {
+                        let result = poll_fn(&mut self.source, poll_time, cx);
+                        if is_pending(&result) {
+                            self.assigned_channels.assign(out_channel, Assignment {
                                 poll_type,
                                 time: poll_time,
                                 source_channel,
@@ -152,25 +151,41 @@ impl<Src: Source> Multiplex<Src> {
                     },
                     // enqueue call and return pending
                     None => {
-                        let new_pending = PendingPoll {
+                        self.enqueue(PendingPoll {
                             poll_type,
                             time: poll_time,
                             out_channel,
                             waker: cx.one_channel_waker.clone(),
-                        };
-                        for pending in pending_channels.iter_mut() {
-                            if pending.out_channel == out_channel {
-                                *pending = new_pending;
-                                return SourcePoll::Pending
-                            }
-                        }
-                        pending_channels.push_back(new_pending);
-                        Poll::Pending
+                        });
+                        Ok(SourcePoll::Pending)
                     },
                 }
             },
         }
     }
+
+    // replace the pending poll of the same output channel, so it keeps its place in line.
+    fn enqueue(&mut self, new_pending: PendingPoll<Src::Time>) {
+        for pending in self.pending_channels.iter_mut() {
+            if pending.out_channel == new_pending.out_channel {
+                *pending = new_pending;
+                return
+            }
+        }
+        self.pending_channels.push_back(new_pending);
+    }
+
+    // hand the source channel to the next pending poll, if there is one.
+    fn free_source_channel(&mut self, source_channel: SrcChannelID) {
+        match self.pending_channels.pop_front() {
+            Some(pending) => pending.assign_to_channel(&mut self.assigned_channels, source_channel),
+            None => self.assigned_channels.unassign(source_channel),
+        }
+    }
+}
+
+fn is_pending<T, E, S, Err>(result: &TrySourcePoll<T, E, S, Err>) -> bool {
+    matches!(result, Ok(SourcePoll::Pending))
 }
 
 struct PendingPoll<Time> {
@@ -205,7 +220,7 @@ impl<Src: Source> Source for Multiplex<Src> {
     type Error = Src::Error;
 
     fn poll(
-        self: Pin<&mut Self>,
+        &mut self,
         time: Self::Time,
         cx: SourceContext,
     ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Src::Error> {
@@ -213,7 +228,7 @@ impl<Src: Source> Source for Multiplex<Src> {
     }
 
     fn poll_forget(
-        self: Pin<&mut Self>,
+        &mut self,
         time: Self::Time,
         cx: SourceContext,
     ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Src::Error> {
@@ -221,23 +236,47 @@ impl<Src: Source> Source for Multiplex<Src> {
     }
 
     fn poll_events(
-        self: Pin<&mut Self>,
+        &mut self,
         time: Self::Time,
         all_channel_waker: Waker,
     ) -> TrySourcePoll<Self::Time, Self::Event, (), Src::Error> {
-        self.project().source.poll_events(time, all_channel_waker)
+        self.source.poll_events(time, all_channel_waker)
     }
 
-    fn advance(self: Pin<&mut Self>, time: Self::Time) {
-        self.project().source.advance(time)
+    fn release_channel(&mut self, channel: usize) {
+        // a released channel is no longer waiting for anything.
+        self.pending_channels
+            .retain(|pending| pending.out_channel != channel);
+
+        let affinity = self.channel_affinity.get_affiliated_source_channel(channel);
+        self.channel_affinity.clear_affiliation(channel);
+
+        match self.assigned_channels.get_assigned_source_channel(channel) {
+            Some(assignment) => {
+                self.source.release_channel(assignment.source_channel);
+                self.free_source_channel(assignment.source_channel);
+            },
+            None => {
+                // the source may still be holding progress from this channel's last poll.
+                if let Some(source_channel) = affinity {
+                    if self
+                        .assigned_channels
+                        .get_assigned_output_channel(source_channel)
+                        .is_none()
+                    {
+                        self.source.release_channel(source_channel);
+                    }
+                }
+            },
+        }
+    }
+
+    fn advance(&mut self, time: Self::Time) {
+        self.source.advance(time)
     }
 
     fn max_channel(&self) -> NonZeroUsize {
         // SAFETY: usize::MAX is not 0.
         NonZeroUsize::new(usize::MAX).unwrap()
     }
-
-    fn release_channel(self: Pin<&mut Self>, _channel: usize) {
-        todo!()
-    }
 }
@@ -0,0 +1,144 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Wake, Waker};
+
+use util::dummy_waker::DummyWaker;
+
+use crate::source_poll::TrySourcePoll;
+use crate::traits::{SourceContext, SourceExt};
+use crate::{Source, SourcePoll};
+
+// a source with channels 0 and 1, where polls stay pending until their channel is marked ready.
+#[derive(Default)]
+struct ChannelSourceInner {
+    ready:    HashSet<usize>,
+    polled:   Vec<usize>,
+    released: Vec<usize>,
+}
+
+#[derive(Clone, Default)]
+struct ChannelSource(Rc<RefCell<ChannelSourceInner>>);
+
+impl Source for ChannelSource {
+    type Time = usize;
+
+    type Event = ();
+
+    type State = usize;
+
+    type Error = ();
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        let mut inner = self.0.borrow_mut();
+        inner.polled.push(cx.channel);
+
+        Ok(if inner.ready.contains(&cx.channel) {
+            SourcePoll::Ready {
+                state:         time,
+                next_event_at: None,
+            }
+        } else {
+            SourcePoll::Pending
+        })
+    }
+
+    fn poll_events(
+        &mut self,
+        _time: Self::Time,
+        _all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        Ok(SourcePoll::Ready {
+            state:         (),
+            next_event_at: None,
+        })
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        self.0.borrow_mut().released.push(channel);
+    }
+
+    fn advance(&mut self, _time: Self::Time) {}
+
+    fn max_channel(&self) -> NonZeroUsize {
+        NonZeroUsize::new(1).unwrap()
+    }
+}
+
+#[derive(Default)]
+struct FlagWaker(AtomicBool);
+
+impl Wake for FlagWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+fn cx(channel: usize, waker: &Arc<FlagWaker>) -> SourceContext {
+    SourceContext {
+        channel,
+        one_channel_waker: Waker::from(waker.clone()),
+        all_channel_waker: DummyWaker::dummy(),
+    }
+}
+
+fn is_pending<S>(poll: TrySourcePoll<usize, (), S, ()>) -> bool {
+    matches!(poll.ok().unwrap(), SourcePoll::Pending)
+}
+
+#[test]
+fn release_wakes_next_pending_test() {
+    let source = ChannelSource::default();
+    let mut multiplex = source.clone().multiplex();
+    let waker = Arc::new(FlagWaker::default());
+
+    assert!(is_pending(multiplex.poll(1, cx(10, &waker))));
+    assert!(is_pending(multiplex.poll(1, cx(11, &waker))));
+    assert_eq!(source.0.borrow().polled, vec![0, 1]);
+
+    // no source channels left, so this waits in line without touching the source.
+    let waker_12 = Arc::new(FlagWaker::default());
+    assert!(is_pending(multiplex.poll(1, cx(12, &waker_12))));
+    assert_eq!(source.0.borrow().polled, vec![0, 1]);
+
+    multiplex.release_channel(10);
+    assert_eq!(source.0.borrow().released, vec![0]);
+    assert!(waker_12.0.load(Ordering::SeqCst));
+
+    assert!(is_pending(multiplex.poll(1, cx(12, &waker_12))));
+    assert_eq!(source.0.borrow().polled, vec![0, 1, 0]);
+
+    // finishing a poll frees its source channel for the next caller.
+    source.0.borrow_mut().ready.insert(1);
+    assert!(!is_pending(multiplex.poll(1, cx(11, &waker))));
+    assert!(!is_pending(multiplex.poll(2, cx(13, &waker))));
+    assert_eq!(source.0.borrow().polled, vec![0, 1, 0, 1, 1]);
+}
+
+#[test]
+fn pending_polls_are_fair_test() {
+    let source = ChannelSource::default();
+    let mut multiplex = source.clone().multiplex();
+    let waker = Arc::new(FlagWaker::default());
+
+    assert!(is_pending(multiplex.poll(1, cx(10, &waker))));
+    assert!(is_pending(multiplex.poll(1, cx(11, &waker))));
+
+    let waker_12 = Arc::new(FlagWaker::default());
+    assert!(is_pending(multiplex.poll(1, cx(12, &waker_12))));
+
+    // polling a different time gives the channel to the caller which has been waiting.
+    assert!(is_pending(multiplex.poll(2, cx(10, &waker))));
+    assert!(waker_12.0.load(Ordering::SeqCst));
+    assert_eq!(source.0.borrow().polled, vec![0, 1]);
+
+    assert!(is_pending(multiplex.poll(1, cx(12, &waker_12))));
+    assert_eq!(source.0.borrow().polled, vec![0, 1, 0]);
+}
@@ -1,9 +1,28 @@
-use self::offload_source::OffloadSource;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+pub use self::offload_future::OffloadFuture;
+pub use self::offload_source::OffloadSource;
+use self::shared::Shared;
 use crate::Source;
 
 mod offload_future;
 mod offload_source;
+mod shared;
+
+#[cfg(test)]
+mod test;
+
+/// Split a source into a handle, and a future which does the work of the source.
+///
+/// The future can be spawned on any executor, so expensive polls don't block the thread using the handle.
+pub fn offload<Src: Source>(source: Src) -> (OffloadSource<Src>, OffloadFuture<Src>) {
+    let max_channel = source.max_channel();
+    let shared = Arc::new(Mutex::new(Shared::new()));
+
+    let handle = OffloadSource::new(shared.clone(), max_channel);
+    let future = OffloadFuture::new(source, shared);
 
-pub fn offload<Src: Source>(source: Src) -> (OffloadSource<Src>, OffloadSource<Src>) {
-    unimplemented!()
+    (handle, future)
 }
@@ -1,18 +1,152 @@
 use core::pin::Pin;
 use core::task::{Context, Poll};
+use std::sync::Arc;
 
 use futures_core::Future;
+use parking_lot::Mutex;
 
-use crate::Source;
+use super::shared::{split_poll, Shared};
+use crate::traits::SourceContext;
+use crate::{Source, SourcePoll};
 
+/// The half of [`offload`](super::offload) which runs the source.
+///
+/// Spawn this on any executor. It completes once the [`OffloadSource`](super::OffloadSource) is dropped.
 pub struct OffloadFuture<Src: Source> {
     source: Src,
+    shared: Arc<Mutex<Shared<Src>>>,
 }
 
+impl<Src: Source> OffloadFuture<Src> {
+    pub(super) fn new(source: Src, shared: Arc<Mutex<Shared<Src>>>) -> Self {
+        Self {
+            source,
+            shared,
+        }
+    }
+}
+
+// the source is never pinned, only borrowed mutably.
+impl<Src: Source> Unpin for OffloadFuture<Src> {}
+
 impl<Src: Source> Future for OffloadFuture<Src> {
     type Output = ();
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        unimplemented!()
+        let this = self.get_mut();
+        let waker = cx.waker();
+
+        // collect the work to do, so the lock isn't held while the source runs.
+        let mut shared = this.shared.lock();
+        if shared.closed {
+            return Poll::Ready(())
+        }
+
+        shared.future_waker = Some(waker.clone());
+        let woken_by_source = !core::mem::take(&mut shared.requested);
+        let advance = shared.advance.take();
+        let released = core::mem::take(&mut shared.released);
+        let channels: Vec<_> = shared
+            .channels
+            .iter()
+            .filter(|(_, request)| request.result.is_none())
+            .map(|(channel, request)| (*channel, request.time, request.forget))
+            .collect();
+        let events = shared
+            .events
+            .as_ref()
+            .filter(|request| request.result.is_none())
+            .map(|request| request.time);
+        drop(shared);
+
+        if let Some(time) = advance {
+            this.source.advance(time);
+        }
+
+        for channel in released {
+            this.source.release_channel(channel);
+        }
+
+        // the source wakes this task, which polls it again on behalf of the handle.
+        let mut state_results = Vec::new();
+        for (channel, time, forget) in channels {
+            let source_cx = SourceContext {
+                channel,
+                one_channel_waker: waker.clone(),
+                all_channel_waker: waker.clone(),
+            };
+
+            let result = if forget {
+                this.source.poll_forget(time, source_cx)
+            } else {
+                this.source.poll(time, source_cx)
+            };
+
+            if !matches!(result, Ok(SourcePoll::Pending)) {
+                state_results.push((channel, time, forget, result));
+            }
+        }
+
+        let events_result = events
+            .map(|time| (time, this.source.poll_events(time, waker.clone())))
+            .filter(|(_, result)| !matches!(result, Ok(SourcePoll::Pending)));
+
+        // hand the states back, unless the handle asked for something else in the meantime.
+        // interrupts are handed out regardless, because the source won't emit them again.
+        let mut shared = this.shared.lock();
+        let mut emitted = false;
+        for (channel, time, forget, result) in state_results {
+            let request = shared
+                .channels
+                .get_mut(&channel)
+                .filter(|request| request.matches(time, forget) && request.result.is_none());
+
+            match split_poll::<Src, _>(result) {
+                Ok(poll) => {
+                    if let Some(request) = request {
+                        request.result = Some(Ok(poll));
+                        request.waker.wake_by_ref();
+                    }
+                },
+                Err(interrupt) => {
+                    if request.is_some() {
+                        shared.channels.remove(&channel).unwrap().waker.wake();
+                    }
+                    shared.emitted.push_back(interrupt);
+                    emitted = true;
+                },
+            }
+        }
+
+        if let Some((time, result)) = events_result {
+            let request = shared
+                .events
+                .as_mut()
+                .filter(|request| request.matches(time, true) && request.result.is_none());
+
+            match split_poll::<Src, _>(result) {
+                Ok(poll) => {
+                    if let Some(request) = request {
+                        request.result = Some(Ok(poll));
+                        request.waker.wake_by_ref();
+                    }
+                },
+                Err(interrupt) => {
+                    if request.is_some() {
+                        shared.events.take().unwrap().waker.wake();
+                    }
+                    shared.emitted.push_back(interrupt);
+                    emitted = true;
+                },
+            }
+        }
+
+        if woken_by_source || emitted {
+            if let Some(all_channel_waker) = shared.all_channel_waker.take() {
+                all_channel_waker.wake()
+            }
+        }
+
+        Poll::Pending
     }
 }
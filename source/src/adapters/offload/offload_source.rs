@@ -1,11 +1,65 @@
-use core::marker::PhantomData;
-use core::pin::Pin;
+use core::num::NonZeroUsize;
+use core::task::Waker;
+use std::sync::Arc;
 
+use parking_lot::Mutex;
+
+use super::shared::{Request, Shared, StatePoll};
+use crate::source_poll::TrySourcePoll;
 use crate::traits::SourceContext;
-use crate::Source;
+use crate::{Source, SourcePoll};
 
+/// The handle half of [`offload`](super::offload).
+///
+/// Every request is forwarded to the [`OffloadFuture`](super::OffloadFuture),
+/// so polls return `Pending` until the future has had a chance to run the source.
+/// Interrupts are handed out by the next poll on any channel, even if the request which produced them has changed.
+/// This is `Send` whenever the times, events, states and errors of the source are.
+/// The source itself doesn't need to be `Send`, because it stays with the future.
 pub struct OffloadSource<Src: Source> {
-    phantom: PhantomData<Src>,
+    shared:      Arc<Mutex<Shared<Src>>>,
+    max_channel: NonZeroUsize,
+}
+
+impl<Src: Source> OffloadSource<Src> {
+    pub(super) fn new(shared: Arc<Mutex<Shared<Src>>>, max_channel: NonZeroUsize) -> Self {
+        Self {
+            shared,
+            max_channel,
+        }
+    }
+
+    fn poll_internal(
+        &mut self,
+        time: Src::Time,
+        cx: SourceContext,
+        forget: bool,
+    ) -> StatePoll<Src> {
+        let mut shared = self.shared.lock();
+        shared.all_channel_waker = Some(cx.all_channel_waker);
+
+        if let Some(emitted) = shared.pop_emitted() {
+            return emitted
+        }
+
+        if let Some(request) = shared.channels.get_mut(&cx.channel) {
+            if request.matches(time, forget) {
+                if let Some(result) = request.result.take() {
+                    shared.channels.remove(&cx.channel);
+                    return result
+                }
+
+                request.waker = cx.one_channel_waker;
+                return Ok(SourcePoll::Pending)
+            }
+        }
+
+        let request = Request::new(time, forget, cx.one_channel_waker);
+        shared.channels.insert(cx.channel, request);
+        shared.wake_future();
+
+        Ok(SourcePoll::Pending)
+    }
 }
 
 impl<Src: Source> Source for OffloadSource<Src> {
@@ -18,18 +72,76 @@ impl<Src: Source> Source for OffloadSource<Src> {
     type Error = Src::Error;
 
     fn poll(
-        self: Pin<&mut Self>,
+        &mut self,
         time: Self::Time,
         cx: SourceContext,
-    ) -> crate::SourcePoll<Self::Time, Self::Event, Self::State, Src::Error> {
-        unimplemented!()
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_internal(time, cx, false)
     }
 
-    fn advance(self: Pin<&mut Self>, time: Self::Time) {
-        todo!()
+    fn poll_forget(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_internal(time, cx, true)
     }
 
-    fn max_channel(&self) -> std::num::NonZeroUsize {
-        todo!()
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        let mut shared = self.shared.lock();
+        shared.all_channel_waker = Some(all_channel_waker.clone());
+
+        if let Some(emitted) = shared.pop_emitted() {
+            return emitted
+        }
+
+        if let Some(request) = &mut shared.events {
+            if request.matches(time, true) {
+                if let Some(result) = request.result.take() {
+                    shared.events = None;
+                    return result
+                }
+
+                request.waker = all_channel_waker;
+                return Ok(SourcePoll::Pending)
+            }
+        }
+
+        shared.events = Some(Request::new(time, true, all_channel_waker));
+        shared.wake_future();
+
+        Ok(SourcePoll::Pending)
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        let mut shared = self.shared.lock();
+        shared.channels.remove(&channel);
+        shared.released.push(channel);
+        shared.wake_future();
+    }
+
+    fn advance(&mut self, time: Self::Time) {
+        let mut shared = self.shared.lock();
+        shared.advance = Some(match shared.advance {
+            Some(t) => t.max(time),
+            None => time,
+        });
+        shared.wake_future();
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        self.max_channel
+    }
+}
+
+impl<Src: Source> Drop for OffloadSource<Src> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock();
+        shared.closed = true;
+        shared.wake_future();
     }
 }
@@ -0,0 +1,109 @@
+use core::task::Waker;
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::source_poll::{Interrupt, SourcePollErr, TrySourcePoll};
+use crate::{Source, SourcePoll};
+
+pub type Poll<Src, S> =
+    TrySourcePoll<<Src as Source>::Time, <Src as Source>::Event, S, <Src as Source>::Error>;
+
+pub type StatePoll<Src> = Poll<Src, <Src as Source>::State>;
+
+pub type EventsPoll<Src> = Poll<Src, ()>;
+
+// an interrupt or error from the source, which goes to whichever channel polls next.
+pub type Emitted<Src> = Result<
+    (<Src as Source>::Time, Interrupt<<Src as Source>::Event>),
+    SourcePollErr<<Src as Source>::Time, <Src as Source>::Error>,
+>;
+
+// split off the interrupts and errors, which can't be tied to the request that produced them.
+pub fn split_poll<Src: Source, S>(
+    poll: Poll<Src, S>,
+) -> Result<SourcePoll<Src::Time, Src::Event, S>, Emitted<Src>> {
+    match poll {
+        Ok(SourcePoll::Interrupt {
+            time,
+            interrupt,
+        }) => Err(Ok((time, interrupt))),
+        Ok(poll) => Ok(poll),
+        Err(err) => Err(Err(err)),
+    }
+}
+
+// a poll the handle is waiting on. the result is filled in by the future.
+pub struct Request<Time, P> {
+    pub time:   Time,
+    pub forget: bool,
+    pub waker:  Waker,
+    pub result: Option<P>,
+}
+
+impl<Time: Ord + Copy, P> Request<Time, P> {
+    pub fn new(time: Time, forget: bool, waker: Waker) -> Self {
+        Self {
+            time,
+            forget,
+            waker,
+            result: None,
+        }
+    }
+
+    pub fn matches(&self, time: Time, forget: bool) -> bool {
+        self.time == time && self.forget == forget
+    }
+}
+
+// everything passed between the handle and the future.
+pub struct Shared<Src: Source> {
+    pub channels: BTreeMap<usize, Request<Src::Time, StatePoll<Src>>>,
+    pub events:   Option<Request<Src::Time, EventsPoll<Src>>>,
+    pub advance:  Option<Src::Time>,
+    pub released: Vec<usize>,
+
+    // interrupts the source has emitted, which haven't been handed out yet.
+    pub emitted: VecDeque<Emitted<Src>>,
+
+    // the task running the future.
+    pub future_waker:      Option<Waker>,
+    // woken when the source has new information nobody asked for.
+    pub all_channel_waker: Option<Waker>,
+    // whether the future was woken by the handle, rather than the source.
+    pub requested:         bool,
+    // whether the handle has been dropped.
+    pub closed:            bool,
+}
+
+impl<Src: Source> Shared<Src> {
+    pub fn new() -> Self {
+        Self {
+            channels:          BTreeMap::new(),
+            events:            None,
+            advance:           None,
+            released:          Vec::new(),
+            emitted:           VecDeque::new(),
+            future_waker:      None,
+            all_channel_waker: None,
+            requested:         false,
+            closed:            false,
+        }
+    }
+
+    // the oldest interrupt which hasn't been handed out yet, as a poll of any kind.
+    pub fn pop_emitted<S>(&mut self) -> Option<Poll<Src, S>> {
+        let emitted = self.emitted.pop_front()?;
+
+        Some(emitted.map(|(time, interrupt)| SourcePoll::Interrupt {
+            time,
+            interrupt,
+        }))
+    }
+
+    // let the future know there is something for it to do.
+    pub fn wake_future(&mut self) {
+        self.requested = true;
+        if let Some(waker) = self.future_waker.take() {
+            waker.wake()
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use util::dummy_waker::DummyWaker;
+
+use super::{offload, OffloadFuture, OffloadSource};
+use crate::source_poll::Interrupt;
+use crate::sources::push::PushSource;
+use crate::sources::transposer::multi_input_transposer::test::{cx, TestSource};
+use crate::{Source, SourcePoll};
+
+fn run<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+    let waker = DummyWaker::dummy();
+    Pin::new(future).poll(&mut Context::from_waker(&waker))
+}
+
+#[test]
+fn results_come_from_future_test() {
+    let source = TestSource::default();
+    source.insert(1, 10);
+
+    let (mut handle, mut future) = offload(source);
+    assert_eq!(handle.max_channel().get(), 4);
+
+    // nothing happens until the future runs.
+    assert!(matches!(handle.poll(5, cx(0)), Ok(SourcePoll::Pending)));
+    assert!(matches!(handle.poll(5, cx(0)), Ok(SourcePoll::Pending)));
+
+    assert!(run(&mut future).is_pending());
+    assert!(matches!(
+        handle.poll(5, cx(0)).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      1,
+            interrupt: Interrupt::Event(10),
+        }
+    ));
+
+    assert!(matches!(handle.poll(5, cx(0)), Ok(SourcePoll::Pending)));
+    assert!(run(&mut future).is_pending());
+    assert!(matches!(
+        handle.poll(5, cx(0)).ok().unwrap(),
+        SourcePoll::Ready {
+            state: 1,
+            ..
+        }
+    ));
+
+    // results for a different request are not handed out.
+    assert!(matches!(handle.poll(6, cx(1)), Ok(SourcePoll::Pending)));
+    assert!(run(&mut future).is_pending());
+    assert!(matches!(handle.poll(7, cx(1)), Ok(SourcePoll::Pending)));
+    assert!(run(&mut future).is_pending());
+    assert!(matches!(
+        handle.poll(7, cx(1)).ok().unwrap(),
+        SourcePoll::Ready {
+            state: 1,
+            ..
+        }
+    ));
+
+    drop(handle);
+    assert!(run(&mut future).is_ready());
+}
+
+#[test]
+fn interrupts_outlive_requests_test() {
+    let source = TestSource::default();
+    source.insert(1, 10);
+    source.insert(2, 20);
+
+    let (mut handle, mut future) = offload(source);
+
+    // the source emits the event for this request.
+    assert!(matches!(handle.poll(5, cx(0)), Ok(SourcePoll::Pending)));
+    assert!(run(&mut future).is_pending());
+
+    // asking for something else doesn't lose it.
+    assert!(matches!(
+        handle.poll(6, cx(0)).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      1,
+            interrupt: Interrupt::Event(10),
+        }
+    ));
+
+    // and neither does a poll on another channel.
+    assert!(matches!(
+        handle.poll_events(6, DummyWaker::dummy()),
+        Ok(SourcePoll::Pending)
+    ));
+    assert!(run(&mut future).is_pending());
+    assert!(matches!(
+        handle.poll(7, cx(1)).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      2,
+            interrupt: Interrupt::Event(20),
+        }
+    ));
+
+    assert!(matches!(handle.poll(7, cx(0)), Ok(SourcePoll::Pending)));
+    assert!(run(&mut future).is_pending());
+    assert!(matches!(
+        handle.poll(7, cx(0)).ok().unwrap(),
+        SourcePoll::Ready {
+            state: 2,
+            ..
+        }
+    ));
+}
+
+#[allow(unused)]
+fn handle_is_send<Src>(handle: OffloadSource<Src>)
+where
+    Src: Source,
+    Src::Time: Send,
+    Src::Event: Send,
+    Src::State: Send,
+    Src::Error: Send,
+{
+    fn assert_send<T: Send>(_: T) {}
+
+    assert_send(handle)
+}
+
+#[test]
+fn send_source_is_send_test() {
+    fn assert_send<T: Send>() {}
+
+    type Push = PushSource<u64, u8, u64, fn(&mut u64, &u8)>;
+
+    assert_send::<Push>();
+    assert_send::<OffloadSource<Push>>();
+    assert_send::<OffloadFuture<Push>>();
+
+    // the handle doesn't need the source to be send.
+    assert_send::<OffloadSource<TestSource>>();
+}
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use futures_core::Future;
+
+pub use self::realtime_events::RealtimeEvents;
+pub use self::realtime_states::RealtimeStates;
+use self::shared::Shared;
+use crate::traits::Timestamp;
+use crate::Source;
+
+mod realtime_events;
+mod realtime_states;
+mod shared;
+
+#[cfg(test)]
+mod test;
+
+/// Drive a source by the wall clock, splitting it into a stream of events and a sampler of states.
+///
+/// `reference` converts the timestamps of the source to and from [`Instant`]s,
+/// and `sleep_fn` should resolve once the given instant has passed (`tokio::time::sleep_until`, for example).
+pub fn realtime<Src, SleepFn, SleepFut>(
+    source: Src,
+    reference: <Src::Time as Timestamp>::Reference,
+    sleep_fn: SleepFn,
+) -> (RealtimeEvents<Src, SleepFn, SleepFut>, RealtimeStates<Src>)
+where
+    Src: Source,
+    Src::Time: Timestamp,
+    SleepFn: Fn(Instant) -> SleepFut,
+    SleepFut: Future<Output = ()>,
+{
+    let shared = Arc::new(Shared::new(source, reference));
+
+    let events = RealtimeEvents::new(shared.clone(), sleep_fn);
+    let states = RealtimeStates::new(shared);
+
+    (events, states)
+}
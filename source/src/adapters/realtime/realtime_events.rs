@@ -0,0 +1,105 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use futures_core::{Future, Stream};
+
+use super::shared::Shared;
+use crate::source_poll::{Interrupt, SourcePollErr};
+use crate::traits::Timestamp;
+use crate::{Source, SourcePoll};
+
+/// The events half of [`realtime`](super::realtime).
+///
+/// This yields the interrupts of the source as the wall clock reaches them,
+/// advancing the source to the current time every time it is polled.
+pub struct RealtimeEvents<Src: Source, SleepFn, SleepFut>
+where
+    Src::Time: Timestamp,
+{
+    shared:        Arc<Shared<Src>>,
+    sleep_fn:      SleepFn,
+    current_sleep: Option<(Instant, Pin<Box<SleepFut>>)>,
+}
+
+impl<Src: Source, SleepFn, SleepFut> RealtimeEvents<Src, SleepFn, SleepFut>
+where
+    Src::Time: Timestamp,
+{
+    pub(super) fn new(shared: Arc<Shared<Src>>, sleep_fn: SleepFn) -> Self {
+        Self {
+            shared,
+            sleep_fn,
+            current_sleep: None,
+        }
+    }
+}
+
+// the future is boxed, so nothing here is ever pinned.
+impl<Src: Source, SleepFn, SleepFut> Unpin for RealtimeEvents<Src, SleepFn, SleepFut> where
+    Src::Time: Timestamp
+{
+}
+
+impl<Src: Source, SleepFn, SleepFut> Stream for RealtimeEvents<Src, SleepFn, SleepFut>
+where
+    Src::Time: Timestamp,
+    SleepFn: Fn(Instant) -> SleepFut,
+    SleepFut: Future<Output = ()>,
+{
+    type Item = Result<(Src::Time, Interrupt<Src::Event>), SourcePollErr<Src::Time, Src::Error>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            let next_event_at = {
+                let mut inner = this.shared.inner.lock();
+                inner.events_waker = Some(cx.waker().clone());
+
+                if let Some(interrupt) = inner.interrupts.pop_front() {
+                    return Poll::Ready(Some(Ok(interrupt)))
+                }
+
+                let poll_time = this.shared.now(&inner);
+                inner.source.advance(poll_time);
+                inner.advanced = Some(poll_time);
+
+                match inner.source.poll_events(poll_time, cx.waker().clone()) {
+                    Err(err) => return Poll::Ready(Some(Err(err))),
+                    Ok(SourcePoll::Interrupt {
+                        time,
+                        interrupt,
+                    }) => return Poll::Ready(Some(Ok((time, interrupt)))),
+                    Ok(SourcePoll::Pending) => return Poll::Pending,
+                    Ok(SourcePoll::Ready {
+                        next_event_at, ..
+                    }) => next_event_at,
+                }
+            };
+
+            // the source will wake us if anything new comes up.
+            let next_event_at = match next_event_at {
+                Some(time) => time.get_instant(&this.shared.reference),
+                None => {
+                    this.current_sleep = None;
+                    return Poll::Pending
+                },
+            };
+
+            let sleep = match &mut this.current_sleep {
+                Some((wake_at, sleep)) if *wake_at == next_event_at => sleep,
+                current_sleep => {
+                    let sleep = Box::pin((this.sleep_fn)(next_event_at));
+                    &mut current_sleep.insert((next_event_at, sleep)).1
+                },
+            };
+
+            match sleep.as_mut().poll(cx) {
+                Poll::Ready(()) => this.current_sleep = None,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use std::task::Poll;
+
+use super::shared::Shared;
+use crate::source_poll::SourcePollErr;
+use crate::traits::{SourceContext, Timestamp};
+use crate::{Source, SourcePoll};
+
+type SampleResult<Src> = Result<
+    (<Src as Source>::Time, <Src as Source>::State),
+    SourcePollErr<<Src as Source>::Time, <Src as Source>::Error>,
+>;
+
+/// The states half of [`realtime`](super::realtime).
+///
+/// This samples the state of the source at the current wall-clock time.
+/// Any interrupts found along the way are handed to the [`RealtimeEvents`](super::RealtimeEvents) stream.
+pub struct RealtimeStates<Src: Source>
+where
+    Src::Time: Timestamp,
+{
+    shared: Arc<Shared<Src>>,
+}
+
+impl<Src: Source> RealtimeStates<Src>
+where
+    Src::Time: Timestamp,
+{
+    pub(super) fn new(shared: Arc<Shared<Src>>) -> Self {
+        Self {
+            shared,
+        }
+    }
+
+    /// Sample the state of the source now, returning the time it was sampled at along with the state.
+    ///
+    /// The source is polled with `poll_forget`, so samples are never rolled back.
+    pub fn poll(&self, cx: SourceContext) -> Poll<SampleResult<Src>> {
+        let mut inner = self.shared.inner.lock();
+        let time = self.shared.now(&inner);
+        let mut found_interrupts = false;
+
+        let poll = loop {
+            match inner.source.poll_forget(time, cx.clone()) {
+                Err(err) => break Poll::Ready(Err(err)),
+                Ok(SourcePoll::Ready {
+                    state, ..
+                }) => break Poll::Ready(Ok((time, state))),
+                Ok(SourcePoll::Interrupt {
+                    time,
+                    interrupt,
+                }) => {
+                    inner.interrupts.push_back((time, interrupt));
+                    found_interrupts = true;
+                },
+                Ok(SourcePoll::Pending) => break Poll::Pending,
+            }
+        };
+
+        let events_waker = found_interrupts
+            .then(|| inner.events_waker.take())
+            .flatten();
+        drop(inner);
+
+        if let Some(waker) = events_waker {
+            waker.wake()
+        }
+
+        poll
+    }
+
+    /// Inform the source that the channel will not be sampled again.
+    pub fn release_channel(&self, channel: usize) {
+        self.shared.inner.lock().source.release_channel(channel)
+    }
+}
+
+impl<Src: Source> Clone for RealtimeStates<Src>
+where
+    Src::Time: Timestamp,
+{
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
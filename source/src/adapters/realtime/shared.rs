@@ -0,0 +1,58 @@
+use std::collections::VecDeque;
+use std::task::Waker;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+
+use crate::source_poll::Interrupt;
+use crate::traits::Timestamp;
+use crate::Source;
+
+pub struct Shared<Src: Source>
+where
+    Src::Time: Timestamp,
+{
+    pub reference: <Src::Time as Timestamp>::Reference,
+    pub inner:     Mutex<Inner<Src>>,
+}
+
+pub struct Inner<Src: Source> {
+    pub source: Src,
+
+    // interrupts the states sampler ran into, which the events stream hasn't yielded yet.
+    pub interrupts:   VecDeque<(Src::Time, Interrupt<Src::Event>)>,
+    pub events_waker: Option<Waker>,
+
+    // the latest time the source has been advanced to, which it can no longer be polled before.
+    pub advanced: Option<Src::Time>,
+}
+
+impl<Src: Source> Shared<Src>
+where
+    Src::Time: Timestamp,
+{
+    pub fn new(source: Src, reference: <Src::Time as Timestamp>::Reference) -> Self {
+        Self {
+            reference,
+            inner: Mutex::new(Inner {
+                source,
+                interrupts: VecDeque::new(),
+                events_waker: None,
+                advanced: None,
+            }),
+        }
+    }
+
+    // the current wall-clock time, as a timestamp of the source.
+    //
+    // this takes the locked inner, so the clock is read after any other thread's advance,
+    // and it never goes back before the time the source was advanced to.
+    pub fn now(&self, inner: &Inner<Src>) -> Src::Time {
+        let now = Src::Time::get_timestamp(&Instant::now(), &self.reference);
+
+        match inner.advanced {
+            Some(advanced) => now.max(advanced),
+            None => now,
+        }
+    }
+}
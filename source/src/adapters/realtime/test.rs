@@ -0,0 +1,134 @@
+use std::cell::RefCell;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+use util::dummy_waker::DummyWaker;
+
+use super::realtime;
+use crate::source_poll::{Interrupt, TrySourcePoll};
+use crate::sources::transposer::multi_input_transposer::test::cx;
+use crate::traits::SourceContext;
+use crate::{Source, SourcePoll};
+
+// emits each of its events once, with the number of events emitted so far as its state.
+struct ScriptedSource {
+    events:   Vec<Duration>,
+    emitted:  usize,
+    advanced: Rc<RefCell<Option<Duration>>>,
+}
+
+impl ScriptedSource {
+    fn poll_internal<S>(
+        &mut self,
+        time: Duration,
+        state: S,
+    ) -> TrySourcePoll<Duration, usize, S, ()> {
+        Ok(match self.events.get(self.emitted) {
+            Some(event_time) if *event_time <= time => {
+                self.emitted += 1;
+                SourcePoll::Interrupt {
+                    time:      *event_time,
+                    interrupt: Interrupt::FinalizedEvent(self.emitted),
+                }
+            },
+            next => SourcePoll::Ready {
+                state,
+                next_event_at: next.copied(),
+            },
+        })
+    }
+}
+
+impl Source for ScriptedSource {
+    type Time = Duration;
+
+    type Event = usize;
+
+    type State = usize;
+
+    type Error = ();
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        _cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_internal(time, self.emitted)
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        _all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        self.poll_internal(time, ())
+    }
+
+    fn release_channel(&mut self, _channel: usize) {}
+
+    fn advance(&mut self, time: Self::Time) {
+        *self.advanced.borrow_mut() = Some(time);
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        NonZeroUsize::MIN
+    }
+}
+
+#[test]
+fn events_and_states_test() {
+    let advanced = Rc::new(RefCell::new(None));
+    let hour = Duration::from_secs(3600);
+    let source = ScriptedSource {
+        events:   vec![
+            Duration::ZERO,
+            Duration::from_millis(1),
+            Duration::from_millis(2),
+            hour,
+        ],
+        emitted:  0,
+        advanced: advanced.clone(),
+    };
+
+    // the source started a minute ago.
+    let reference = Instant::now() - Duration::from_secs(60);
+    let sleeps = Rc::new(RefCell::new(Vec::new()));
+    let sleeps_ref = sleeps.clone();
+    let (mut events, states) = realtime(source, reference, move |instant| {
+        sleeps_ref.borrow_mut().push(instant);
+        std::future::pending()
+    });
+
+    let waker = DummyWaker::dummy();
+    let mut context = Context::from_waker(&waker);
+
+    match Pin::new(&mut events).poll_next(&mut context) {
+        Poll::Ready(Some(Ok((time, Interrupt::FinalizedEvent(1))))) => {
+            assert_eq!(time, Duration::ZERO)
+        },
+        _ => panic!(),
+    }
+    assert!(advanced.borrow().unwrap() >= Duration::from_secs(60));
+
+    // sampling the state passes the interrupts it runs into on to the events stream.
+    match states.poll(cx(0)) {
+        Poll::Ready(Ok((time, 3))) => assert!(time >= Duration::from_secs(60)),
+        _ => panic!(),
+    }
+
+    let mut yielded = Vec::new();
+    while let Poll::Ready(Some(Ok((time, _)))) = Pin::new(&mut events).poll_next(&mut context) {
+        yielded.push(time);
+    }
+    assert_eq!(yielded, vec![
+        Duration::from_millis(1),
+        Duration::from_millis(2)
+    ]);
+
+    // the last event is an hour in, so the stream sleeps until then.
+    assert_eq!(*sleeps.borrow(), vec![reference + hour]);
+}
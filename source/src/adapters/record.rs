@@ -0,0 +1,151 @@
+use std::io::{self, Write};
+use std::num::NonZeroUsize;
+use std::task::Waker;
+
+use serde::Serialize;
+
+use crate::source_poll::{SourcePollErr, TrySourcePoll};
+use crate::sources::replay::{write_header, write_record, ReplayHeader};
+use crate::traits::SourceContext;
+use crate::{Source, SourcePoll};
+
+/// A source which writes every interrupt of another source to a replay file, as it passes through.
+///
+/// The file also holds the start time and rng seed of the transposer the source feeds,
+/// so the run can be reproduced by feeding a [`ReplaySource`](crate::sources::replay::ReplaySource)
+/// into the same transposer.
+///
+/// Only the interrupts are recorded, not the states, so only sources with a state of `()` can be recorded.
+/// A transposer reading the state of its input would see something different on replay.
+pub struct Record<Src: Source, W: Write> {
+    source: Src,
+    writer: W,
+}
+
+/// An error from the recorded source, or from writing the replay file.
+#[derive(Debug)]
+pub enum RecordError<E> {
+    Source(E),
+    Io(io::Error),
+}
+
+impl<Src, W> Record<Src, W>
+where
+    Src: Source,
+    Src::Time: Serialize,
+    Src::Event: Serialize,
+    W: Write,
+{
+    /// write the header of the replay file, and start recording.
+    pub fn new(
+        source: Src,
+        mut writer: W,
+        start_time: Src::Time,
+        rng_seed: [u8; 32],
+    ) -> io::Result<Self>
+    where
+        Src: Source<State = ()>,
+    {
+        let header = ReplayHeader {
+            start_time,
+            rng_seed,
+        };
+        write_header(&mut writer, &header)?;
+
+        Ok(Self {
+            source,
+            writer,
+        })
+    }
+
+    /// stop recording, returning the writer.
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    fn record<S>(
+        &mut self,
+        poll: TrySourcePoll<Src::Time, Src::Event, S, Src::Error>,
+    ) -> TrySourcePoll<Src::Time, Src::Event, S, RecordError<Src::Error>> {
+        let poll = poll.map_err(map_err)?;
+
+        if let SourcePoll::Interrupt {
+            time,
+            interrupt,
+        } = &poll
+        {
+            write_record(&mut self.writer, time, interrupt)
+                .map_err(|err| SourcePollErr::SpecificError(RecordError::Io(err)))?;
+        }
+
+        Ok(poll)
+    }
+}
+
+fn map_err<T, E>(err: SourcePollErr<T, E>) -> SourcePollErr<T, RecordError<E>> {
+    match err {
+        SourcePollErr::OutOfBoundsChannel => SourcePollErr::OutOfBoundsChannel,
+        SourcePollErr::PollAfterAdvance {
+            advanced,
+        } => SourcePollErr::PollAfterAdvance {
+            advanced,
+        },
+        SourcePollErr::PollBeforeDefault => SourcePollErr::PollBeforeDefault,
+        SourcePollErr::SpecificError(err) => SourcePollErr::SpecificError(RecordError::Source(err)),
+    }
+}
+
+impl<Src, W> Source for Record<Src, W>
+where
+    Src: Source,
+    Src::Time: Serialize,
+    Src::Event: Serialize,
+    W: Write,
+{
+    type Time = Src::Time;
+
+    type Event = Src::Event;
+
+    type State = Src::State;
+
+    type Error = RecordError<Src::Error>;
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        let poll = self.source.poll(time, cx);
+        self.record(poll)
+    }
+
+    fn poll_forget(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        let poll = self.source.poll_forget(time, cx);
+        self.record(poll)
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        let poll = self.source.poll_events(time, all_channel_waker);
+        self.record(poll)
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        self.source.release_channel(channel)
+    }
+
+    fn advance(&mut self, time: Self::Time) {
+        self.source.advance(time)
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        self.source.max_channel()
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+use std::ops::Bound;
+use std::task::Waker;
+
+use crate::source_poll::{Interrupt, TrySourcePoll};
+use crate::traits::{SourceContext, StateHashSource};
+use crate::{Source, SourcePoll};
+
+/// An event from a [`StateHashes`] source.
+pub enum HashedEvent<E> {
+    /// An event from the wrapped source.
+    Event(E),
+
+    /// The state hash of the step at the time of the interrupt.
+    StateHash(u64),
+}
+
+type PendingInterrupt<Src> = (
+    <Src as Source>::Time,
+    Interrupt<HashedEvent<<Src as Source>::Event>>,
+);
+
+/// A source which emits the state hash of each step of a transposer, alongside its events.
+///
+/// The hash of a step is emitted after all of its events, and before the events of any later step.
+/// Two peers running the same transposer on the same inputs should emit identical hashes,
+/// so the first hash that differs is the time at which they diverged.
+pub struct StateHashes<Src: StateHashSource> {
+    source: Src,
+
+    // the hashes of the steps before this bound have been emitted.
+    unemitted: Bound<Src::Time>,
+
+    // the sequence number and time of the last step whose hash was emitted.
+    // steps at the same time as it are only emitted if they come after it.
+    last_emitted: Option<(usize, Src::Time)>,
+
+    // interrupts waiting to be emitted, before anything else from the source.
+    pending: VecDeque<PendingInterrupt<Src>>,
+}
+
+impl<Src: StateHashSource> StateHashes<Src> {
+    pub fn new(mut source: Src) -> Self {
+        source.enable_state_hashing();
+
+        Self {
+            source,
+            unemitted: Bound::Unbounded,
+            last_emitted: None,
+            pending: VecDeque::new(),
+        }
+    }
+
+    // queue the hashes of the unemitted steps which end before `until`.
+    fn queue_hashes(&mut self, until: Bound<Src::Time>) {
+        let mut hashes = self.source.state_hashes((self.unemitted, until));
+
+        if let Some((last_emitted, _)) = self.last_emitted {
+            hashes.retain(|(i, ..)| *i > last_emitted);
+        }
+
+        if let Some((i, time, _)) = hashes.last() {
+            self.unemitted = Bound::Included(*time);
+            self.last_emitted = Some((*i, *time));
+        }
+
+        self.pending.extend(
+            hashes
+                .into_iter()
+                .map(|(_, time, hash)| (time, Interrupt::Event(HashedEvent::StateHash(hash)))),
+        );
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn poll_state(
+        &mut self,
+        time: Src::Time,
+        cx: SourceContext,
+        forget: bool,
+    ) -> TrySourcePoll<Src::Time, HashedEvent<Src::Event>, Src::State, Src::Error> {
+        // make sure every hash up to time has been emitted before handing out the state.
+        match self.poll_events(time, cx.all_channel_waker.clone())? {
+            SourcePoll::Ready {
+                ..
+            } => {},
+            SourcePoll::Interrupt {
+                time,
+                interrupt,
+            } => {
+                return Ok(SourcePoll::Interrupt {
+                    time,
+                    interrupt,
+                })
+            },
+            SourcePoll::Pending => return Ok(SourcePoll::Pending),
+        }
+
+        let poll = if forget {
+            self.source.poll_forget(time, cx)?
+        } else {
+            self.source.poll(time, cx)?
+        };
+
+        Ok(self.handle_poll(poll, time))
+    }
+
+    fn handle_poll<S>(
+        &mut self,
+        poll: SourcePoll<Src::Time, Src::Event, S>,
+        time: Src::Time,
+    ) -> SourcePoll<Src::Time, HashedEvent<Src::Event>, S> {
+        let (interrupt_time, interrupt) = match poll {
+            SourcePoll::Ready {
+                state,
+                next_event_at,
+            } => {
+                // every step at or before time is saturated.
+                self.queue_hashes(Bound::Included(time));
+                return match self.pending.pop_front() {
+                    Some((time, interrupt)) => SourcePoll::Interrupt {
+                        time,
+                        interrupt,
+                    },
+                    None => SourcePoll::Ready {
+                        state,
+                        next_event_at,
+                    },
+                }
+            },
+            SourcePoll::Interrupt {
+                time,
+                interrupt,
+            } => (time, interrupt),
+            SourcePoll::Pending => return SourcePoll::Pending,
+        };
+
+        let interrupt = match interrupt {
+            Interrupt::Event(event) => Interrupt::Event(HashedEvent::Event(event)),
+            Interrupt::FinalizedEvent(event) => {
+                Interrupt::FinalizedEvent(HashedEvent::Event(event))
+            },
+            Interrupt::Rollback => {
+                // the hashes of the rolled back steps need to be emitted again.
+                // the steps replacing them reuse their sequence numbers, so only the time is kept.
+                if let Some((_, time)) = self.last_emitted {
+                    if time >= interrupt_time {
+                        self.unemitted = Bound::Included(interrupt_time);
+                        self.last_emitted = None;
+                    }
+                }
+
+                return SourcePoll::Interrupt {
+                    time:      interrupt_time,
+                    interrupt: Interrupt::Rollback,
+                }
+            },
+            Interrupt::Finalize => Interrupt::Finalize,
+        };
+
+        // every step before this event is saturated.
+        self.queue_hashes(Bound::Excluded(interrupt_time));
+        self.pending.push_back((interrupt_time, interrupt));
+        let (time, interrupt) = self.pending.pop_front().unwrap();
+
+        SourcePoll::Interrupt {
+            time,
+            interrupt,
+        }
+    }
+}
+
+impl<Src: StateHashSource> Source for StateHashes<Src> {
+    type Time = Src::Time;
+
+    type Event = HashedEvent<Src::Event>;
+
+    type State = Src::State;
+
+    type Error = Src::Error;
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_state(time, cx, false)
+    }
+
+    fn poll_forget(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_state(time, cx, true)
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        if let Some((time, interrupt)) = self.pending.pop_front() {
+            return Ok(SourcePoll::Interrupt {
+                time,
+                interrupt,
+            })
+        }
+
+        let poll = self.source.poll_events(time, all_channel_waker)?;
+        Ok(self.handle_poll(poll, time))
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        self.source.release_channel(channel)
+    }
+
+    fn advance(&mut self, time: Self::Time) {
+        self.source.advance(time)
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        self.source.max_channel()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::VecDeque;
+    use std::num::NonZeroUsize;
+    use std::ops::{Bound, RangeBounds};
+    use std::task::Waker;
+
+    use util::dummy_waker::DummyWaker;
+
+    use super::{HashedEvent, StateHashes};
+    use crate::source_poll::{Interrupt, TrySourcePoll};
+    use crate::traits::{SourceContext, StateHashSource};
+    use crate::{Source, SourcePoll};
+
+    // emits its events once, and reports whichever steps have been pushed as saturated.
+    #[derive(Default)]
+    struct ScriptedSource {
+        events: VecDeque<(usize, usize)>,
+        hashes: Vec<(usize, usize, u64)>,
+    }
+
+    impl Source for ScriptedSource {
+        type Time = usize;
+
+        type Event = usize;
+
+        type State = ();
+
+        type Error = ();
+
+        fn poll(
+            &mut self,
+            time: Self::Time,
+            cx: SourceContext,
+        ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+            self.poll_events(time, cx.all_channel_waker)
+        }
+
+        fn poll_events(
+            &mut self,
+            time: Self::Time,
+            _all_channel_waker: Waker,
+        ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+            match self.events.front() {
+                Some((t, _)) if *t <= time => {
+                    let (time, event) = self.events.pop_front().unwrap();
+                    Ok(SourcePoll::Interrupt {
+                        time,
+                        interrupt: Interrupt::Event(event),
+                    })
+                },
+                _ => Ok(SourcePoll::Ready {
+                    state:         (),
+                    next_event_at: self.events.front().map(|(t, _)| *t),
+                }),
+            }
+        }
+
+        fn release_channel(&mut self, _channel: usize) {}
+
+        fn advance(&mut self, _time: Self::Time) {}
+
+        fn max_channel(&self) -> NonZeroUsize {
+            NonZeroUsize::MIN
+        }
+    }
+
+    impl StateHashSource for ScriptedSource {
+        fn enable_state_hashing(&mut self) {}
+
+        fn state_hashes(&self, range: (Bound<usize>, Bound<usize>)) -> Vec<(usize, usize, u64)> {
+            self.hashes
+                .iter()
+                .filter(|(_, time, _)| range.contains(time))
+                .copied()
+                .collect()
+        }
+    }
+
+    fn drain(source: &mut StateHashes<ScriptedSource>, time: usize) -> Vec<(usize, u64)> {
+        let mut hashes = Vec::new();
+        loop {
+            match source.poll_events(time, DummyWaker::dummy()).ok().unwrap() {
+                SourcePoll::Interrupt {
+                    time,
+                    interrupt: Interrupt::Event(HashedEvent::StateHash(hash)),
+                } => hashes.push((time, hash)),
+                SourcePoll::Interrupt {
+                    ..
+                } => {},
+                _ => break hashes,
+            }
+        }
+    }
+
+    #[test]
+    fn same_time_steps_test() {
+        let mut source = StateHashes::new(ScriptedSource {
+            events: VecDeque::new(),
+            hashes: vec![(0, 0, 10), (1, 1, 11)],
+        });
+
+        assert_eq!(drain(&mut source, 1), vec![(0, 10), (1, 11)]);
+
+        // a second step at time 1 is saturated after the first one's hash was emitted.
+        source.source.hashes.push((2, 1, 12));
+        source.source.events.push_back((2, 0));
+
+        assert_eq!(drain(&mut source, 2), vec![(1, 12)]);
+    }
+}
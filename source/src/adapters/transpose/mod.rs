@@ -1,334 +1,120 @@
-// mod caller_channel_status;
-// mod channel_assignments;
-mod channels;
-mod input_buffer;
-mod retention_policy;
-mod steps;
 mod storage;
-// mod transpose_step_metadata;
-
-use std::pin::Pin;
-use std::sync::Weak;
-use std::task::{Poll, Waker};
-
-use pin_project::pin_project;
-use transposer::Transposer;
-use util::replace_waker::ReplaceWaker;
-use util::stack_waker::StackWaker;
-
-use self::channels::ChannelStatuses;
-use self::input_buffer::InputBuffer;
-use self::retention_policy::RetentionPolicy;
-use self::steps::Steps;
-use crate::adapters::transpose::channels::CallerChannelStatus;
-use crate::source_poll::{Interrupt, SourcePoll, TrySourcePoll};
-use crate::traits::SourceContext;
+#[cfg(test)]
+mod test;
+
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::ops::Bound;
+use std::task::Waker;
+
+use transposer::schedule_storage::{DefaultStorage, StorageFamily};
+use transposer::single_input_state::SingleInputState;
+use transposer::step::{EraseInput, InputState};
+use transposer::{TransposerHash, TransposerInput, TransposerInputEventHandler};
+
+pub use self::storage::TransposeStorage;
+use crate::source_poll::TrySourcePoll;
+use crate::sources::transposer::checkpoint_strategy::CheckpointStrategy;
+use crate::sources::transposer::input_sources::InputSource;
+use crate::sources::transposer::multi_input_transposer::{
+    MultiInputTransposerSource,
+    TooFewChannels,
+};
+use crate::traits::{SourceContext, StateHashSource};
 use crate::Source;
 
-#[pin_project(project=TransposeProject)]
-pub struct Transpose<Src: Source, T: Transposer> {
-    // the source we pull from
-    #[pin]
-    source: Src,
-
-    last_scheduled: Option<T::Time>,
-
-    // the all channel waker to keep up to date.
-    all_channel_waker: Weak<ReplaceWaker>,
-
-    // the time to use for poll_events calls.
-    // this should be the time of the latest emitted state,
-    // or the currently saturating/unsaturated "original" step,
-    // whichever is later
-    events_poll_time: T::Time,
-
-    // current channel obligations
-    channel_statuses: ChannelStatuses<T>,
-
-    steps: Steps<T>,
-
-    input_buffer: InputBuffer<T>,
+/// A source which feeds the events and states of another source into a transposer.
+///
+/// The events of `Src` are handed to the transposer as the input `I`,
+/// and the transposer requests the states of `Src` whenever it needs them.
+/// Its input state manager is a [`SingleInputState`], so it should be something like
+/// [`SingleInputStateManager<I>`](transposer::single_input_state::SingleInputStateManager).
+///
+/// When the source rolls back, the steps which depended on the discarded events are discarded too,
+/// and a rollback is emitted if anything derived from them could have been observed.
+pub struct Transpose<Src, T, I, S = DefaultStorage>
+where
+    Src: Source<Time = T::Time, Event = I::InputEvent, State = I::InputState>,
+    T: TransposerInputEventHandler<I>,
+    SingleInputState<I>: InputState<T>,
+    I: TransposerInput<Base = T>,
+    S: StorageFamily,
+    S::Erasure: EraseInput<I, S>,
+{
+    inner: MultiInputTransposerSource<T, SingleInputState<I>, (InputSource<I, Src>,), S>,
 }
 
-enum TrySourcePollToHandle {}
-
-impl<Src, T> Transpose<Src, T>
+impl<Src, T, I> Transpose<Src, T, I>
 where
-    Src: Source,
-    T::Time: Copy + Ord + Default + Unpin, // TODO remove once https://github.com/rust-lang/rust/issues/91985 is resolved.
-    T: Transposer<Time = Src::Time, Input = Src::Event, InputState = Src::State>,
-    T: Clone,
+    Src: Source<Time = T::Time, Event = I::InputEvent, State = I::InputState>,
+    T: TransposerInputEventHandler<I>,
+    SingleInputState<I>: InputState<T>,
+    I: TransposerInput<Base = T>,
+    <DefaultStorage as StorageFamily>::Erasure: EraseInput<I, DefaultStorage>,
 {
-    pub fn new(source: Src, transposer: T, rng_seed: [u8; 32]) -> Self {
-        Self {
-            source,
-            last_scheduled: None,
-            all_channel_waker: ReplaceWaker::new_empty(),
-            events_poll_time: T::Time::default(),
-            channel_statuses: ChannelStatuses::new(),
-            steps: Steps::new(transposer, rng_seed),
-            input_buffer: InputBuffer::new(),
-        }
+    /// fails if `source` doesn't allow at least three channels, because one is reserved for computing steps.
+    pub fn new(
+        source: Src,
+        transposer: T,
+        start_time: T::Time,
+        rng_seed: [u8; 32],
+    ) -> Result<Self, TooFewChannels> {
+        Self::new_with_storage(source, transposer, start_time, rng_seed)
     }
+}
 
-    fn ready_or_scheduled(
-        &self,
-        state: T::OutputState,
-    ) -> SourcePoll<T::Time, T::OutputEvent, T::OutputState> {
-        // match (
-        //     self.channel_statuses.get_scheduled_time(),
-        //     self.last_scheduled,
-        // ) {
-        //     (None, None) => SourcePollOk::Ready(state),
-        //     (None, Some(t)) => SourcePollOk::Scheduled(state, t),
-        //     (Some(t), None) => SourcePollOk::Scheduled(state, t),
-        //     (Some(t1), Some(t2)) => SourcePollOk::Scheduled(state, std::cmp::min(t1, t2)),
-        // }
-        todo!()
+impl<Src, T, I, S> Transpose<Src, T, I, S>
+where
+    Src: Source<Time = T::Time, Event = I::InputEvent, State = I::InputState>,
+    T: TransposerInputEventHandler<I>,
+    SingleInputState<I>: InputState<T>,
+    I: TransposerInput<Base = T>,
+    S: StorageFamily,
+    S::Erasure: EraseInput<I, S>,
+{
+    /// create a source which keeps the state of its steps in the storage family `S`,
+    /// which is chosen with a turbofish, like `Transpose::<_, _, _, TransposeStorage>::new_with_storage(..)`.
+    pub fn new_with_storage(
+        source: Src,
+        transposer: T,
+        start_time: T::Time,
+        rng_seed: [u8; 32],
+    ) -> Result<Self, TooFewChannels> {
+        let inputs = (InputSource::new(source),);
+
+        Ok(Self {
+            inner: MultiInputTransposerSource::new_with_storage(
+                inputs, transposer, start_time, rng_seed,
+            )?,
+        })
     }
 
-    fn poll_inner(
-        mut self: Pin<&mut Self>,
-        poll_time: T::Time,
-        cx: SourceContext,
-        forget: bool,
-    ) -> TrySourcePoll<T::Time, T::OutputEvent, T::OutputState, Src::Error> {
-        let TransposeProject {
-            mut source,
-            last_scheduled,
-            all_channel_waker,
-            events_poll_time,
-            channel_statuses,
-            steps,
-            input_buffer,
-        } = self.as_mut().project();
-
-        let SourceContext {
-            channel: caller_channel,
-            one_channel_waker,
-            all_channel_waker: caller_all_channel_waker,
-        } = cx;
-
-        let mut unhandled_interrupt: Option<(Src::Time, Interrupt<Src::Event>)> = None;
-
-        // poll events if our all channel waker was triggered.
-        if let Some(waker) = ReplaceWaker::register(all_channel_waker, caller_all_channel_waker) {
-            match source.as_mut().poll_events(*events_poll_time, waker)? {
-                SourcePoll::Ready {
-                    state: (),
-                    next_event_at,
-                } => *last_scheduled = next_event_at,
-                SourcePoll::Interrupt {
-                    time,
-                    interrupt,
-                } => {
-                    debug_assert!(unhandled_interrupt.is_none());
-                    unhandled_interrupt = Some((time, interrupt))
-                },
-                SourcePoll::Pending => return Ok(SourcePoll::Pending),
-            }
+    /// choose which saturated steps are kept around, to trade memory against recomputation.
+    pub fn with_checkpoint_strategy(
+        self,
+        strategy: impl CheckpointStrategy<T::Time> + 'static,
+    ) -> Self {
+        Self {
+            inner: self.inner.with_checkpoint_strategy(strategy),
         }
+    }
 
-        // at this point we only need to poll the source if state is needed.
-        // we are ready to start manipulating the status,
-        // handling blockers as they arise.
-
-        let mut status = channel_statuses.get_channel_status(caller_channel);
-
-        let all_channel_waker = ReplaceWaker::get_waker(all_channel_waker);
-
-        loop {
-            if let Some((time, interrupt)) = unhandled_interrupt.take() {
-                match interrupt {
-                    Interrupt::Event(e) => input_buffer.insert_back(time, e),
-                    Interrupt::Rollback => input_buffer.rollback(time),
-                    Interrupt::Finalize => {
-                        // let should_release_old_steps = retention_policy.source_finalize(time);
-                        // if should_release_old_steps {
-                        //     todo!()
-                        // }
-                        todo!();
-                        return Ok(SourcePoll::Interrupt {
-                            time,
-                            interrupt: Interrupt::Finalize,
-                        })
-                    },
-                }
-            }
-
-            match core::mem::replace(&mut status, CallerChannelStatus::Limbo) {
-                CallerChannelStatus::Limbo => unreachable!(),
-                CallerChannelStatus::Free(inner_status) => {
-                    status = inner_status.poll(forget, poll_time);
-                },
-                CallerChannelStatus::OriginalStepSourceState(mut inner_status) => {
-                    let (time, source_channel) = inner_status.get_args_for_source_poll();
-
-                    // original steps can emit events which effect all channels,
-                    // so this uses the all channel waker for both of these.
-                    let cx = SourceContext {
-                        channel:           source_channel,
-                        one_channel_waker: all_channel_waker.clone(),
-                        all_channel_waker: all_channel_waker.clone(),
-                    };
-
-                    let state = match source.as_mut().poll(time, cx)? {
-                        SourcePoll::Ready {
-                            state,
-                            next_event_at,
-                        } => {
-                            *last_scheduled = next_event_at;
-                            state
-                        },
-                        SourcePoll::Interrupt {
-                            time,
-                            interrupt,
-                        } => {
-                            unhandled_interrupt = Some((time, interrupt));
-                            continue
-                        },
-                        SourcePoll::Pending => return Ok(SourcePoll::Pending),
-                    };
-
-                    // this provide state call will not poll the future.
-                    let inner_status = inner_status.provide_state(state);
-
-                    // now loop again, polling the future on the next pass.
-                    status = CallerChannelStatus::OriginalStepFuture(inner_status);
-                },
-                CallerChannelStatus::OriginalStepFuture(inner_status) => {
-                    todo!()
-                    // let t = inner_status.time();
-
-                    // // get the first item, so it can be pulled if needed by poll
-                    // // (if original completes it needs to make a new original future)
-                    // let mut first = input_buffer.pop_first();
-
-                    // let (s, output) = inner_status.poll(&all_channel_waker, &mut first);
-
-                    // // if poll didn't need the input, put it back in the buffer
-                    // if let Some((t, inputs)) = first {
-                    //     input_buffer.extend_front(t, inputs)
-                    // }
-
-                    // // handle all the generated outputs
-                    // if let Some(output) = output {
-                    //     return Poll::Ready(Ok(SourcePollOk::Event(output, t)))
-                    // }
-
-                    // status = s;
-                },
-                CallerChannelStatus::RepeatStepSourceState(mut inner_status) => {
-                    let (time, stack_waker, source_channel) =
-                        inner_status.get_args_for_source_poll();
-
-                    let stacked_waker = match StackWaker::register(
-                        stack_waker,
-                        caller_channel,
-                        one_channel_waker.clone(),
-                    ) {
-                        Some(w) => w,
-                        None => return Ok(SourcePoll::Pending),
-                    };
-
-                    let cx = SourceContext {
-                        channel:           source_channel,
-                        one_channel_waker: stacked_waker,
-                        all_channel_waker: all_channel_waker.clone(),
-                    };
-
-                    let state = match source.as_mut().poll(time, cx)? {
-                        SourcePoll::Ready {
-                            state,
-                            next_event_at,
-                        } => {
-                            *last_scheduled = next_event_at;
-                            state
-                        },
-                        SourcePoll::Interrupt {
-                            time,
-                            interrupt,
-                        } => {
-                            unhandled_interrupt = Some((time, interrupt));
-                            continue
-                        },
-                        SourcePoll::Pending => return Ok(SourcePoll::Pending),
-                    };
-
-                    // this provide state call will not poll the future.
-                    let inner_status = inner_status.provide_state(state);
-
-                    // now loop again, polling the future on the next pass.
-                    status = CallerChannelStatus::RepeatStepFuture(inner_status);
-                },
-                CallerChannelStatus::RepeatStepFuture(inner_status) => {
-                    status = match inner_status.poll(&one_channel_waker) {
-                        Poll::Ready(status) => status,
-                        Poll::Pending => return Ok(SourcePoll::Pending),
-                    };
-                },
-                CallerChannelStatus::InterpolationSourceState(mut inner_status) => {
-                    let (source_channel, never_remebered) =
-                        inner_status.get_args_for_source_poll(forget);
-
-                    let cx = SourceContext {
-                        channel:           source_channel,
-                        one_channel_waker: one_channel_waker.clone(),
-                        all_channel_waker: all_channel_waker.clone(),
-                    };
-
-                    let poll = if never_remebered {
-                        source.as_mut().poll_forget(poll_time, cx)
-                    } else {
-                        source.as_mut().poll(poll_time, cx)
-                    }?;
-
-                    let state = match poll {
-                        SourcePoll::Ready {
-                            state,
-                            next_event_at,
-                        } => {
-                            *last_scheduled = next_event_at;
-                            state
-                        },
-                        SourcePoll::Interrupt {
-                            time,
-                            interrupt,
-                        } => {
-                            unhandled_interrupt = Some((time, interrupt));
-                            continue
-                        },
-                        SourcePoll::Pending => return Ok(SourcePoll::Pending),
-                    };
-
-                    let inner_status = inner_status.provide_state(state);
-
-                    // now loop again, polling the future on the next pass.
-                    status = CallerChannelStatus::InterpolationFuture(inner_status);
-                },
-                CallerChannelStatus::InterpolationFuture(inner_status) => {
-                    let output_state = match inner_status.poll(&one_channel_waker) {
-                        Ok(Poll::Ready(output_state)) => output_state,
-                        Ok(Poll::Pending) => return Ok(SourcePoll::Pending),
-                        Err(s) => {
-                            status = s;
-                            continue
-                        },
-                    };
-
-                    return Ok(self.ready_or_scheduled(output_state))
-                },
-            }
-        }
+    /// the approximate number of bytes held by the saturated steps this source is keeping.
+    ///
+    /// this is based on [`Transposer::heap_size`](transposer::Transposer::heap_size).
+    pub fn memory_usage(&self) -> usize {
+        self.inner.memory_usage()
     }
 }
 
-impl<Src, T> Source for Transpose<Src, T>
+impl<Src, T, I, S> Source for Transpose<Src, T, I, S>
 where
-    Src: Source,
-    T::Time: Copy + Ord + Default + Unpin, // TODO remove once https://github.com/rust-lang/rust/issues/91985 is resolved.
-    T: Transposer<Time = Src::Time, Input = Src::Event, InputState = Src::State>,
-    T: Clone,
+    Src: Source<Time = T::Time, Event = I::InputEvent, State = I::InputState>,
+    T: TransposerInputEventHandler<I>,
+    SingleInputState<I>: InputState<T>,
+    I: TransposerInput<Base = T>,
+    S: StorageFamily,
+    S::Erasure: EraseInput<I, S>,
 {
     type Time = T::Time;
 
@@ -339,44 +125,60 @@ where
     type Error = Src::Error;
 
     fn poll(
-        self: Pin<&mut Self>,
-        poll_time: Self::Time,
+        &mut self,
+        time: Self::Time,
         cx: SourceContext,
     ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
-        self.poll_inner(poll_time, cx, false)
+        self.inner.poll(time, cx)
     }
 
     fn poll_forget(
-        self: Pin<&mut Self>,
-        poll_time: Self::Time,
+        &mut self,
+        time: Self::Time,
         cx: SourceContext,
     ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
-        self.poll_inner(poll_time, cx, true)
+        self.inner.poll_forget(time, cx)
     }
 
     fn poll_events(
-        self: Pin<&mut Self>,
-        poll_time: Self::Time,
+        &mut self,
+        time: Self::Time,
         all_channel_waker: Waker,
     ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
-        todo!(/* some variant of poll_inner? */)
+        self.inner.poll_events(time, all_channel_waker)
     }
 
-    fn advance(self: Pin<&mut Self>, time: Self::Time) {
-        todo!(/*
-            move the caller advance header, mark old steps for deletion
-        */)
+    fn release_channel(&mut self, channel: usize) {
+        self.inner.release_channel(channel)
     }
 
-    fn max_channel(&self) -> std::num::NonZeroUsize {
-        // this works out mathematically, as
-        self.source.max_channel()
+    fn advance(&mut self, time: Self::Time) {
+        self.inner.advance(time)
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        self.inner.max_channel()
+    }
+}
+
+impl<Src, T, I, S> StateHashSource for Transpose<Src, T, I, S>
+where
+    Src: Source<Time = T::Time, Event = I::InputEvent, State = I::InputState>,
+    T: TransposerInputEventHandler<I>,
+    SingleInputState<I>: InputState<T>,
+    T: TransposerHash,
+    T::Time: Hash,
+    T::Period: Hash,
+    T::Scheduled: Hash,
+    I: TransposerInput<Base = T>,
+    S: StorageFamily,
+    S::Erasure: EraseInput<I, S>,
+{
+    fn enable_state_hashing(&mut self) {
+        self.inner.enable_state_hashing()
     }
 
-    fn release_channel(self: Pin<&mut Self>, channel: usize) {
-        todo!(/*
-            delete the entry from the channel statuses,
-            and forward the held channel if it was exclusively held
-        */)
+    fn state_hashes(&self, range: (Bound<T::Time>, Bound<T::Time>)) -> Vec<(usize, T::Time, u64)> {
+        self.inner.state_hashes(range)
     }
 }
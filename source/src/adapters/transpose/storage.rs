@@ -1,26 +1,19 @@
 use core::hash::Hash;
 use std::rc::Rc;
-use std::sync::Arc;
 
-use transposer::schedule_storage::StorageFamily;
+use transposer::schedule_storage::{LocalErasure, StorageFamily};
 
+/// A storage family for sources which are only used from one thread.
+///
+/// This is like [`DefaultStorage`](transposer::schedule_storage::DefaultStorage),
+/// but without the cost of atomic reference counts.
 #[derive(Clone, Copy)]
 pub struct TransposeStorage;
 
 impl StorageFamily for TransposeStorage {
     type OrdMap<K: Ord + Eq + Clone, V: Clone> = im_rc::OrdMap<K, V>;
     type HashMap<K: Hash + Eq + Clone, V: Clone> = im_rc::HashMap<K, V>;
-    type Transposer<T: Clone> = Rc<T>;
-    type LazyState<T> = Rc<T>;
-}
-
-// this is never actually instantiated. it is used in a where clause for a Send impl.
-#[derive(Clone, Copy)]
-pub struct DummySendStorage;
-
-impl StorageFamily for DummySendStorage {
-    type OrdMap<K: Ord + Eq + Clone, V: Clone> = im::OrdMap<K, V>;
-    type HashMap<K: Hash + Eq + Clone, V: Clone> = im::HashMap<K, V>;
-    type Transposer<T: Clone> = Arc<T>;
-    type LazyState<T> = Arc<T>;
+    type Transposer<T> = Rc<T>;
+    type LazyState<T: ?Sized> = Rc<T>;
+    type Erasure = LocalErasure;
 }
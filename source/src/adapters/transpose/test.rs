@@ -0,0 +1,255 @@
+use std::num::NonZeroUsize;
+
+use transposer::context::{
+    HandleInputContext,
+    InitContext,
+    InputStateContextExt,
+    InterpolateContext,
+};
+use transposer::schedule_storage::StdStorage;
+use transposer::single_input_state::SingleInputStateManager;
+use transposer::{Transposer, TransposerInput, TransposerInputEventHandler};
+use util::dummy_waker::DummyWaker;
+
+use crate::adapters::{HashedEvent, Transpose};
+use crate::source_poll::Interrupt;
+use crate::sources::transposer::multi_input_transposer::test::{cx, TestSource};
+use crate::sources::transposer::multi_input_transposer::TooFewChannels;
+use crate::sources::vec_source::VecSource;
+use crate::traits::SourceExt;
+use crate::{Source, SourcePoll};
+
+#[derive(Clone, Hash)]
+struct SumTransposer {
+    total: usize,
+}
+
+struct SumInput;
+
+impl TransposerInput for SumInput {
+    type Base = SumTransposer;
+    type InputEvent = usize;
+    type InputState = usize;
+
+    const SORT: u64 = 0;
+}
+
+impl Transposer for SumTransposer {
+    type Time = usize;
+
+    type OutputEvent = usize;
+
+    // (total of inputs, state of the source)
+    type OutputState = (usize, usize);
+
+    type Scheduled = ();
+
+    type InputStateManager = SingleInputStateManager<SumInput>;
+
+    async fn init(&mut self, _cx: &mut impl InitContext<Self>) {}
+
+    async fn interpolate(&self, cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {
+        let state = cx.get_input_state::<SumInput>().await;
+        (self.total, *state)
+    }
+}
+
+impl TransposerInputEventHandler<SumInput> for SumTransposer {
+    async fn handle_input(&mut self, event: &usize, cx: &mut impl HandleInputContext<Self>) {
+        self.total += event;
+        let state = *cx.get_input_state::<SumInput>().await;
+        cx.emit_event(self.total * 100 + state).await;
+    }
+}
+
+#[allow(unused)]
+fn transpose_is_send() {
+    fn assert_send<T: Send>() {}
+
+    type Src = VecSource<usize, usize, fn(usize) -> usize>;
+    assert_send::<Transpose<Src, SumTransposer, SumInput>>();
+    assert_send::<Transpose<Src, SumTransposer, SumInput, StdStorage>>();
+}
+
+#[test]
+fn events_and_states_test() {
+    let source = TestSource::default();
+    source.0.borrow_mut().finalized = true;
+    source.insert(1, 1);
+    source.insert(3, 10);
+
+    let mut transpose = source
+        .transpose(
+            SumTransposer {
+                total: 0
+            },
+            0,
+            [0; 32],
+        )
+        .unwrap();
+
+    let mut emitted = Vec::new();
+    loop {
+        match transpose.poll_events(5, DummyWaker::dummy()).ok().unwrap() {
+            SourcePoll::Ready {
+                next_event_at, ..
+            } => {
+                assert_eq!(next_event_at, None);
+                break
+            },
+            SourcePoll::Interrupt {
+                time,
+                interrupt: Interrupt::Event(e),
+            } => emitted.push((time, e)),
+            SourcePoll::Interrupt {
+                interrupt: Interrupt::Finalize,
+                ..
+            } => {},
+            _ => panic!(),
+        }
+    }
+
+    assert_eq!(emitted, vec![(1, 101), (3, 1102)]);
+
+    match transpose.poll(5, cx(0)).ok().unwrap() {
+        SourcePoll::Ready {
+            state, ..
+        } => assert_eq!(state, (11, 2)),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn too_few_channels_test() {
+    let transposer = SumTransposer {
+        total: 0
+    };
+
+    // the top channel is reserved, and callers need at least two.
+    let source = TestSource::default();
+    source.0.borrow_mut().max_channel = NonZeroUsize::new(1);
+    assert!(matches!(
+        source.transpose(transposer.clone(), 0, [0; 32]),
+        Err(TooFewChannels)
+    ));
+
+    let source = TestSource::default();
+    source.0.borrow_mut().max_channel = NonZeroUsize::new(2);
+    let transpose = source.transpose(transposer, 0, [0; 32]).unwrap();
+    assert_eq!(transpose.max_channel().get(), 1);
+}
+
+// poll at time 10 until a state is ready, recording (time, is_rollback) for every interrupt.
+fn poll_until_ready<S: Source<Time = usize>>(source: &mut S) -> (S::State, Vec<(usize, bool)>) {
+    let mut interrupts = Vec::new();
+    loop {
+        match source.poll(10, cx(0)).ok().unwrap() {
+            SourcePoll::Ready {
+                state, ..
+            } => return (state, interrupts),
+            SourcePoll::Interrupt {
+                time,
+                interrupt,
+            } => interrupts.push((time, matches!(interrupt, Interrupt::Rollback))),
+            SourcePoll::Pending => panic!(),
+        }
+    }
+}
+
+#[test]
+fn late_input_rolls_back_test() {
+    let source = TestSource::default();
+    source.insert(1, 1);
+    source.insert(8, 100);
+
+    let mut transpose = source
+        .clone()
+        .transpose(
+            SumTransposer {
+                total: 0
+            },
+            0,
+            [0; 32],
+        )
+        .unwrap();
+
+    let (state, interrupts) = poll_until_ready(&mut transpose);
+    assert_eq!(state, (101, 2));
+    assert_eq!(interrupts, vec![(1, false), (8, false)]);
+
+    source.insert(5, 10);
+
+    let (state, interrupts) = poll_until_ready(&mut transpose);
+    assert_eq!(state, (111, 3));
+    assert_eq!(interrupts, vec![(5, true), (5, false), (8, false)]);
+}
+
+type HashedRun = (Vec<(usize, usize)>, Vec<(usize, u64)>);
+
+// poll events up to time 5, recording (time, event) for events and (time, hash) for state hashes.
+fn hashed_run(inputs: &[(usize, usize)]) -> HashedRun {
+    let source = TestSource::default();
+    source.0.borrow_mut().finalized = true;
+    for (time, event) in inputs {
+        source.insert(*time, *event);
+    }
+
+    let mut hashes = source
+        .transpose(
+            SumTransposer {
+                total: 0
+            },
+            0,
+            [0; 32],
+        )
+        .unwrap()
+        .state_hashes();
+
+    let mut events = Vec::new();
+    let mut state_hashes = Vec::new();
+    loop {
+        match hashes.poll_events(5, DummyWaker::dummy()).ok().unwrap() {
+            SourcePoll::Ready {
+                ..
+            } => break,
+            SourcePoll::Interrupt {
+                time,
+                interrupt: Interrupt::Event(HashedEvent::Event(e)),
+            } => {
+                // every step before an event has had its hash emitted.
+                assert_eq!(state_hashes.last().map(|(t, _)| *t < time), Some(true));
+                events.push((time, e))
+            },
+            SourcePoll::Interrupt {
+                time,
+                interrupt: Interrupt::Event(HashedEvent::StateHash(hash)),
+            } => state_hashes.push((time, hash)),
+            SourcePoll::Interrupt {
+                interrupt: Interrupt::Finalize,
+                ..
+            } => {},
+            _ => panic!(),
+        }
+    }
+
+    (events, state_hashes)
+}
+
+#[test]
+fn state_hashes_test() {
+    let (events, hashes) = hashed_run(&[(1, 1), (3, 10)]);
+    assert_eq!(events, vec![(1, 101), (3, 1102)]);
+    assert_eq!(hashes.iter().map(|(t, _)| *t).collect::<Vec<_>>(), vec![
+        0, 1, 3
+    ]);
+
+    // the same inputs always hash the same.
+    let (_, same_hashes) = hashed_run(&[(1, 1), (3, 10)]);
+    assert_eq!(hashes, same_hashes);
+
+    // a different input pinpoints the first divergent step.
+    let (_, other_hashes) = hashed_run(&[(1, 1), (3, 11)]);
+    assert_eq!(hashes[..2], other_hashes[..2]);
+    assert_eq!(hashes[2].0, other_hashes[2].0);
+    assert_ne!(hashes[2].1, other_hashes[2].1);
+}
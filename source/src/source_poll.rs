@@ -24,6 +24,7 @@ pub enum SourcePoll<T, E, S> {
 }
 
 /// The type of interrupt emitted from the source
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Interrupt<E> {
     /// A new event is available.
     Event(E),
@@ -49,3 +50,75 @@ pub enum SourcePollErr<T, Err> {
 }
 
 pub type TrySourcePoll<T, E, S, Err> = Result<SourcePoll<T, E, S>, SourcePollErr<T, Err>>;
+
+impl<T, E, S> SourcePoll<T, E, S> {
+    /// Map the state of a [`Ready`](SourcePoll::Ready) poll, leaving everything else untouched.
+    pub fn map_state<S2>(self, f: impl FnOnce(S) -> S2) -> SourcePoll<T, E, S2> {
+        match self {
+            SourcePoll::Ready {
+                state,
+                next_event_at,
+            } => SourcePoll::Ready {
+                state: f(state),
+                next_event_at,
+            },
+            SourcePoll::Interrupt {
+                time,
+                interrupt,
+            } => SourcePoll::Interrupt {
+                time,
+                interrupt,
+            },
+            SourcePoll::Pending => SourcePoll::Pending,
+        }
+    }
+
+    /// Map the event of an [`Interrupt`](SourcePoll::Interrupt), leaving everything else untouched.
+    pub fn map_event<E2>(self, f: impl FnOnce(E) -> E2) -> SourcePoll<T, E2, S> {
+        match self {
+            SourcePoll::Ready {
+                state,
+                next_event_at,
+            } => SourcePoll::Ready {
+                state,
+                next_event_at,
+            },
+            SourcePoll::Interrupt {
+                time,
+                interrupt,
+            } => SourcePoll::Interrupt {
+                time,
+                interrupt: interrupt.map(f),
+            },
+            SourcePoll::Pending => SourcePoll::Pending,
+        }
+    }
+}
+
+impl<E> Interrupt<E> {
+    /// Map the event of an [`Event`](Interrupt::Event) or [`FinalizedEvent`](Interrupt::FinalizedEvent).
+    pub fn map<E2>(self, f: impl FnOnce(E) -> E2) -> Interrupt<E2> {
+        match self {
+            Interrupt::Event(e) => Interrupt::Event(f(e)),
+            Interrupt::FinalizedEvent(e) => Interrupt::FinalizedEvent(f(e)),
+            Interrupt::Rollback => Interrupt::Rollback,
+            Interrupt::Finalize => Interrupt::Finalize,
+        }
+    }
+}
+
+impl<T, Err> SourcePollErr<T, Err> {
+    /// Map the error of a [`SpecificError`](SourcePollErr::SpecificError), leaving everything else untouched.
+    pub fn map_err<Err2>(self, f: impl FnOnce(Err) -> Err2) -> SourcePollErr<T, Err2> {
+        match self {
+            SourcePollErr::OutOfBoundsChannel => SourcePollErr::OutOfBoundsChannel,
+            SourcePollErr::PollAfterAdvance {
+                advanced,
+            } => SourcePollErr::PollAfterAdvance {
+                advanced,
+            },
+            SourcePollErr::PollBeforeDefault => SourcePollErr::PollBeforeDefault,
+            SourcePollErr::SpecificError(err) => SourcePollErr::SpecificError(f(err)),
+        }
+    }
+}
@@ -1 +1,5 @@
+pub mod push;
+#[cfg(feature = "serde")]
+pub mod replay;
 pub mod transposer;
+pub mod vec_source;
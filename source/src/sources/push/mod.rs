@@ -0,0 +1,168 @@
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::task::Waker;
+
+use parking_lot::Mutex;
+
+pub use self::push_sender::{PushSender, SendError};
+use self::shared::Shared;
+use crate::source_poll::{SourcePollErr, TrySourcePoll};
+use crate::traits::SourceContext;
+use crate::{Source, SourcePoll};
+
+mod push_sender;
+mod shared;
+
+#[cfg(test)]
+mod test;
+
+/// A source of events pushed in from a [`PushSender`], like player commands.
+///
+/// The state at a time is the accumulation of every event at or before it,
+/// starting from `initial_state` and applying `accumulate` to each event in time order.
+///
+/// Events may be sent late. If one arrives before an event which has already been emitted,
+/// or before the time of a state returned from [`poll`](Source::poll), the source emits a rollback to the time of the late event.
+pub struct PushSource<T, E, S, F> {
+    shared:     Arc<Mutex<Shared<T, E>>>,
+    // the accumulation of the events which can no longer change, and have been removed from `shared`.
+    base:       S,
+    accumulate: F,
+    advanced:   Option<T>,
+}
+
+impl<T, E, S, F> PushSource<T, E, S, F>
+where
+    T: Ord + Copy,
+    E: Clone,
+    S: Clone,
+    F: Fn(&mut S, &E),
+{
+    pub fn new(initial_state: S, accumulate: F) -> (Self, PushSender<T, E>) {
+        let shared = Arc::new(Mutex::new(Shared::new()));
+
+        let source = Self {
+            shared: shared.clone(),
+            base: initial_state,
+            accumulate,
+            advanced: None,
+        };
+
+        (source, PushSender::new(shared))
+    }
+
+    fn check_advanced(&self, time: T) -> Result<(), SourcePollErr<T, ()>> {
+        match self.advanced {
+            Some(advanced) if time < advanced => Err(SourcePollErr::PollAfterAdvance {
+                advanced,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn poll_internal(&mut self, time: T, waker: Waker, forget: bool) -> TrySourcePoll<T, E, S, ()> {
+        self.check_advanced(time)?;
+
+        let mut shared = self.shared.lock();
+
+        let next_event_at = match shared.poll_events(time, waker) {
+            SourcePoll::Ready {
+                next_event_at, ..
+            } => next_event_at,
+            SourcePoll::Interrupt {
+                time,
+                interrupt,
+            } => {
+                return Ok(SourcePoll::Interrupt {
+                    time,
+                    interrupt,
+                })
+            },
+            SourcePoll::Pending => return Ok(SourcePoll::Pending),
+        };
+
+        if !forget {
+            shared.polled = shared.polled.max(Some(time));
+        }
+
+        let mut state = self.base.clone();
+        for (_, event) in shared.events.iter().take_while(|(t, _)| *t <= time) {
+            (self.accumulate)(&mut state, event);
+        }
+
+        Ok(SourcePoll::Ready {
+            state,
+            next_event_at,
+        })
+    }
+}
+
+impl<T, E, S, F> Source for PushSource<T, E, S, F>
+where
+    T: Ord + Copy,
+    E: Clone,
+    S: Clone,
+    F: Fn(&mut S, &E),
+{
+    type Time = T;
+
+    type Event = E;
+
+    type State = S;
+
+    type Error = ();
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_internal(time, cx.all_channel_waker, false)
+    }
+
+    fn poll_forget(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_internal(time, cx.all_channel_waker, true)
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        self.check_advanced(time)?;
+
+        Ok(self.shared.lock().poll_events(time, all_channel_waker))
+    }
+
+    fn release_channel(&mut self, _channel: usize) {}
+
+    fn advance(&mut self, time: Self::Time) {
+        self.advanced = self.advanced.max(Some(time));
+
+        // events which are both finalized and before the advanced time can never be needed individually again.
+        let mut shared = self.shared.lock();
+        let Some(finalized) = shared.finalized else {
+            return
+        };
+        let fold_before = finalized.min(time);
+        let count = shared
+            .events
+            .iter()
+            .take(shared.emitted)
+            .take_while(|(t, _)| *t < fold_before)
+            .count();
+
+        for (_, event) in shared.events.drain(..count) {
+            (self.accumulate)(&mut self.base, &event);
+        }
+        shared.emitted -= count;
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        NonZeroUsize::MAX
+    }
+}
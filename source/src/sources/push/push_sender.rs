@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use super::shared::Shared;
+
+/// The handle used to feed events into a [`PushSource`](super::PushSource).
+///
+/// This can be cloned to feed the same source from several places.
+pub struct PushSender<T, E> {
+    shared: Arc<Mutex<Shared<T, E>>>,
+}
+
+/// The event could not be sent, because its time has already been finalized.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SendError<E>(pub E);
+
+impl<T: Ord + Copy, E> PushSender<T, E> {
+    pub(super) fn new(shared: Arc<Mutex<Shared<T, E>>>) -> Self {
+        Self {
+            shared,
+        }
+    }
+
+    /// Add an event to the source.
+    ///
+    /// Events may arrive in any order. If an event is earlier than something the source has already emitted or polled,
+    /// the source rolls back to the time of the event.
+    pub fn send(&self, time: T, event: E) -> Result<(), SendError<E>> {
+        let mut shared = self.shared.lock();
+
+        if shared.finalized.is_some_and(|finalized| time < finalized) {
+            return Err(SendError(event))
+        }
+
+        shared.insert(time, event);
+        Ok(())
+    }
+
+    /// Promise that no events will be sent before `time`.
+    ///
+    /// Once the source has caught up to `time`, it emits [`Finalize`](crate::source_poll::Interrupt::Finalize).
+    pub fn finalize(&self, time: T) {
+        self.shared.lock().finalize(time)
+    }
+}
+
+impl<T, E> Clone for PushSender<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
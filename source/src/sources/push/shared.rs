@@ -0,0 +1,107 @@
+use std::task::Waker;
+
+use crate::source_poll::Interrupt;
+use crate::SourcePoll;
+
+// everything the sender and the source both touch.
+pub struct Shared<T, E> {
+    // every event which hasn't been folded into the base state, in time order.
+    pub events:           Vec<(T, E)>,
+    // how many of `events` have been emitted.
+    pub emitted:          usize,
+    pub rollback:         Option<T>,
+    // the latest time a state was returned from `poll`, since the last rollback.
+    pub polled:           Option<T>,
+    pub finalized:        Option<T>,
+    pub finalize_emitted: Option<T>,
+    pub waker:            Option<Waker>,
+}
+
+impl<T: Ord + Copy, E> Shared<T, E> {
+    pub fn new() -> Self {
+        Self {
+            events:           Vec::new(),
+            emitted:          0,
+            rollback:         None,
+            polled:           None,
+            finalized:        None,
+            finalize_emitted: None,
+            waker:            None,
+        }
+    }
+
+    pub fn insert(&mut self, time: T, event: E) {
+        let i = self.events.partition_point(|(t, _)| *t <= time);
+        self.events.insert(i, (time, event));
+
+        // anything emitted or polled at or after this event is now wrong.
+        let emitted_after = i < self.emitted;
+        let polled_after = self.polled.is_some_and(|polled| time <= polled);
+        if emitted_after || polled_after {
+            self.rollback = Some(self.rollback.map_or(time, |rollback| rollback.min(time)));
+
+            // a rollback discards every emitted event at or after its time, so those need to be emitted again.
+            let before = self.events.partition_point(|(t, _)| *t < time);
+            self.emitted = self.emitted.min(before);
+        }
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake()
+        }
+    }
+
+    pub fn finalize(&mut self, time: T) {
+        self.finalized = self.finalized.max(Some(time));
+
+        if let Some(waker) = self.waker.take() {
+            waker.wake()
+        }
+    }
+
+    pub fn poll_events(&mut self, time: T, waker: Waker) -> SourcePoll<T, E, ()>
+    where
+        E: Clone,
+    {
+        self.waker = Some(waker);
+
+        if let Some(rollback) = self.rollback.take() {
+            self.polled = None;
+            return SourcePoll::Interrupt {
+                time:      rollback,
+                interrupt: Interrupt::Rollback,
+            }
+        }
+
+        if let Some((t, event)) = self.events.get(self.emitted) {
+            if *t <= time {
+                self.emitted += 1;
+                let interrupt = if self.finalized.is_some_and(|finalized| *t < finalized) {
+                    Interrupt::FinalizedEvent(event.clone())
+                } else {
+                    Interrupt::Event(event.clone())
+                };
+
+                return SourcePoll::Interrupt {
+                    time: *t,
+                    interrupt,
+                }
+            }
+        }
+
+        // every event up to `time` has been emitted, so any finalize up to `time` can be too.
+        if let Some(finalized) = self.finalized {
+            if finalized <= time && self.finalize_emitted < Some(finalized) {
+                self.finalize_emitted = Some(finalized);
+                return SourcePoll::Interrupt {
+                    time:      finalized,
+                    interrupt: Interrupt::Finalize,
+                }
+            }
+        }
+
+        SourcePoll::Ready {
+            state:         (),
+            next_event_at: self.events.get(self.emitted).map(|(t, _)| *t),
+        }
+    }
+}
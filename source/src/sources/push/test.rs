@@ -0,0 +1,110 @@
+use util::dummy_waker::DummyWaker;
+
+use super::{PushSender, PushSource, SendError};
+use crate::source_poll::Interrupt;
+use crate::sources::transposer::multi_input_transposer::test::cx;
+use crate::{Source, SourcePoll};
+
+type SumSource = PushSource<usize, usize, usize, fn(&mut usize, &usize)>;
+
+fn sum_source() -> (SumSource, PushSender<usize, usize>) {
+    PushSource::new(0, |sum, event| *sum += event)
+}
+
+// poll at `time` until ready, recording (time, is_rollback) for every interrupt.
+fn poll_until_ready(source: &mut SumSource, time: usize) -> (usize, Vec<(usize, bool)>) {
+    let mut interrupts = Vec::new();
+    loop {
+        match source.poll(time, cx(0)).ok().unwrap() {
+            SourcePoll::Ready {
+                state, ..
+            } => return (state, interrupts),
+            SourcePoll::Interrupt {
+                time,
+                interrupt,
+            } => interrupts.push((time, matches!(interrupt, Interrupt::Rollback))),
+            SourcePoll::Pending => panic!(),
+        }
+    }
+}
+
+#[test]
+fn late_events_roll_back_test() {
+    let (mut source, sender) = sum_source();
+    sender.send(3, 30).unwrap();
+    sender.send(1, 10).unwrap();
+
+    assert_eq!(
+        poll_until_ready(&mut source, 5),
+        (40, vec![(1, false), (3, false)])
+    );
+
+    // earlier than an emitted event.
+    sender.send(2, 20).unwrap();
+    assert_eq!(
+        poll_until_ready(&mut source, 5),
+        (60, vec![(2, true), (2, false), (3, false)])
+    );
+
+    // after every emitted event, but before the polled state.
+    sender.send(4, 40).unwrap();
+    assert_eq!(
+        poll_until_ready(&mut source, 5),
+        (100, vec![(4, true), (4, false)])
+    );
+
+    // states from poll_forget don't need rolling back.
+    assert!(matches!(
+        source.poll_forget(8, cx(0)).ok().unwrap(),
+        SourcePoll::Ready {
+            state: 100,
+            ..
+        }
+    ));
+    sender.send(7, 70).unwrap();
+    assert!(matches!(
+        source.poll_events(7, DummyWaker::dummy()).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      7,
+            interrupt: Interrupt::Event(70),
+        }
+    ));
+}
+
+#[test]
+fn finalize_test() {
+    let (mut source, sender) = sum_source();
+    sender.send(1, 10).unwrap();
+    sender.send(6, 60).unwrap();
+    sender.finalize(4);
+
+    assert_eq!(sender.send(3, 30), Err(SendError(30)));
+
+    assert!(matches!(
+        source.poll_events(5, DummyWaker::dummy()).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      1,
+            interrupt: Interrupt::FinalizedEvent(10),
+        }
+    ));
+    assert!(matches!(
+        source.poll_events(5, DummyWaker::dummy()).ok().unwrap(),
+        SourcePoll::Interrupt {
+            time:      4,
+            interrupt: Interrupt::Finalize,
+        }
+    ));
+    assert!(matches!(
+        source.poll_events(5, DummyWaker::dummy()).ok().unwrap(),
+        SourcePoll::Ready {
+            next_event_at: Some(6),
+            ..
+        }
+    ));
+
+    // finalized events are folded away on advance, without changing the state.
+    source.advance(5);
+    assert_eq!(source.shared.lock().events.len(), 1);
+    assert_eq!(poll_until_ready(&mut source, 6), (70, vec![(6, false)]));
+    assert!(source.poll(4, cx(0)).is_err());
+}
@@ -0,0 +1,104 @@
+//! The replay file format.
+//!
+//! A replay file is the magic bytes, then the format version as a little endian `u32`,
+//! then a [`ReplayHeader`] and every recorded `(time, interrupt)` pair, each encoded as CBOR.
+
+use std::io::{self, BufRead, Read, Write};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::source_poll::Interrupt;
+
+pub(crate) const MAGIC: [u8; 8] = *b"COZALRPL";
+
+/// The version written by this version of cozal. Files with any other version are rejected.
+pub const REPLAY_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ReplayHeader<T> {
+    pub start_time: T,
+    pub rng_seed:   [u8; 32],
+}
+
+/// An error encountered while reading a replay file.
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+
+    /// The file does not start with the replay magic bytes.
+    NotAReplay,
+
+    /// The file was written with a different version of the format.
+    UnsupportedVersion(u32),
+
+    /// The header or a record could not be decoded.
+    Decode(String),
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        ReplayError::Io(err)
+    }
+}
+
+fn encode<V: Serialize, W: Write>(value: &V, writer: W) -> io::Result<()> {
+    ciborium::into_writer(value, writer).map_err(|err| match err {
+        ciborium::ser::Error::Io(err) => err,
+        ciborium::ser::Error::Value(msg) => io::Error::new(io::ErrorKind::InvalidData, msg),
+    })
+}
+
+fn decode<V: DeserializeOwned, R: Read>(reader: R) -> Result<V, ReplayError> {
+    ciborium::from_reader(reader).map_err(|err| match err {
+        ciborium::de::Error::Io(err) => ReplayError::Io(err),
+        err => ReplayError::Decode(err.to_string()),
+    })
+}
+
+pub(crate) fn write_header<T: Serialize, W: Write>(
+    mut writer: W,
+    header: &ReplayHeader<T>,
+) -> io::Result<()> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&REPLAY_FORMAT_VERSION.to_le_bytes())?;
+    encode(header, writer)
+}
+
+pub(crate) fn write_record<T: Serialize, E: Serialize, W: Write>(
+    writer: W,
+    time: &T,
+    interrupt: &Interrupt<E>,
+) -> io::Result<()> {
+    encode(&(time, interrupt), writer)
+}
+
+pub(crate) fn read_header<T: DeserializeOwned, R: Read>(
+    mut reader: R,
+) -> Result<ReplayHeader<T>, ReplayError> {
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(ReplayError::NotAReplay)
+    }
+
+    let mut version = [0; 4];
+    reader.read_exact(&mut version)?;
+    let version = u32::from_le_bytes(version);
+    if version != REPLAY_FORMAT_VERSION {
+        return Err(ReplayError::UnsupportedVersion(version))
+    }
+
+    decode(reader)
+}
+
+/// read the next record, or `None` at the end of the file.
+pub(crate) fn read_record<T: DeserializeOwned, E: DeserializeOwned, R: BufRead>(
+    mut reader: R,
+) -> Result<Option<(T, Interrupt<E>)>, ReplayError> {
+    if reader.fill_buf()?.is_empty() {
+        return Ok(None)
+    }
+
+    decode(reader).map(Some)
+}
@@ -0,0 +1,123 @@
+mod format;
+#[cfg(test)]
+mod test;
+
+use std::collections::VecDeque;
+use std::io::{BufReader, Read};
+use std::num::NonZeroUsize;
+use std::task::Waker;
+
+pub(crate) use format::{write_header, write_record, ReplayHeader};
+pub use format::{ReplayError, REPLAY_FORMAT_VERSION};
+use serde::de::DeserializeOwned;
+
+use crate::source_poll::{Interrupt, SourcePollErr, TrySourcePoll};
+use crate::traits::SourceContext;
+use crate::{Source, SourcePoll};
+
+/// A source which plays back a file written by [`Record`](crate::adapters::Record).
+///
+/// The recorded interrupts are emitted in exactly the order they were recorded,
+/// each one as soon as the source is polled at or after its time.
+/// Only the interrupts are recorded, so the state is always `()`.
+pub struct ReplaySource<T, E> {
+    start_time: T,
+    rng_seed:   [u8; 32],
+    records:    VecDeque<(T, Interrupt<E>)>,
+    advanced:   Option<T>,
+}
+
+impl<T, E> ReplaySource<T, E>
+where
+    T: Ord + Copy + DeserializeOwned,
+    E: DeserializeOwned,
+{
+    /// read an entire replay file.
+    pub fn new<R: Read>(reader: R) -> Result<Self, ReplayError> {
+        let mut reader = BufReader::new(reader);
+        let ReplayHeader {
+            start_time,
+            rng_seed,
+        } = format::read_header(&mut reader)?;
+
+        let mut records = VecDeque::new();
+        while let Some(record) = format::read_record(&mut reader)? {
+            records.push_back(record);
+        }
+
+        Ok(Self {
+            start_time,
+            rng_seed,
+            records,
+            advanced: None,
+        })
+    }
+}
+
+impl<T, E> ReplaySource<T, E> {
+    /// the start time of the recorded transposer.
+    pub fn start_time(&self) -> &T {
+        &self.start_time
+    }
+
+    /// the rng seed of the recorded transposer.
+    pub fn rng_seed(&self) -> [u8; 32] {
+        self.rng_seed
+    }
+}
+
+impl<T: Ord + Copy, E> Source for ReplaySource<T, E> {
+    type Time = T;
+
+    type Event = E;
+
+    type State = ();
+
+    type Error = ();
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_events(time, cx.all_channel_waker)
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        _all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        if let Some(advanced) = self.advanced {
+            if time < advanced {
+                return Err(SourcePollErr::PollAfterAdvance {
+                    advanced,
+                })
+            }
+        }
+
+        match self.records.front() {
+            Some((t, _)) if *t <= time => {
+                let (time, interrupt) = self.records.pop_front().unwrap();
+                Ok(SourcePoll::Interrupt {
+                    time,
+                    interrupt,
+                })
+            },
+            next => Ok(SourcePoll::Ready {
+                state:         (),
+                next_event_at: next.map(|(t, _)| *t),
+            }),
+        }
+    }
+
+    fn release_channel(&mut self, _channel: usize) {}
+
+    fn advance(&mut self, time: Self::Time) {
+        self.advanced = self.advanced.max(Some(time));
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        NonZeroUsize::MAX
+    }
+}
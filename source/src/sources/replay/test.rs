@@ -0,0 +1,88 @@
+use util::dummy_waker::DummyWaker;
+
+use super::{ReplayError, ReplaySource};
+use crate::source_poll::Interrupt;
+use crate::sources::transposer::multi_input_transposer::test::TestSource;
+use crate::traits::SourceExt;
+use crate::{Source, SourcePoll};
+
+// (time, kind, event) for every interrupt up to `time`.
+fn drain<S: Source<Time = usize, Event = usize>>(
+    source: &mut S,
+    time: usize,
+) -> Vec<(usize, &'static str, Option<usize>)> {
+    let mut interrupts = Vec::new();
+    loop {
+        match source.poll_events(time, DummyWaker::dummy()).ok().unwrap() {
+            SourcePoll::Ready {
+                ..
+            } => return interrupts,
+            SourcePoll::Interrupt {
+                time,
+                interrupt,
+            } => interrupts.push(match interrupt {
+                Interrupt::Event(e) => (time, "event", Some(e)),
+                Interrupt::FinalizedEvent(e) => (time, "finalized event", Some(e)),
+                Interrupt::Rollback => (time, "rollback", None),
+                Interrupt::Finalize => (time, "finalize", None),
+            }),
+            SourcePoll::Pending => panic!(),
+        }
+    }
+}
+
+#[test]
+fn record_replay_test() {
+    let source = TestSource::default();
+    source.insert(1, 1);
+    source.insert(4, 4);
+
+    let mut recorded = Vec::new();
+    let mut record = source
+        .clone()
+        .map_state(|_| ())
+        .record(&mut recorded, 7, [3; 32])
+        .unwrap();
+
+    let mut expected = drain(&mut record, 5);
+    source.insert(2, 2);
+    source.0.borrow_mut().finalized = true;
+    source.insert(6, 6);
+    expected.extend(drain(&mut record, 10));
+    drop(record);
+
+    assert_eq!(expected, vec![
+        (1, "event", Some(1)),
+        (4, "event", Some(4)),
+        (2, "rollback", None),
+        (2, "finalized event", Some(2)),
+        (4, "finalized event", Some(4)),
+        (6, "finalized event", Some(6)),
+    ]);
+
+    let mut replay = ReplaySource::<usize, usize>::new(recorded.as_slice()).unwrap();
+    assert_eq!(*replay.start_time(), 7);
+    assert_eq!(replay.rng_seed(), [3; 32]);
+
+    let mut replayed = drain(&mut replay, 5);
+    replayed.extend(drain(&mut replay, 10));
+    assert_eq!(replayed, expected);
+}
+
+#[test]
+fn replay_version_test() {
+    let mut recorded = Vec::new();
+    drop(
+        TestSource::default()
+            .map_state(|_| ())
+            .record(&mut recorded, 0, [0; 32])
+            .unwrap(),
+    );
+
+    recorded[8] += 1;
+    let replay = ReplaySource::<usize, usize>::new(recorded.as_slice());
+    assert!(matches!(replay, Err(ReplayError::UnsupportedVersion(2))));
+
+    let replay = ReplaySource::<usize, usize>::new(&b"not a replay"[..]);
+    assert!(matches!(replay, Err(ReplayError::NotAReplay)));
+}
@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 use std::sync::Weak;
 
-use transposer::schedule_storage::DefaultStorage;
-use transposer::step::{Interpolation, NoInput, NoInputManager};
+use transposer::schedule_storage::StorageFamily;
+use transposer::step::{InputState, Interpolation};
 use transposer::Transposer;
 use util::extended_entry::hash_map::{get_occupied, VacantExtEntry as HashMapVacantEntry};
 use util::stack_waker::StackWaker;
@@ -12,21 +12,21 @@ use super::original_step_future::OriginalStepFuture;
 use super::repeat_step_future::RepeatStepFuture;
 use super::{get_pinned_times, CallerChannelBlockedReason, CallerChannelBlockedReasonInner};
 
-pub struct Free<'a, T: Transposer<InputStateManager = NoInputManager>> {
+pub struct Free<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> {
     // entries
-    pub caller_channel: HashMapVacantEntry<'a, usize, CallerChannelBlockedReason<T>>,
+    pub caller_channel: HashMapVacantEntry<'a, usize, CallerChannelBlockedReason<T, Is, S>>,
 
     // extra
     pub blocked_repeat_step_wakers:
         &'a mut HashMap</* step_id */ usize, (usize, Weak<StackWaker>)>,
 }
 
-impl<'a, T: Transposer<InputStateManager = NoInputManager>> Free<'a, T> {
+impl<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> Free<'a, T, Is, S> {
     pub fn start_interpolation(
         self,
-        interpolation: Interpolation<T, NoInput, DefaultStorage>,
+        interpolation: Interpolation<T, Is, S>,
         poll_time: T::Time,
-    ) -> InterpolationFuture<'a, T> {
+    ) -> InterpolationFuture<'a, T, Is, S> {
         let Self {
             caller_channel: vacant_channel,
             blocked_repeat_step_wakers,
@@ -45,7 +45,11 @@ impl<'a, T: Transposer<InputStateManager = NoInputManager>> Free<'a, T> {
         }
     }
 
-    pub fn start_repeat_step(self, step_id: usize, poll_time: T::Time) -> RepeatStepFuture<'a, T> {
+    pub fn start_repeat_step(
+        self,
+        step_id: usize,
+        poll_time: T::Time,
+    ) -> RepeatStepFuture<'a, T, Is, S> {
         let Self {
             caller_channel: vacant_channel,
             blocked_repeat_step_wakers,
@@ -72,7 +76,7 @@ impl<'a, T: Transposer<InputStateManager = NoInputManager>> Free<'a, T> {
         }
     }
 
-    pub fn start_original_step(self, poll_time: T::Time) -> OriginalStepFuture<'a, T> {
+    pub fn start_original_step(self, poll_time: T::Time) -> OriginalStepFuture<'a, T, Is, S> {
         let Self {
             caller_channel: vacant_channel,
             blocked_repeat_step_wakers,
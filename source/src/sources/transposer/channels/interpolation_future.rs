@@ -3,7 +3,8 @@ use std::sync::Weak;
 use std::task::{Context, Poll, Waker};
 
 use futures_core::Future;
-use transposer::step::NoInputManager;
+use transposer::schedule_storage::StorageFamily;
+use transposer::step::InputState;
 use transposer::Transposer;
 use util::extended_entry::hash_map::OccupiedExtEntry as HashMapOccupiedEntry;
 use util::stack_waker::StackWaker;
@@ -11,20 +12,20 @@ use util::stack_waker::StackWaker;
 use super::{CallerChannelBlockedReason, CallerChannelBlockedReasonInner, CallerChannelStatus};
 use crate::sources::transposer::channels::free::Free;
 
-pub struct InterpolationFuture<'a, T: Transposer<InputStateManager = NoInputManager>> {
+pub struct InterpolationFuture<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> {
     // entries
-    pub caller_channel: HashMapOccupiedEntry<'a, usize, CallerChannelBlockedReason<T>>,
+    pub caller_channel: HashMapOccupiedEntry<'a, usize, CallerChannelBlockedReason<T, Is, S>>,
 
     // extra
     pub blocked_repeat_step_wakers:
         &'a mut HashMap</* step_id */ usize, (usize, Weak<StackWaker>)>,
 }
 
-impl<'a, T: Transposer<InputStateManager = NoInputManager>> InterpolationFuture<'a, T> {
+impl<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> InterpolationFuture<'a, T, Is, S> {
     pub fn poll(
         self,
         one_channel_waker: &Waker,
-    ) -> (CallerChannelStatus<'a, T>, Poll<T::OutputState>) {
+    ) -> (CallerChannelStatus<'a, T, Is, S>, Poll<T::OutputState>) {
         let Self {
             mut caller_channel,
             blocked_repeat_step_wakers,
@@ -55,7 +56,7 @@ impl<'a, T: Transposer<InputStateManager = NoInputManager>> InterpolationFuture<
         (status, poll)
     }
 
-    pub fn abandon(self) -> Free<'a, T> {
+    pub fn abandon(self) -> Free<'a, T, Is, S> {
         let Self {
             caller_channel,
             blocked_repeat_step_wakers,
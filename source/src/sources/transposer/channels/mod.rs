@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 use std::sync::Weak;
 
-use transposer::schedule_storage::DefaultStorage;
-use transposer::step::{Interpolation, NoInput, NoInputManager};
+use transposer::schedule_storage::StorageFamily;
+use transposer::step::{InputState, Interpolation};
 use transposer::Transposer;
 use util::extended_entry::hash_map::get_occupied;
 use util::stack_waker::StackWaker;
@@ -21,11 +21,11 @@ pub mod repeat_step_future;
 // own steps.
 // own interpolations.
 // own one_channel_wakers.
-pub struct ChannelStatuses<T: Transposer<InputStateManager = NoInputManager>> {
+pub struct ChannelStatuses<T: Transposer, Is: InputState<T>, S: StorageFamily> {
     // These are all the currently pending operations, from the perspective of the caller.
     // They can be blocked due to pending source_state, step_future, or interpolation_future.
     pub blocked_caller_channels:
-        HashMap</* caller_channel */ usize, CallerChannelBlockedReason<T>>,
+        HashMap</* caller_channel */ usize, CallerChannelBlockedReason<T, Is, S>>,
 
     // these are the wakers currently registered to each repeat step.
     pub blocked_repeat_step_wakers: HashMap<
@@ -37,7 +37,7 @@ pub struct ChannelStatuses<T: Transposer<InputStateManager = NoInputManager>> {
     >,
 }
 
-impl<T: Transposer<InputStateManager = NoInputManager>> ChannelStatuses<T> {
+impl<T: Transposer, Is: InputState<T>, S: StorageFamily> ChannelStatuses<T, Is, S> {
     pub fn new() -> Self {
         Self {
             blocked_caller_channels:    HashMap::new(),
@@ -49,10 +49,32 @@ impl<T: Transposer<InputStateManager = NoInputManager>> ChannelStatuses<T> {
         get_pinned_times(&self.blocked_caller_channels)
     }
 
+    /// abandon every blocked caller channel polling at or after `time`.
+    ///
+    /// this is needed when the steps these channels were blocked on have been rolled back.
+    /// returns whether any channels were abandoned.
+    pub fn reset_after(&mut self, time: T::Time) -> bool {
+        let channels: Vec<usize> = self
+            .blocked_caller_channels
+            .iter()
+            .filter(|(_, blocked)| blocked.poll_time >= time)
+            .map(|(channel, _)| *channel)
+            .collect();
+
+        for channel in channels.iter() {
+            let _ = self.get_channel_status(*channel).abandon();
+        }
+
+        !channels.is_empty()
+    }
+
     /// from the current state, get the status.
     ///
     /// this internally holds mutable refs to the ChannelStatuses
-    pub fn get_channel_status(&mut self, caller_channel: usize) -> CallerChannelStatus<'_, T> {
+    pub fn get_channel_status(
+        &mut self,
+        caller_channel: usize,
+    ) -> CallerChannelStatus<'_, T, Is, S> {
         match get_occupied(&mut self.blocked_caller_channels, caller_channel) {
             Ok(occupied) => match occupied.get_value().inner {
                 CallerChannelBlockedReasonInner::OriginalStep => {
@@ -83,18 +105,18 @@ impl<T: Transposer<InputStateManager = NoInputManager>> ChannelStatuses<T> {
     }
 }
 
-pub struct CallerChannelBlockedReason<T: Transposer<InputStateManager = NoInputManager>> {
+pub struct CallerChannelBlockedReason<T: Transposer, Is: InputState<T>, S: StorageFamily> {
     pub poll_time: T::Time,
-    pub inner:     CallerChannelBlockedReasonInner<T>,
+    pub inner:     CallerChannelBlockedReasonInner<T, Is, S>,
 }
 
-pub enum CallerChannelBlockedReasonInner<T: Transposer<InputStateManager = NoInputManager>> {
+pub enum CallerChannelBlockedReasonInner<T: Transposer, Is: InputState<T>, S: StorageFamily> {
     OriginalStep,
     RepeatStep(usize),
-    InterpolationFuture(Interpolation<T, NoInput, DefaultStorage>),
+    InterpolationFuture(Interpolation<T, Is, S>),
 }
 
-impl<T: Transposer<InputStateManager = NoInputManager>> CallerChannelBlockedReason<T> {
+impl<T: Transposer, Is: InputState<T>, S: StorageFamily> CallerChannelBlockedReason<T, Is, S> {
     pub fn get_pinned_time(&self) -> Option<T::Time> {
         match self.inner {
             CallerChannelBlockedReasonInner::OriginalStep
@@ -114,15 +136,26 @@ impl<T: Transposer<InputStateManager = NoInputManager>> CallerChannelBlockedReas
 /// this enum represents the current blocked status for a given channel.
 /// it can move between statuses under various circumstances,
 /// like being provided a source state, or a future polling ready.
-pub enum CallerChannelStatus<'a, T: Transposer<InputStateManager = NoInputManager>> {
-    Free(free::Free<'a, T>),
-    InterpolationFuture(interpolation_future::InterpolationFuture<'a, T>),
-    OriginalStepFuture(original_step_future::OriginalStepFuture<'a, T>),
-    RepeatStepFuture(repeat_step_future::RepeatStepFuture<'a, T>),
+pub enum CallerChannelStatus<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> {
+    Free(free::Free<'a, T, Is, S>),
+    InterpolationFuture(interpolation_future::InterpolationFuture<'a, T, Is, S>),
+    OriginalStepFuture(original_step_future::OriginalStepFuture<'a, T, Is, S>),
+    RepeatStepFuture(repeat_step_future::RepeatStepFuture<'a, T, Is, S>),
+}
+
+impl<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> CallerChannelStatus<'a, T, Is, S> {
+    pub fn abandon(self) -> Free<'a, T, Is, S> {
+        match self {
+            CallerChannelStatus::Free(f) => f,
+            CallerChannelStatus::InterpolationFuture(i) => i.abandon(),
+            CallerChannelStatus::OriginalStepFuture(o) => o.abandon(),
+            CallerChannelStatus::RepeatStepFuture(r) => r.abandon(),
+        }
+    }
 }
 
-pub fn get_pinned_times<T: Transposer<InputStateManager = NoInputManager>>(
-    blocked_caller_channels: &HashMap<usize, CallerChannelBlockedReason<T>>,
+pub fn get_pinned_times<T: Transposer, Is: InputState<T>, S: StorageFamily>(
+    blocked_caller_channels: &HashMap<usize, CallerChannelBlockedReason<T, Is, S>>,
 ) -> Vec<T::Time> {
     blocked_caller_channels
         .values()
@@ -2,8 +2,8 @@ use std::collections::HashMap;
 use std::sync::Weak;
 use std::task::Waker;
 
-use transposer::schedule_storage::DefaultStorage;
-use transposer::step::{NoInput, NoInputManager, Step, StepPoll};
+use transposer::schedule_storage::StorageFamily;
+use transposer::step::{InputState, Step, StepPoll};
 use transposer::Transposer;
 use util::extended_entry::hash_map::OccupiedExtEntry as HashMapOccupiedEntry;
 use util::stack_waker::StackWaker;
@@ -11,20 +11,20 @@ use util::stack_waker::StackWaker;
 use super::free::Free;
 use super::CallerChannelBlockedReason;
 
-pub struct OriginalStepFuture<'a, T: Transposer<InputStateManager = NoInputManager>> {
+pub struct OriginalStepFuture<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> {
     // entries
-    pub caller_channel:             HashMapOccupiedEntry<'a, usize, CallerChannelBlockedReason<T>>,
+    pub caller_channel: HashMapOccupiedEntry<'a, usize, CallerChannelBlockedReason<T, Is, S>>,
     // extra
     pub blocked_repeat_step_wakers:
         &'a mut HashMap</* step_id */ usize, (usize, Weak<StackWaker>)>,
 }
 
-impl<'a, T: Transposer<InputStateManager = NoInputManager>> OriginalStepFuture<'a, T> {
+impl<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> OriginalStepFuture<'a, T, Is, S> {
     pub fn poll(
         self,
-        step: &mut Step<T, NoInput, DefaultStorage>,
+        step: &mut Step<T, Is, S>,
         all_channel_waker: &Waker,
-    ) -> OriginalStepPoll<'a, T> {
+    ) -> OriginalStepPoll<'a, T, Is, S> {
         let Self {
             caller_channel,
             blocked_repeat_step_wakers,
@@ -42,7 +42,7 @@ impl<'a, T: Transposer<InputStateManager = NoInputManager>> OriginalStepFuture<'
         }
     }
 
-    pub fn abandon(self) -> Free<'a, T> {
+    pub fn abandon(self) -> Free<'a, T, Is, S> {
         let Self {
             caller_channel,
             blocked_repeat_step_wakers,
@@ -55,8 +55,8 @@ impl<'a, T: Transposer<InputStateManager = NoInputManager>> OriginalStepFuture<'
     }
 }
 
-pub enum OriginalStepPoll<'a, T: Transposer<InputStateManager = NoInputManager>> {
+pub enum OriginalStepPoll<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> {
     OutputEvent(T::OutputEvent),
-    Free(Free<'a, T>),
+    Free(Free<'a, T, Is, S>),
     Pending,
 }
@@ -1,8 +1,8 @@
 use std::sync::Weak;
 use std::task::{Poll, Waker};
 
-use transposer::schedule_storage::DefaultStorage;
-use transposer::step::{NoInput, NoInputManager, Step, StepPoll};
+use transposer::schedule_storage::StorageFamily;
+use transposer::step::{InputState, Step, StepPoll};
 use transposer::Transposer;
 use util::extended_entry::hash_map::OccupiedExtEntry as HashMapOccupiedEntry;
 use util::stack_waker::StackWaker;
@@ -10,18 +10,18 @@ use util::stack_waker::StackWaker;
 use super::free::Free;
 use super::CallerChannelBlockedReason;
 
-pub struct RepeatStepFuture<'a, T: Transposer<InputStateManager = NoInputManager>> {
+pub struct RepeatStepFuture<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> {
     // entries
-    pub caller_channel: HashMapOccupiedEntry<'a, usize, CallerChannelBlockedReason<T>>,
+    pub caller_channel: HashMapOccupiedEntry<'a, usize, CallerChannelBlockedReason<T, Is, S>>,
     pub wakers: HashMapOccupiedEntry<'a, /* step_id */ usize, (usize, Weak<StackWaker>)>,
 }
 
-impl<'a, T: Transposer<InputStateManager = NoInputManager>> RepeatStepFuture<'a, T> {
+impl<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> RepeatStepFuture<'a, T, Is, S> {
     pub fn poll(
         self,
-        step: &mut Step<T, NoInput, DefaultStorage>,
+        step: &mut Step<T, Is, S>,
         one_channel_waker: &Waker,
-    ) -> Poll<Free<'a, T>> {
+    ) -> Poll<Free<'a, T, Is, S>> {
         let Self {
             caller_channel,
             mut wakers,
@@ -55,7 +55,7 @@ impl<'a, T: Transposer<InputStateManager = NoInputManager>> RepeatStepFuture<'a,
         }
     }
 
-    pub fn abandon(self) -> Free<'a, T> {
+    pub fn abandon(self) -> Free<'a, T, Is, S> {
         let Self {
             caller_channel,
             mut wakers,
@@ -0,0 +1,265 @@
+//! Strategies for choosing which saturated steps a transposer source keeps around as checkpoints.
+//!
+//! Every checkpoint costs memory, and every missing checkpoint costs recomputation when an earlier time is polled.
+//! A strategy is asked which checkpoint to discard each time a new one is about to be created.
+//! The source only ever offers checkpoints which can be discarded while still making progress on every pinned time,
+//! so no strategy can stall a poll.
+
+use transposer::period::RepeatPeriod;
+
+/// A saturated step, as seen by a [`CheckpointStrategy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint<Time> {
+    /// the position of the step among every step ever created by the source.
+    pub sequence_number: usize,
+
+    /// the time of the step.
+    pub time: Time,
+
+    /// the approximate memory used by the step, in bytes.
+    ///
+    /// this comes from [`Transposer::heap_size`](transposer::Transposer::heap_size),
+    /// so it is only as accurate as the transposer's estimate.
+    pub size: usize,
+
+    /// whether the step can be discarded without breaking the progress guarantees of the source.
+    pub evictable: bool,
+}
+
+/// Decides which checkpoints a transposer source discards.
+pub trait CheckpointStrategy<Time>: Send {
+    /// Choose a checkpoint to discard, before `next` is created.
+    ///
+    /// `checkpoints` are the current checkpoints in order. The returned value is an index into `checkpoints`,
+    /// which must be [`evictable`](Checkpoint::evictable). This is called again after each discarded checkpoint,
+    /// until it returns `None` or nothing evictable remains.
+    fn choose_eviction(
+        &mut self,
+        checkpoints: &[Checkpoint<Time>],
+        next: &Checkpoint<Time>,
+    ) -> Option<usize>;
+}
+
+// the evictable checkpoint whose removal disturbs even spacing the least.
+//
+// this picks the one with the fewest trailing zeros in its sequence number, then the earliest,
+// so the remaining checkpoints stay roughly equally spaced.
+fn evenly_spaced_eviction<Time>(checkpoints: &[Checkpoint<Time>]) -> Option<usize> {
+    checkpoints
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.evictable)
+        .min_by_key(|(_, c)| (c.sequence_number.trailing_zeros(), c.sequence_number))
+        .map(|(i, _)| i)
+}
+
+/// Keep a fixed number of checkpoints, spaced roughly evenly.
+///
+/// This is the default strategy, with 30 checkpoints.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedCount(pub usize);
+
+impl Default for FixedCount {
+    fn default() -> Self {
+        Self(30)
+    }
+}
+
+impl<Time> CheckpointStrategy<Time> for FixedCount {
+    fn choose_eviction(
+        &mut self,
+        checkpoints: &[Checkpoint<Time>],
+        _next: &Checkpoint<Time>,
+    ) -> Option<usize> {
+        if checkpoints.len() < self.0 {
+            return None
+        }
+
+        evenly_spaced_eviction(checkpoints)
+    }
+}
+
+/// Keep a fixed number of checkpoints, packed densely near the latest step and sparsely further back.
+///
+/// This suits sources where rollbacks are usually short, but can occasionally reach far back.
+#[derive(Debug, Clone, Copy)]
+pub struct ExponentialSpacing(pub usize);
+
+impl<Time> CheckpointStrategy<Time> for ExponentialSpacing {
+    fn choose_eviction(
+        &mut self,
+        checkpoints: &[Checkpoint<Time>],
+        next: &Checkpoint<Time>,
+    ) -> Option<usize> {
+        if checkpoints.len() < self.0 {
+            return None
+        }
+
+        // steps can be saturated out of order, so the newest step isn't necessarily `next`.
+        let newest = checkpoints
+            .last()
+            .map_or(next.sequence_number, |c| c.sequence_number)
+            .max(next.sequence_number);
+
+        // the gap left behind by each checkpoint, relative to how far back it is.
+        // evicting the smallest keeps the ratio of each gap to its distance roughly constant.
+        let relative_gap = |i: usize| {
+            let before = match i.checked_sub(1) {
+                Some(j) => checkpoints[j].sequence_number,
+                None => checkpoints[i].sequence_number,
+            };
+            let after = checkpoints.get(i + 1).map_or(newest, |c| c.sequence_number);
+            let distance = newest - checkpoints[i].sequence_number + 1;
+
+            (after - before) as f64 / distance as f64
+        };
+
+        (0..checkpoints.len())
+            .filter(|i| checkpoints[*i].evictable)
+            .min_by(|a, b| relative_gap(*a).total_cmp(&relative_gap(*b)))
+    }
+}
+
+/// Keep at most one checkpoint per interval of time, besides the latest.
+///
+/// The number of checkpoints grows with the amount of time covered, but not with how many steps happen in it.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeInterval<Period>(pub Period);
+
+impl<Time, Period> CheckpointStrategy<Time> for TimeInterval<Period>
+where
+    Time: Ord + Copy,
+    Period: RepeatPeriod<Time> + Send,
+{
+    fn choose_eviction(
+        &mut self,
+        checkpoints: &[Checkpoint<Time>],
+        _next: &Checkpoint<Time>,
+    ) -> Option<usize> {
+        let mut last_kept = checkpoints.first()?.time;
+
+        for (i, checkpoint) in checkpoints.iter().enumerate().skip(1) {
+            // an interval which runs past the end of time covers every later checkpoint.
+            let in_interval = self
+                .0
+                .checked_add_to(last_kept)
+                .is_none_or(|end| checkpoint.time < end);
+
+            if checkpoint.evictable && in_interval {
+                return Some(i)
+            }
+
+            last_kept = checkpoint.time;
+        }
+
+        None
+    }
+}
+
+/// Keep the checkpoints within a number of bytes, spaced roughly evenly.
+///
+/// Sizes are estimated with [`Transposer::heap_size`](transposer::Transposer::heap_size),
+/// which reports nothing unless the transposer implements it.
+/// At least the checkpoints needed to make progress are always kept, even if they alone are over budget.
+#[derive(Debug, Clone, Copy)]
+pub struct ByteBudget(pub usize);
+
+impl<Time> CheckpointStrategy<Time> for ByteBudget {
+    fn choose_eviction(
+        &mut self,
+        checkpoints: &[Checkpoint<Time>],
+        next: &Checkpoint<Time>,
+    ) -> Option<usize> {
+        let total: usize = checkpoints.iter().map(|c| c.size).sum::<usize>() + next.size;
+        if total <= self.0 {
+            return None
+        }
+
+        evenly_spaced_eviction(checkpoints)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn checkpoints(sequence_numbers: &[usize]) -> Vec<Checkpoint<usize>> {
+        sequence_numbers
+            .iter()
+            .map(|i| Checkpoint {
+                sequence_number: *i,
+                time:            *i * 10,
+                size:            100,
+                evictable:       *i != 0,
+            })
+            .collect()
+    }
+
+    fn next(sequence_number: usize) -> Checkpoint<usize> {
+        Checkpoint {
+            sequence_number,
+            time: sequence_number * 10,
+            size: 100,
+            evictable: false,
+        }
+    }
+
+    // run a strategy over `steps` steps, returning the sequence numbers it kept.
+    fn run<S: CheckpointStrategy<usize>>(mut strategy: S, steps: usize) -> Vec<usize> {
+        let mut kept = checkpoints(&[0]);
+        for i in 1..steps {
+            let next = next(i);
+            while let Some(evict) = strategy.choose_eviction(&kept, &next) {
+                assert!(kept[evict].evictable);
+                kept.remove(evict);
+            }
+            kept.push(Checkpoint {
+                evictable: true,
+                ..next
+            });
+        }
+
+        kept.iter().map(|c| c.sequence_number).collect()
+    }
+
+    #[test]
+    fn fixed_count_test() {
+        let kept = run(FixedCount(5), 100);
+        assert_eq!(kept.len(), 5);
+        assert_eq!(kept[0], 0);
+        assert_eq!(*kept.last().unwrap(), 99);
+    }
+
+    #[test]
+    fn exponential_spacing_test() {
+        let kept = run(ExponentialSpacing(8), 1000);
+        assert_eq!(kept.len(), 8);
+        assert_eq!(kept[0], 0);
+
+        // the gaps grow going back in time.
+        let gaps: Vec<usize> = kept.windows(2).map(|w| w[1] - w[0]).collect();
+        assert!(gaps[0] > *gaps.last().unwrap());
+        assert!(kept[kept.len() - 2] >= 990);
+    }
+
+    #[test]
+    fn time_interval_test() {
+        let kept = run(TimeInterval(35), 20);
+        let times: Vec<usize> = kept.iter().map(|i| i * 10).collect();
+        assert!(times.windows(2).all(|w| w[1] - w[0] >= 35 || w[1] == 190));
+        assert_eq!(*kept.last().unwrap(), 19);
+    }
+
+    #[test]
+    fn time_interval_overflow_test() {
+        let kept = run(TimeInterval(usize::MAX), 20);
+        assert_eq!(kept, vec![0, 19]);
+    }
+
+    #[test]
+    fn byte_budget_test() {
+        let kept = run(ByteBudget(450), 100);
+        assert_eq!(kept.len(), 4);
+        assert_eq!(kept[0], 0);
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+use transposer::schedule_storage::StorageFamily;
+use transposer::step::StepInputs;
+use transposer::Transposer;
+
+/// inputs which have been received from the sources, but not yet handed to a step.
+pub struct InputBuffer<T: Transposer, S: StorageFamily>(BTreeMap<T::Time, StepInputs<T, S>>);
+
+impl<T: Transposer, S: StorageFamily> InputBuffer<T, S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add inputs, combining them with any already buffered at the same time.
+    pub fn insert(&mut self, inputs: StepInputs<T, S>) {
+        match self.0.entry(inputs.time()) {
+            Entry::Vacant(v) => {
+                v.insert(inputs);
+            },
+            Entry::Occupied(mut o) => o.get_mut().merge(inputs),
+        }
+    }
+
+    /// take the earliest inputs, if they are at or before `time`.
+    pub fn pop_first_before_or_at(&mut self, time: T::Time) -> Option<StepInputs<T, S>> {
+        let entry = self.0.first_entry()?;
+
+        if *entry.key() > time {
+            return None
+        }
+
+        Some(entry.remove())
+    }
+
+    pub fn first_time(&self) -> Option<T::Time> {
+        self.0.first_key_value().map(|(t, _)| *t)
+    }
+
+    /// discard the inputs at or after `time` for the input with this `SORT`.
+    pub fn rollback_input(&mut self, time: T::Time, sort: u64) {
+        let InputBuffer(inner) = self;
+
+        for (_, inputs) in inner.range_mut(time..) {
+            inputs.remove_input(sort);
+        }
+
+        inner.retain(|_, inputs| !inputs.is_empty());
+    }
+}
+
+impl<T: Transposer, S: StorageFamily> Default for InputBuffer<T, S> {
+    fn default() -> Self {
+        Self(BTreeMap::new())
+    }
+}
@@ -0,0 +1,328 @@
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::task::Waker;
+
+use transposer::schedule_storage::StorageFamily;
+use transposer::step::{EraseInput, FulfillInputState, InputState, StepInputs};
+use transposer::{Transposer, TransposerInput, TransposerInputEventHandler};
+
+use crate::source_poll::{Interrupt, SourcePollErr};
+use crate::traits::SourceContext;
+use crate::{Source, SourcePoll};
+
+/// The upstream source for a single [`TransposerInput`].
+///
+/// The events of `Src` become the input events of `I`, and the states of `Src` are handed to
+/// the transposer whenever it requests the input state of `I`.
+pub struct InputSource<I: TransposerInput, Src: Source> {
+    source: Src,
+
+    // all the events the source will emit at or before this time have been received.
+    events_complete: Option<Src::Time>,
+
+    // the next event the source has told us about.
+    next_event_at: Option<Src::Time>,
+
+    // the latest finalize the source has emitted.
+    finalized: Option<Src::Time>,
+
+    phantom: PhantomData<fn() -> I>,
+}
+
+impl<I, Src> InputSource<I, Src>
+where
+    I: TransposerInput,
+    Src: Source<Event = I::InputEvent, State = I::InputState>,
+{
+    pub fn new(source: Src) -> Self {
+        Self {
+            source,
+            events_complete: None,
+            next_event_at: None,
+            finalized: None,
+            phantom: PhantomData,
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn poll_events<T, S>(
+        &mut self,
+        time: T::Time,
+        all_channel_waker: &Waker,
+    ) -> Result<InputsPoll<T, S>, SourcePollErr<T::Time, Src::Error>>
+    where
+        T: Transposer<Time = Src::Time> + TransposerInputEventHandler<I>,
+        S: StorageFamily,
+        S::Erasure: EraseInput<I, S>,
+        I: TransposerInput<Base = T>,
+    {
+        loop {
+            match self.source.poll_events(time, all_channel_waker.clone())? {
+                SourcePoll::Ready {
+                    state: (),
+                    next_event_at,
+                } => {
+                    self.events_complete = self.events_complete.max(Some(time));
+                    self.next_event_at = next_event_at;
+                    return Ok(InputsPoll::Ready)
+                },
+                SourcePoll::Interrupt {
+                    time,
+                    interrupt,
+                } => {
+                    if let Some(poll) = self.handle_interrupt(time, interrupt) {
+                        return Ok(poll)
+                    }
+                },
+                SourcePoll::Pending => return Ok(InputsPoll::Pending),
+            }
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn poll_state<T, Is, S>(
+        &mut self,
+        time: T::Time,
+        input_state: &Is,
+        cx: SourceContext,
+        forget: bool,
+    ) -> Result<InputsPoll<T, S>, SourcePollErr<T::Time, Src::Error>>
+    where
+        T: Transposer<Time = Src::Time> + TransposerInputEventHandler<I>,
+        S: StorageFamily,
+        S::Erasure: EraseInput<I, S>,
+        I: TransposerInput<Base = T>,
+        Is: FulfillInputState<I>,
+    {
+        if !input_state.is_requested() {
+            return Ok(InputsPoll::Ready)
+        }
+
+        loop {
+            let poll = if forget {
+                self.source.poll_forget(time, cx.clone())?
+            } else {
+                self.source.poll(time, cx.clone())?
+            };
+
+            match poll {
+                SourcePoll::Ready {
+                    state, ..
+                } => {
+                    let _ = input_state.set_state(state);
+                    return Ok(InputsPoll::Ready)
+                },
+                SourcePoll::Interrupt {
+                    time,
+                    interrupt,
+                } => {
+                    if let Some(poll) = self.handle_interrupt(time, interrupt) {
+                        return Ok(poll)
+                    }
+                },
+                SourcePoll::Pending => return Ok(InputsPoll::Pending),
+            }
+        }
+    }
+
+    fn handle_interrupt<T, S>(
+        &mut self,
+        time: T::Time,
+        interrupt: Interrupt<I::InputEvent>,
+    ) -> Option<InputsPoll<T, S>>
+    where
+        T: Transposer<Time = Src::Time> + TransposerInputEventHandler<I>,
+        S: StorageFamily,
+        S::Erasure: EraseInput<I, S>,
+        I: TransposerInput<Base = T>,
+    {
+        let event = match interrupt {
+            Interrupt::Event(event) => event,
+            Interrupt::FinalizedEvent(event) => {
+                self.finalized = self.finalized.max(Some(time));
+                event
+            },
+            Interrupt::Rollback => {
+                self.events_complete = None;
+                return Some(InputsPoll::Rollback {
+                    time,
+                    sort: I::SORT,
+                })
+            },
+            Interrupt::Finalize => {
+                self.finalized = self.finalized.max(Some(time));
+                return None
+            },
+        };
+
+        if !T::can_handle(time, &event) {
+            return None
+        }
+
+        let mut inputs = StepInputs::new(time);
+        inputs.add_event::<I>(event);
+        Some(InputsPoll::Event(inputs))
+    }
+}
+
+/// What happened while polling the upstream sources of a transposer.
+pub enum InputsPoll<T: Transposer, S: StorageFamily> {
+    /// All the requested work is done.
+    Ready,
+
+    /// An upstream source is pending.
+    Pending,
+
+    /// An upstream source emitted an event.
+    Event(StepInputs<T, S>),
+
+    /// The upstream source for the input with this `SORT` rolled back.
+    Rollback { time: T::Time, sort: u64 },
+}
+
+/// The set of upstream sources driving a transposer, one per [`TransposerInput`].
+///
+/// This is implemented for tuples of [`InputSource`], which must all share an error type.
+pub trait InputSources<T: Transposer, Is: InputState<T>, S: StorageFamily> {
+    type Error;
+
+    /// receive events from every source, up to `time`.
+    #[allow(clippy::type_complexity)]
+    fn poll_events(
+        &mut self,
+        time: T::Time,
+        all_channel_waker: &Waker,
+    ) -> Result<InputsPoll<T, S>, SourcePollErr<T::Time, Self::Error>>;
+
+    /// whether `input_state` is waiting on any of the sources.
+    fn is_requested(&self, input_state: &Is) -> bool;
+
+    /// provide the states `input_state` has requested, by polling the matching sources at `time`.
+    #[allow(clippy::type_complexity)]
+    fn poll_states(
+        &mut self,
+        time: T::Time,
+        input_state: &Is,
+        cx: SourceContext,
+        forget: bool,
+    ) -> Result<InputsPoll<T, S>, SourcePollErr<T::Time, Self::Error>>;
+
+    /// all the events at or before this time have been received from every source.
+    fn events_complete(&self) -> Option<T::Time>;
+
+    /// the earliest next event any of the sources has told us about.
+    fn next_event_at(&self) -> Option<T::Time>;
+
+    /// every source has finalized up to this time.
+    fn finalized(&self) -> Option<T::Time>;
+
+    fn release_channel(&mut self, channel: usize);
+
+    fn advance(&mut self, time: T::Time);
+
+    fn max_channel(&self) -> NonZeroUsize;
+}
+
+macro_rules! impl_input_sources {
+    ($(($idx:tt, $I:ident, $Src:ident)),+) => {
+        impl<T, Is, S, E, $($I, $Src),+> InputSources<T, Is, S> for ($(InputSource<$I, $Src>,)+)
+        where
+            T: Transposer $(+ TransposerInputEventHandler<$I>)+,
+            Is: InputState<T> $(+ FulfillInputState<$I>)+,
+            S: StorageFamily,
+            $(
+                $I: TransposerInput<Base = T>,
+                S::Erasure: EraseInput<$I, S>,
+                $Src: Source<
+                    Time = T::Time,
+                    Event = <$I as TransposerInput>::InputEvent,
+                    State = <$I as TransposerInput>::InputState,
+                    Error = E,
+                >,
+            )+
+        {
+            type Error = E;
+
+            fn poll_events(
+                &mut self,
+                time: T::Time,
+                all_channel_waker: &Waker,
+            ) -> Result<InputsPoll<T, S>, SourcePollErr<T::Time, E>> {
+                $(
+                    match self.$idx.poll_events(time, all_channel_waker)? {
+                        InputsPoll::Ready => {},
+                        poll => return Ok(poll),
+                    }
+                )+
+
+                Ok(InputsPoll::Ready)
+            }
+
+            fn is_requested(&self, input_state: &Is) -> bool {
+                $(FulfillInputState::<$I>::is_requested(input_state))||+
+            }
+
+            fn poll_states(
+                &mut self,
+                time: T::Time,
+                input_state: &Is,
+                cx: SourceContext,
+                forget: bool,
+            ) -> Result<InputsPoll<T, S>, SourcePollErr<T::Time, E>> {
+                $(
+                    match self.$idx.poll_state(time, input_state, cx.clone(), forget)? {
+                        InputsPoll::Ready => {},
+                        poll => return Ok(poll),
+                    }
+                )+
+
+                Ok(InputsPoll::Ready)
+            }
+
+            fn events_complete(&self) -> Option<T::Time> {
+                // None is less than Some, so this is None if any source is None.
+                [$(self.$idx.events_complete),+].into_iter().min().flatten()
+            }
+
+            fn next_event_at(&self) -> Option<T::Time> {
+                [$(self.$idx.next_event_at),+].into_iter().flatten().min()
+            }
+
+            fn finalized(&self) -> Option<T::Time> {
+                [$(self.$idx.finalized),+].into_iter().min().flatten()
+            }
+
+            fn release_channel(&mut self, channel: usize) {
+                $(self.$idx.source.release_channel(channel);)+
+            }
+
+            fn advance(&mut self, time: T::Time) {
+                $(self.$idx.source.advance(time);)+
+            }
+
+            fn max_channel(&self) -> NonZeroUsize {
+                [$(self.$idx.source.max_channel()),+].into_iter().min().unwrap()
+            }
+        }
+    };
+}
+
+impl_input_sources!((0, I0, S0));
+impl_input_sources!((0, I0, S0), (1, I1, S1));
+impl_input_sources!((0, I0, S0), (1, I1, S1), (2, I2, S2));
+impl_input_sources!((0, I0, S0), (1, I1, S1), (2, I2, S2), (3, I3, S3));
+impl_input_sources!(
+    (0, I0, S0),
+    (1, I1, S1),
+    (2, I2, S2),
+    (3, I3, S3),
+    (4, I4, S4)
+);
+impl_input_sources!(
+    (0, I0, S0),
+    (1, I1, S1),
+    (2, I2, S2),
+    (3, I3, S3),
+    (4, I4, S4),
+    (5, I5, S5)
+);
@@ -1,3 +1,8 @@
-mod channels;
+pub(crate) mod channels;
+pub mod checkpoint_strategy;
+pub(crate) mod input_buffer;
+pub mod input_sources;
+pub mod multi_input_transposer;
 pub mod no_input_transposer;
-mod steps;
+pub(crate) mod retention_policy;
+pub(crate) mod steps;
@@ -0,0 +1,681 @@
+#[cfg(test)]
+pub(crate) mod test;
+
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::ops::Bound;
+use std::task::{Poll, Waker};
+
+use transposer::schedule_storage::{DefaultStorage, StorageFamily};
+use transposer::step::{InputState, StepPoll};
+use transposer::{Transposer, TransposerHash};
+
+use super::channels::original_step_future::OriginalStepPoll;
+use super::channels::{CallerChannelBlockedReasonInner, CallerChannelStatus, ChannelStatuses};
+use super::checkpoint_strategy::CheckpointStrategy;
+use super::input_buffer::InputBuffer;
+use super::input_sources::{InputSources, InputsPoll};
+use super::retention_policy::RetentionPolicy;
+use super::steps::{BeforeStatus, BeforeStatusEvents, Steps};
+use crate::source_poll::{Interrupt, SourcePollErr, TrySourcePoll};
+use crate::traits::{SourceContext, StateHashSource};
+use crate::{Source, SourcePoll};
+
+/// A source which drives a transposer with several inputs, each fed by its own upstream source.
+///
+/// The events of every upstream are merged into the inputs of each step,
+/// and the transposer's requests for input state are fulfilled by polling the matching upstream at the step's time.
+///
+/// When an upstream emits an event before steps which have already been computed, or rolls back,
+/// those steps are discarded, and a rollback is emitted if anything derived from them could have been observed.
+pub struct MultiInputTransposerSource<
+    T: Transposer,
+    Is: InputState<T>,
+    Inputs,
+    S: StorageFamily = DefaultStorage,
+> {
+    // the sources we pull from
+    inputs: Inputs,
+
+    steps: Steps<T, Is, S>,
+
+    // current channel obligations
+    channel_statuses: ChannelStatuses<T, Is, S>,
+
+    // inputs which have been recieved, but not yet handed to a step.
+    input_buffer: InputBuffer<T, S>,
+
+    // what the sources and the caller have promised about the times they will need.
+    retention_policy: RetentionPolicy<T::Time>,
+
+    start_time: T::Time,
+
+    // the latest finalize seen from the sources.
+    finalized: Option<T::Time>,
+
+    // the states emitted from poll (and not poll_forget) which haven't been rolled back are all within this bound.
+    emitted_states_bound: Bound<T::Time>,
+
+    // a finalize from the sources which hasn't been passed on yet.
+    unreported_finalize: Option<T::Time>,
+
+    // the top channel of the sources, which is reserved for the states needed by original steps.
+    reserved_channel: usize,
+}
+
+/// The upstream sources don't allow enough channels.
+///
+/// One channel of the upstream sources is reserved for computing steps,
+/// so they must allow at least three channels, leaving two for callers.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TooFewChannels;
+
+impl<T, Is, Inputs> MultiInputTransposerSource<T, Is, Inputs>
+where
+    T: Transposer,
+    Is: InputState<T>,
+    Inputs: InputSources<T, Is, DefaultStorage>,
+{
+    pub fn new(
+        inputs: Inputs,
+        transposer: T,
+        start_time: T::Time,
+        rng_seed: [u8; 32],
+    ) -> Result<Self, TooFewChannels> {
+        Self::new_with_storage(inputs, transposer, start_time, rng_seed)
+    }
+}
+
+impl<T, Is, Inputs, S> MultiInputTransposerSource<T, Is, Inputs, S>
+where
+    T: Transposer,
+    Is: InputState<T>,
+    Inputs: InputSources<T, Is, S>,
+    S: StorageFamily,
+{
+    /// create a source which keeps the state of its steps in the storage family `S`,
+    /// which is chosen with a turbofish, like `MultiInputTransposerSource::<_, _, _, StdStorage>::new_with_storage(..)`.
+    ///
+    /// see [`schedule_storage`](transposer::schedule_storage) for the families available.
+    pub fn new_with_storage(
+        inputs: Inputs,
+        transposer: T,
+        start_time: T::Time,
+        rng_seed: [u8; 32],
+    ) -> Result<Self, TooFewChannels> {
+        let reserved_channel = inputs.max_channel().get();
+        if reserved_channel < 2 {
+            return Err(TooFewChannels)
+        }
+
+        Ok(Self {
+            inputs,
+            steps: Steps::new(transposer, start_time, rng_seed),
+            channel_statuses: ChannelStatuses::new(),
+            input_buffer: InputBuffer::new(),
+            retention_policy: RetentionPolicy::new(start_time),
+            start_time,
+            finalized: None,
+            emitted_states_bound: Bound::Excluded(start_time),
+            unreported_finalize: None,
+            reserved_channel,
+        })
+    }
+
+    /// choose which saturated steps are kept around, to trade memory against recomputation.
+    ///
+    /// the default is [`FixedCount`](super::checkpoint_strategy::FixedCount) with 30 checkpoints.
+    pub fn with_checkpoint_strategy(
+        mut self,
+        strategy: impl CheckpointStrategy<T::Time> + 'static,
+    ) -> Self {
+        self.steps.set_checkpoint_strategy(strategy);
+        self
+    }
+
+    /// the approximate number of bytes held by the saturated steps this source is keeping.
+    ///
+    /// this is based on [`Transposer::heap_size`],
+    /// and is what [`ByteBudget`](super::checkpoint_strategy::ByteBudget) keeps within its budget.
+    pub fn memory_usage(&self) -> usize {
+        self.steps.memory_usage()
+    }
+
+    /// discard every step and channel which could have been affected by an input at `time`.
+    ///
+    /// returns whether the caller needs to be told about it.
+    fn rollback_steps(&mut self, time: T::Time, all_channel_waker: &Waker) -> bool {
+        let observed_steps = self.steps.rollback(time, &mut self.input_buffer);
+
+        if self.channel_statuses.reset_after(time) {
+            all_channel_waker.wake_by_ref();
+        }
+
+        let observed_states = bound_reaches(self.emitted_states_bound, time);
+
+        if observed_states {
+            self.emitted_states_bound = Bound::Excluded(time);
+        }
+
+        observed_steps || observed_states
+    }
+
+    fn state_emitted(&mut self, time: T::Time) {
+        if !bound_reaches(self.emitted_states_bound, time) {
+            self.emitted_states_bound = Bound::Included(time);
+        }
+    }
+
+    fn check_finalize(&mut self) {
+        let finalized = self.inputs.finalized();
+        if finalized <= self.finalized {
+            return
+        }
+        self.finalized = finalized;
+
+        let time = finalized.unwrap().max(self.start_time);
+        self.unreported_finalize = Some(time);
+
+        if self.retention_policy.source_finalize(time) {
+            self.apply_retention();
+        }
+    }
+
+    fn apply_retention(&mut self) {
+        let retain_after = self.retention_policy.get_retain_after();
+        let earliest_needed = self.steps.delete_before(retain_after);
+        let advance_to = match earliest_needed {
+            Some(t) => t.min(retain_after),
+            None => retain_after,
+        };
+
+        self.inputs.advance(advance_to);
+    }
+
+    fn handle_inputs_poll<State>(
+        &mut self,
+        poll: InputsPoll<T, S>,
+        all_channel_waker: &Waker,
+    ) -> Option<SourcePoll<T::Time, T::OutputEvent, State>> {
+        let rollback_time = match poll {
+            InputsPoll::Ready => None,
+            InputsPoll::Pending => return Some(SourcePoll::Pending),
+            InputsPoll::Event(inputs) => {
+                let time = inputs.time();
+                if time < self.start_time {
+                    None
+                } else {
+                    let rollback = self.rollback_steps(time, all_channel_waker);
+                    self.input_buffer.insert(inputs);
+                    rollback.then_some(time)
+                }
+            },
+            InputsPoll::Rollback {
+                time,
+                sort,
+            } => {
+                let rollback = self.rollback_steps(time, all_channel_waker);
+                self.input_buffer.rollback_input(time, sort);
+                rollback.then_some(time)
+            },
+        };
+
+        self.check_finalize();
+
+        if let Some(time) = rollback_time {
+            return Some(SourcePoll::Interrupt {
+                time,
+                interrupt: Interrupt::Rollback,
+            })
+        }
+
+        self.unreported_finalize
+            .take()
+            .map(|time| SourcePoll::Interrupt {
+                time,
+                interrupt: Interrupt::Finalize,
+            })
+    }
+
+    /// recieve all the events the sources have at or before `time`.
+    ///
+    /// returns a poll if it must be passed to the caller before continuing.
+    #[allow(clippy::type_complexity)]
+    fn poll_inputs_events<State>(
+        &mut self,
+        time: T::Time,
+        all_channel_waker: &Waker,
+    ) -> Result<
+        Option<SourcePoll<T::Time, T::OutputEvent, State>>,
+        SourcePollErr<T::Time, Inputs::Error>,
+    > {
+        loop {
+            let poll = self.inputs.poll_events(time, all_channel_waker)?;
+            let ready = matches!(poll, InputsPoll::Ready);
+
+            if let Some(poll) = self.handle_inputs_poll(poll, all_channel_waker) {
+                return Ok(Some(poll))
+            }
+
+            if ready {
+                return Ok(None)
+            }
+        }
+    }
+
+    /// provide the input states requested by `target`, by polling the sources at `time`.
+    ///
+    /// returns a poll if it must be passed to the caller before continuing.
+    #[allow(clippy::type_complexity)]
+    fn poll_inputs_states<State>(
+        &mut self,
+        time: T::Time,
+        target: StateTarget,
+        cx: SourceContext,
+        forget: bool,
+    ) -> Result<
+        Option<SourcePoll<T::Time, T::OutputEvent, State>>,
+        SourcePollErr<T::Time, Inputs::Error>,
+    > {
+        let all_channel_waker = cx.all_channel_waker.clone();
+
+        let input_state = match target {
+            StateTarget::Step(None) => self.steps.get_last().get_input_state(),
+            StateTarget::Step(Some(step_id)) => self
+                .steps
+                .get_mut_by_sequence_number(step_id)
+                .unwrap()
+                .get_input_state(),
+            StateTarget::Interpolation(channel) => {
+                match &self.channel_statuses.blocked_caller_channels[&channel].inner {
+                    CallerChannelBlockedReasonInner::InterpolationFuture(interpolation) => {
+                        interpolation.get_input_state()
+                    },
+                    _ => unreachable!(),
+                }
+            },
+        };
+
+        let poll = self.inputs.poll_states(time, input_state, cx, forget)?;
+
+        Ok(self.handle_inputs_poll(poll, &all_channel_waker))
+    }
+
+    fn poll_inner(
+        &mut self,
+        time: T::Time,
+        cx: SourceContext,
+        forget: bool,
+    ) -> TrySourcePoll<T::Time, T::OutputEvent, T::OutputState, Inputs::Error> {
+        if cx.channel >= self.reserved_channel {
+            return Err(SourcePollErr::OutOfBoundsChannel)
+        }
+
+        if let Some(poll) = self.poll_inputs_events(time, &cx.all_channel_waker)? {
+            return Ok(poll)
+        }
+
+        loop {
+            let (state_time, target) = match self.poll_steps(time, &cx) {
+                StepsPoll::Done(poll) => {
+                    if !forget && matches!(poll, SourcePoll::Ready { .. }) {
+                        self.state_emitted(time);
+                    }
+                    return Ok(poll)
+                },
+                StepsPoll::NeedsState {
+                    time,
+                    target,
+                } => (time, target),
+            };
+
+            let source_cx = match target {
+                StateTarget::Step(None) => SourceContext {
+                    channel:           self.reserved_channel,
+                    one_channel_waker: cx.all_channel_waker.clone(),
+                    all_channel_waker: cx.all_channel_waker.clone(),
+                },
+                _ => cx.clone(),
+            };
+            let forget = forget && matches!(target, StateTarget::Interpolation(_));
+
+            if let Some(poll) = self.poll_inputs_states(state_time, target, source_cx, forget)? {
+                return Ok(poll)
+            }
+        }
+    }
+
+    fn poll_steps(&mut self, time: T::Time, cx: &SourceContext) -> StepsPoll<T> {
+        let SourceContext {
+            channel: caller_channel,
+            one_channel_waker,
+            all_channel_waker,
+        } = cx;
+
+        let mut current_state = self.channel_statuses.get_channel_status(*caller_channel);
+
+        loop {
+            current_state = match current_state {
+                CallerChannelStatus::Free(free) => {
+                    let pinned_times = free.get_pinned_times();
+                    let mut next_inputs = self
+                        .inputs
+                        .events_complete()
+                        .and_then(|t| self.input_buffer.pop_first_before_or_at(t));
+
+                    let before_status = self
+                        .steps
+                        .get_before_or_at(time, &pinned_times, &mut next_inputs)
+                        .unwrap();
+
+                    if let Some(inputs) = next_inputs {
+                        self.input_buffer.insert(inputs);
+                    }
+
+                    match before_status {
+                        BeforeStatus::Saturated {
+                            step, ..
+                        } => {
+                            let interpolation = step.interpolate(time).unwrap();
+                            let interpolation = free.start_interpolation(interpolation, time);
+                            CallerChannelStatus::InterpolationFuture(interpolation)
+                        },
+                        BeforeStatus::Saturating {
+                            step,
+                            step_index,
+                        } => {
+                            if step.can_produce_events() {
+                                let original_entry = free.start_original_step(time);
+                                CallerChannelStatus::OriginalStepFuture(original_entry)
+                            } else {
+                                let repeat_entry = free.start_repeat_step(step_index, time);
+                                CallerChannelStatus::RepeatStepFuture(repeat_entry)
+                            }
+                        },
+                    }
+                },
+                CallerChannelStatus::InterpolationFuture(interpolation) => {
+                    let prev_time = interpolation.caller_channel.get_value().poll_time;
+                    if prev_time != time {
+                        current_state = CallerChannelStatus::Free(interpolation.abandon());
+                        continue
+                    }
+
+                    let (status, poll) = interpolation.poll(one_channel_waker);
+                    return match poll {
+                        Poll::Ready(state) => StepsPoll::Done(SourcePoll::Ready {
+                            state,
+                            next_event_at: next_event_at(
+                                self.steps.get_scheduled_time(),
+                                self.input_buffer.first_time(),
+                                self.inputs.next_event_at(),
+                            ),
+                        }),
+                        Poll::Pending => {
+                            let requested = match status {
+                                CallerChannelStatus::InterpolationFuture(interpolation) => {
+                                    match &interpolation.caller_channel.get_value().inner {
+                                        CallerChannelBlockedReasonInner::InterpolationFuture(
+                                            interpolation,
+                                        ) => self
+                                            .inputs
+                                            .is_requested(interpolation.get_input_state()),
+                                        _ => unreachable!(),
+                                    }
+                                },
+                                _ => unreachable!(),
+                            };
+
+                            if requested {
+                                StepsPoll::NeedsState {
+                                    time,
+                                    target: StateTarget::Interpolation(*caller_channel),
+                                }
+                            } else {
+                                StepsPoll::Done(SourcePoll::Pending)
+                            }
+                        },
+                    }
+                },
+                CallerChannelStatus::OriginalStepFuture(original) => {
+                    let prev_time = original.caller_channel.get_value().poll_time;
+                    if prev_time != time {
+                        current_state = CallerChannelStatus::Free(original.abandon());
+                        continue
+                    }
+
+                    let step = self.steps.get_last_mut();
+
+                    let free = match original.poll(step, all_channel_waker) {
+                        OriginalStepPoll::OutputEvent(event) => {
+                            return StepsPoll::Done(SourcePoll::Interrupt {
+                                time:      step.get_time(),
+                                interrupt: Interrupt::Event(event),
+                            })
+                        },
+                        OriginalStepPoll::Pending => {
+                            let requested = self.inputs.is_requested(step.get_input_state());
+                            return StepsPoll::pending_step(step.get_time(), None, requested)
+                        },
+                        OriginalStepPoll::Free(free) => free,
+                    };
+
+                    CallerChannelStatus::Free(free)
+                },
+                CallerChannelStatus::RepeatStepFuture(repeat) => {
+                    let block = repeat.caller_channel.get_value();
+                    let step_id = block.unwrap_repeat_step();
+                    let prev_time = block.poll_time;
+                    if prev_time != time {
+                        current_state = CallerChannelStatus::Free(repeat.abandon());
+                        continue
+                    }
+
+                    let step = self.steps.get_mut_by_sequence_number(step_id).unwrap();
+
+                    let free = match repeat.poll(step, one_channel_waker) {
+                        Poll::Pending => {
+                            let requested = self.inputs.is_requested(step.get_input_state());
+                            return StepsPoll::pending_step(
+                                step.get_time(),
+                                Some(step_id),
+                                requested,
+                            )
+                        },
+                        Poll::Ready(free) => free,
+                    };
+
+                    CallerChannelStatus::Free(free)
+                },
+            };
+        }
+    }
+}
+
+impl<T, Is, Inputs, S> Source for MultiInputTransposerSource<T, Is, Inputs, S>
+where
+    T: Transposer,
+    Is: InputState<T>,
+    Inputs: InputSources<T, Is, S>,
+    S: StorageFamily,
+{
+    type Time = T::Time;
+
+    type Event = T::OutputEvent;
+
+    type State = T::OutputState;
+
+    type Error = Inputs::Error;
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_inner(time, cx, false)
+    }
+
+    fn poll_forget(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_inner(time, cx, true)
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        if let Some(poll) = self.poll_inputs_events(time, &all_channel_waker)? {
+            return Ok(poll)
+        }
+
+        loop {
+            let pinned_times = self.channel_statuses.get_pinned_times();
+            let mut next_inputs = self
+                .inputs
+                .events_complete()
+                .and_then(|t| self.input_buffer.pop_first_before_or_at(t));
+
+            let before_status = self
+                .steps
+                .get_before_or_at_events(time, &pinned_times, &mut next_inputs)
+                .unwrap();
+
+            if let Some(inputs) = next_inputs {
+                self.input_buffer.insert(inputs);
+            }
+
+            let (step_time, step_index, requested, poll) = match before_status {
+                BeforeStatusEvents::Ready {
+                    next_time,
+                } => {
+                    return Ok(SourcePoll::Ready {
+                        state:         (),
+                        next_event_at: next_event_at(
+                            next_time,
+                            self.input_buffer.first_time(),
+                            self.inputs.next_event_at(),
+                        ),
+                    })
+                },
+                BeforeStatusEvents::Saturating {
+                    step,
+                    step_index,
+                } => {
+                    let poll = step.poll(&all_channel_waker).unwrap();
+                    let requested = self.inputs.is_requested(step.get_input_state());
+                    (step.get_time(), step_index, requested, poll)
+                },
+            };
+
+            match poll {
+                StepPoll::Emitted(event) => {
+                    return Ok(SourcePoll::Interrupt {
+                        time:      step_time,
+                        interrupt: Interrupt::Event(event),
+                    })
+                },
+                StepPoll::Pending if requested => {
+                    let source_cx = SourceContext {
+                        channel:           self.reserved_channel,
+                        one_channel_waker: all_channel_waker.clone(),
+                        all_channel_waker: all_channel_waker.clone(),
+                    };
+
+                    let target = StateTarget::Step(Some(step_index));
+                    if let Some(poll) =
+                        self.poll_inputs_states(step_time, target, source_cx, false)?
+                    {
+                        return Ok(poll)
+                    }
+                },
+                StepPoll::Pending => return Ok(SourcePoll::Pending),
+                StepPoll::Ready => {},
+            }
+        }
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        let _ = self.channel_statuses.get_channel_status(channel).abandon();
+        self.inputs.release_channel(channel);
+    }
+
+    fn advance(&mut self, time: Self::Time) {
+        if self.retention_policy.caller_advance(time) {
+            self.apply_retention();
+        }
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        // this was checked on creation.
+        NonZeroUsize::new(self.reserved_channel - 1).unwrap()
+    }
+}
+
+// whether an upper bound includes `time` or anything after it.
+fn bound_reaches<Time: Ord>(bound: Bound<Time>, time: Time) -> bool {
+    match bound {
+        Bound::Included(t) => t >= time,
+        Bound::Excluded(t) => t > time,
+        Bound::Unbounded => true,
+    }
+}
+
+fn next_event_at<Time: Ord>(
+    scheduled: Option<Time>,
+    buffered: Option<Time>,
+    source: Option<Time>,
+) -> Option<Time> {
+    [scheduled, buffered, source].into_iter().flatten().min()
+}
+
+// which input state is waiting on the sources.
+enum StateTarget {
+    // a step, either the original step (None) or any other step (by sequence number)
+    Step(Option<usize>),
+    // the interpolation blocking a caller channel
+    Interpolation(usize),
+}
+
+enum StepsPoll<T: Transposer> {
+    Done(SourcePoll<T::Time, T::OutputEvent, T::OutputState>),
+    NeedsState {
+        time:   T::Time,
+        target: StateTarget,
+    },
+}
+
+impl<T: Transposer> StepsPoll<T> {
+    fn pending_step(time: T::Time, step_id: Option<usize>, requested: bool) -> Self {
+        if requested {
+            StepsPoll::NeedsState {
+                time,
+                target: StateTarget::Step(step_id),
+            }
+        } else {
+            StepsPoll::Done(SourcePoll::Pending)
+        }
+    }
+}
+
+impl<T, Is, Inputs, S> StateHashSource for MultiInputTransposerSource<T, Is, Inputs, S>
+where
+    T: Transposer + TransposerHash,
+    T::Time: Hash,
+    T::Period: Hash,
+    T::Scheduled: Hash,
+    Is: InputState<T>,
+    Inputs: InputSources<T, Is, S>,
+    S: StorageFamily,
+{
+    fn enable_state_hashing(&mut self) {
+        self.steps.enable_state_hashing()
+    }
+
+    fn state_hashes(&self, range: (Bound<T::Time>, Bound<T::Time>)) -> Vec<(usize, T::Time, u64)> {
+        self.steps.state_hashes(range)
+    }
+}
@@ -0,0 +1,240 @@
+use std::cell::RefCell;
+use std::num::NonZeroUsize;
+use std::rc::Rc;
+use std::task::Waker;
+
+use transposer::context::{
+    HandleInputContext,
+    InitContext,
+    InputStateContextExt,
+    InterpolateContext,
+};
+use transposer::multi_input_state::MultiInputState;
+use transposer::{transposer_inputs, Transposer, TransposerInputEventHandler};
+use util::dummy_waker::DummyWaker;
+
+use super::MultiInputTransposerSource;
+use crate::source_poll::{Interrupt, TrySourcePoll};
+use crate::sources::transposer::input_sources::InputSource;
+use crate::traits::SourceContext;
+use crate::{Source, SourcePoll};
+
+// a source whose state at t is the number of events at or before t.
+#[derive(Default)]
+pub(crate) struct TestSourceInner {
+    pub(crate) events:    Vec<(usize, usize)>,
+    pub(crate) emitted:   usize,
+    pub(crate) rollback:  Option<usize>,
+    pub(crate) finalized: bool,
+    pub(crate) advanced:  Option<usize>,
+    pub(crate) released:  Vec<usize>,
+
+    // the default allows channels up to 4.
+    pub(crate) max_channel: Option<NonZeroUsize>,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct TestSource(pub(crate) Rc<RefCell<TestSourceInner>>);
+
+impl TestSource {
+    pub(crate) fn insert(&self, time: usize, event: usize) {
+        let mut inner = self.0.borrow_mut();
+        let i = inner.events.partition_point(|(t, _)| *t <= time);
+        inner.events.insert(i, (time, event));
+
+        if i < inner.emitted {
+            inner.emitted = i;
+            inner.rollback = Some(time);
+        }
+    }
+}
+
+impl Source for TestSource {
+    type Time = usize;
+
+    type Event = usize;
+
+    type State = usize;
+
+    type Error = ();
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        Ok(match self.poll_events(time, cx.all_channel_waker)? {
+            SourcePoll::Ready {
+                state: (),
+                next_event_at,
+            } => SourcePoll::Ready {
+                state: self.0.borrow().events.partition_point(|(t, _)| *t <= time),
+                next_event_at,
+            },
+            SourcePoll::Interrupt {
+                time,
+                interrupt,
+            } => SourcePoll::Interrupt {
+                time,
+                interrupt,
+            },
+            SourcePoll::Pending => SourcePoll::Pending,
+        })
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        _all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        let mut inner = self.0.borrow_mut();
+
+        if let Some(rollback) = inner.rollback.take() {
+            return Ok(SourcePoll::Interrupt {
+                time:      rollback,
+                interrupt: Interrupt::Rollback,
+            })
+        }
+
+        let next = inner.events.get(inner.emitted).copied();
+        Ok(match next {
+            Some((t, event)) if t <= time => {
+                inner.emitted += 1;
+                let interrupt = if inner.finalized {
+                    Interrupt::FinalizedEvent(event)
+                } else {
+                    Interrupt::Event(event)
+                };
+                SourcePoll::Interrupt {
+                    time: t,
+                    interrupt,
+                }
+            },
+            next => SourcePoll::Ready {
+                state:         (),
+                next_event_at: next.map(|(t, _)| t),
+            },
+        })
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        self.0.borrow_mut().released.push(channel);
+    }
+
+    fn advance(&mut self, time: Self::Time) {
+        let mut inner = self.0.borrow_mut();
+        inner.advanced = inner.advanced.max(Some(time));
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        self.0
+            .borrow()
+            .max_channel
+            .unwrap_or(NonZeroUsize::new(4).unwrap())
+    }
+}
+
+pub(crate) fn cx(channel: usize) -> SourceContext {
+    SourceContext {
+        channel,
+        one_channel_waker: DummyWaker::dummy(),
+        all_channel_waker: DummyWaker::dummy(),
+    }
+}
+
+#[transposer_inputs(
+    retriever = PairInputStateManager,
+    input(AInput, event = usize, state = usize),
+    input(BInput, event = usize, state = usize),
+)]
+#[derive(Clone)]
+struct PairTransposer {
+    a_total: usize,
+    b_total: usize,
+}
+
+impl Transposer for PairTransposer {
+    type Time = usize;
+
+    type OutputEvent = usize;
+
+    // (a total, b total, a state, b state)
+    type OutputState = (usize, usize, usize, usize);
+
+    type Scheduled = ();
+
+    type InputStateManager = dyn PairInputStateManager + Send + Sync;
+
+    async fn init(&mut self, _cx: &mut impl InitContext<Self>) {}
+
+    async fn interpolate(&self, cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {
+        let a = *cx.get_input_state::<AInput>().await;
+        let b = *cx.get_input_state::<BInput>().await;
+        (self.a_total, self.b_total, a, b)
+    }
+}
+
+impl TransposerInputEventHandler<AInput> for PairTransposer {
+    async fn handle_input(&mut self, event: &usize, cx: &mut impl HandleInputContext<Self>) {
+        self.a_total += event;
+        let b = *cx.get_input_state::<BInput>().await;
+        cx.emit_event(self.a_total + 100 * b).await;
+    }
+}
+
+impl TransposerInputEventHandler<BInput> for PairTransposer {
+    async fn handle_input(&mut self, event: &usize, cx: &mut impl HandleInputContext<Self>) {
+        self.b_total += event;
+        let a = *cx.get_input_state::<AInput>().await;
+        cx.emit_event(self.b_total + 100 * a).await;
+    }
+}
+
+#[test]
+fn two_inputs_test() {
+    let a = TestSource::default();
+    a.insert(1, 1);
+    a.insert(4, 2);
+
+    let b = TestSource::default();
+    b.insert(2, 10);
+    b.insert(4, 20);
+
+    let inputs = (
+        InputSource::<AInput, _>::new(a),
+        InputSource::<BInput, _>::new(b),
+    );
+    let transposer = PairTransposer {
+        a_total: 0,
+        b_total: 0,
+    };
+    let mut source: MultiInputTransposerSource<_, MultiInputState<_>, _> =
+        MultiInputTransposerSource::new(inputs, transposer, 0, [0; 32]).unwrap();
+
+    let mut emitted = Vec::new();
+    loop {
+        match source.poll_events(5, DummyWaker::dummy()).ok().unwrap() {
+            SourcePoll::Ready {
+                next_event_at, ..
+            } => {
+                assert_eq!(next_event_at, None);
+                break
+            },
+            SourcePoll::Interrupt {
+                time,
+                interrupt: Interrupt::Event(e),
+            } => emitted.push((time, e)),
+            _ => panic!(),
+        }
+    }
+
+    // inputs at the same time are handled in SORT order.
+    assert_eq!(emitted, vec![(1, 1), (2, 110), (4, 203), (4, 230)]);
+
+    match source.poll(5, cx(0)).ok().unwrap() {
+        SourcePoll::Ready {
+            state, ..
+        } => assert_eq!(state, (3, 30, 2, 2)),
+        _ => panic!(),
+    }
+}
@@ -1,66 +1,140 @@
-use std::task::Poll;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::ops::Bound;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
 
-use transposer::step::{NoInputManager, StepPoll};
-use transposer::Transposer;
+use futures_core::Future;
+use parking_lot::Mutex;
+use transposer::schedule_storage::{DefaultStorage, StorageFamily};
+use transposer::step::{Interpolation, NoInput, NoInputManager, StepPoll};
+use transposer::{Transposer, TransposerHash};
 
 use super::channels::free::Free;
 use super::channels::{CallerChannelStatus, ChannelStatuses};
+use super::checkpoint_strategy::CheckpointStrategy;
 use super::steps::{BeforeStatus, BeforeStatusEvents, Steps};
 use crate::source_poll::{self, TrySourcePoll};
 use crate::sources::transposer::channels::original_step_future::OriginalStepPoll;
-use crate::traits::SourceContext;
+use crate::traits::{ConcurrentSource, SourceContext, StateHashSource};
 use crate::{Source, SourcePoll};
 
-pub struct NoInputTransposerSource<T: Transposer<InputStateManager = NoInputManager>> {
-    steps: Steps<T>,
+/// A source which runs a transposer with no inputs.
+///
+/// This implements [`ConcurrentSource`] without a single lock around everything.
+/// Saturating steps is done by one caller at a time,
+/// but once a channel has started interpolating it only locks its own interpolation,
+/// so interpolations on different channels can proceed in parallel.
+pub struct NoInputTransposerSource<
+    T: Transposer<InputStateManager = NoInputManager>,
+    S: StorageFamily = DefaultStorage,
+> {
+    saturation: Mutex<Saturation<T, S>>,
 
-    channel_statuses: ChannelStatuses<T>,
+    // interpolations own everything they need from their step, so they are kept out of the saturation lock.
+    interpolations: Mutex<HashMap</* caller_channel */ usize, SharedInterpolation<T, S>>>,
+}
+
+struct Saturation<T: Transposer<InputStateManager = NoInputManager>, S: StorageFamily> {
+    steps: Steps<T, NoInput, S>,
+
+    channel_statuses: ChannelStatuses<T, NoInput, S>,
+}
+
+struct ChannelInterpolation<T: Transposer<InputStateManager = NoInputManager>, S: StorageFamily> {
+    poll_time:     T::Time,
+    interpolation: Interpolation<T, NoInput, S>,
+}
+
+type SharedInterpolation<T, S> = Arc<Mutex<ChannelInterpolation<T, S>>>;
+
+type NoInputPoll<T> = TrySourcePoll<
+    <T as Transposer>::Time,
+    <T as Transposer>::OutputEvent,
+    <T as Transposer>::OutputState,
+    (),
+>;
+
+enum SaturationPoll<T: Transposer<InputStateManager = NoInputManager>, S: StorageFamily> {
+    Done(NoInputPoll<T>),
+    Interpolate(Interpolation<T, NoInput, S>),
 }
 
 impl<T: Transposer<InputStateManager = NoInputManager>> NoInputTransposerSource<T> {
     pub fn new(transposer: T, start_time: T::Time, rng_seed: [u8; 32]) -> Self {
-        Self {
-            steps:            Steps::new(transposer, start_time, rng_seed),
-            channel_statuses: ChannelStatuses::new(),
-        }
+        Self::new_with_storage(transposer, start_time, rng_seed)
     }
 }
 
-impl<T: Transposer<InputStateManager = NoInputManager>> Source for NoInputTransposerSource<T> {
-    type Time = T::Time;
-
-    type Event = T::OutputEvent;
+impl<T: Transposer<InputStateManager = NoInputManager>, S: StorageFamily>
+    NoInputTransposerSource<T, S>
+{
+    /// create a source which keeps the state of its steps in the storage family `S`,
+    /// which is chosen with a turbofish, like `NoInputTransposerSource::<_, StdStorage>::new_with_storage(..)`.
+    ///
+    /// see [`schedule_storage`](transposer::schedule_storage) for the families available.
+    pub fn new_with_storage(transposer: T, start_time: T::Time, rng_seed: [u8; 32]) -> Self {
+        Self {
+            saturation:     Mutex::new(Saturation {
+                steps:            Steps::new(transposer, start_time, rng_seed),
+                channel_statuses: ChannelStatuses::new(),
+            }),
+            interpolations: Mutex::new(HashMap::new()),
+        }
+    }
 
-    type State = T::OutputState;
+    /// choose which saturated steps are kept around, to trade memory against recomputation.
+    ///
+    /// the default is [`FixedCount`](super::checkpoint_strategy::FixedCount) with 30 checkpoints.
+    pub fn with_checkpoint_strategy(
+        mut self,
+        strategy: impl CheckpointStrategy<T::Time> + 'static,
+    ) -> Self {
+        self.saturation
+            .get_mut()
+            .steps
+            .set_checkpoint_strategy(strategy);
+        self
+    }
 
-    type Error = ();
+    /// the approximate number of bytes held by the saturated steps this source is keeping.
+    ///
+    /// this is based on [`Transposer::heap_size`],
+    /// and is what [`ByteBudget`](super::checkpoint_strategy::ByteBudget) keeps within its budget.
+    pub fn memory_usage(&self) -> usize {
+        self.saturation.lock().steps.memory_usage()
+    }
 
-    fn poll(
-        &mut self,
-        time: Self::Time,
-        cx: SourceContext,
-    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+    // drive the steps until the channel can interpolate, under the saturation lock.
+    fn poll_saturation(&self, time: T::Time, cx: &SourceContext) -> SaturationPoll<T, S> {
         let SourceContext {
             channel: caller_channel,
             one_channel_waker,
             all_channel_waker,
         } = cx;
 
-        let mut current_state = self.channel_statuses.get_channel_status(caller_channel);
+        let mut saturation = self.saturation.lock();
+        let Saturation {
+            steps,
+            channel_statuses,
+        } = &mut *saturation;
+
+        let mut current_state = channel_statuses.get_channel_status(*caller_channel);
 
         loop {
             current_state = match current_state {
                 CallerChannelStatus::Free(free) => {
                     let pinned_times = free.get_pinned_times();
 
-                    match self.steps.get_before_or_at(time, &pinned_times).unwrap() {
+                    match steps
+                        .get_before_or_at(time, &pinned_times, &mut None)
+                        .unwrap()
+                    {
                         BeforeStatus::Saturated {
                             step, ..
-                        } => {
-                            let interpolation = step.interpolate(time).unwrap();
-                            let interpolation = free.start_interpolation(interpolation, time);
-                            CallerChannelStatus::InterpolationFuture(interpolation)
-                        },
+                        } => return SaturationPoll::Interpolate(step.interpolate(time).unwrap()),
                         BeforeStatus::Saturating {
                             step,
                             step_index,
@@ -75,21 +149,9 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Source for NoInputTransp
                         },
                     }
                 },
+                // interpolations are never stored in the channel statuses.
                 CallerChannelStatus::InterpolationFuture(interpolation) => {
-                    let prev_time = interpolation.caller_channel.get_value().poll_time;
-                    if prev_time != time {
-                        current_state = CallerChannelStatus::Free(interpolation.abandon());
-                        continue
-                    }
-
-                    let (_, poll) = interpolation.poll(&one_channel_waker);
-                    return Ok(match poll {
-                        Poll::Pending => SourcePoll::Pending,
-                        Poll::Ready(state) => SourcePoll::Ready {
-                            state,
-                            next_event_at: self.steps.get_scheduled_time(),
-                        },
-                    })
+                    CallerChannelStatus::Free(interpolation.abandon())
                 },
                 CallerChannelStatus::OriginalStepFuture(original) => {
                     let prev_time = original.caller_channel.get_value().poll_time;
@@ -98,16 +160,18 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Source for NoInputTransp
                         continue
                     }
 
-                    let step = self.steps.get_last_mut();
+                    let step = steps.get_last_mut();
 
-                    let free = match original.poll(step, &all_channel_waker) {
+                    let free = match original.poll(step, all_channel_waker) {
                         OriginalStepPoll::OutputEvent(event) => {
-                            return Ok(SourcePoll::Interrupt {
+                            return SaturationPoll::Done(Ok(SourcePoll::Interrupt {
                                 time:      step.get_time(),
                                 interrupt: crate::source_poll::Interrupt::FinalizedEvent(event),
-                            })
+                            }))
+                        },
+                        OriginalStepPoll::Pending => {
+                            return SaturationPoll::Done(Ok(SourcePoll::Pending))
                         },
-                        OriginalStepPoll::Pending => return Ok(SourcePoll::Pending),
                         OriginalStepPoll::Free(free) => free,
                     };
 
@@ -122,10 +186,10 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Source for NoInputTransp
                         continue
                     }
 
-                    let step = self.steps.get_mut_by_sequence_number(step_id).unwrap();
+                    let step = steps.get_mut_by_sequence_number(step_id).unwrap();
 
-                    let free = match repeat.poll(step, &one_channel_waker) {
-                        Poll::Pending => return Ok(SourcePoll::Pending),
+                    let free = match repeat.poll(step, one_channel_waker) {
+                        Poll::Pending => return SaturationPoll::Done(Ok(SourcePoll::Pending)),
                         Poll::Ready(free) => free,
                     };
 
@@ -135,17 +199,131 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Source for NoInputTransp
         }
     }
 
+    // forget the interpolation of a channel, unless it has already been replaced.
+    fn remove_interpolation(
+        &self,
+        caller_channel: usize,
+        interpolation: &SharedInterpolation<T, S>,
+    ) {
+        let mut interpolations = self.interpolations.lock();
+        if interpolations
+            .get(&caller_channel)
+            .is_some_and(|current| Arc::ptr_eq(current, interpolation))
+        {
+            interpolations.remove(&caller_channel);
+        }
+    }
+}
+
+impl<T: Transposer<InputStateManager = NoInputManager>, S: StorageFamily> Source
+    for NoInputTransposerSource<T, S>
+{
+    type Time = T::Time;
+
+    type Event = T::OutputEvent;
+
+    type State = T::OutputState;
+
+    type Error = ();
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_concurrent(time, cx)
+    }
+
     fn poll_events(
         &mut self,
         time: Self::Time,
-        all_channel_waker: std::task::Waker,
+        all_channel_waker: Waker,
     ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
-        let pinned_times = self.channel_statuses.get_pinned_times();
+        self.poll_events_concurrent(time, all_channel_waker)
+    }
+
+    fn release_channel(&mut self, channel: usize) {
+        self.release_channel_concurrent(channel)
+    }
+
+    fn advance(&mut self, time: Self::Time) {
+        self.advance_concurrent(time)
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        NonZeroUsize::MAX
+    }
+}
+
+impl<T: Transposer<InputStateManager = NoInputManager>, S: StorageFamily> ConcurrentSource
+    for NoInputTransposerSource<T, S>
+{
+    fn poll_concurrent(
+        &self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        loop {
+            let interpolation = self.interpolations.lock().get(&cx.channel).cloned();
+
+            let interpolation = match interpolation {
+                Some(interpolation) => interpolation,
+                None => match self.poll_saturation(time, &cx) {
+                    SaturationPoll::Done(poll) => return poll,
+                    SaturationPoll::Interpolate(interpolation) => {
+                        let interpolation = Arc::new(Mutex::new(ChannelInterpolation {
+                            poll_time: time,
+                            interpolation,
+                        }));
+                        self.interpolations
+                            .lock()
+                            .insert(cx.channel, interpolation.clone());
+                        interpolation
+                    },
+                },
+            };
+
+            // only this channel's interpolation is locked while it runs.
+            let mut channel_interpolation = interpolation.lock();
+            if channel_interpolation.poll_time != time {
+                drop(channel_interpolation);
+                self.remove_interpolation(cx.channel, &interpolation);
+                continue
+            }
+
+            let mut context = Context::from_waker(&cx.one_channel_waker);
+            let poll = Pin::new(&mut channel_interpolation.interpolation).poll(&mut context);
+            drop(channel_interpolation);
+
+            return Ok(match poll {
+                Poll::Pending => SourcePoll::Pending,
+                Poll::Ready(state) => {
+                    self.remove_interpolation(cx.channel, &interpolation);
+                    SourcePoll::Ready {
+                        state,
+                        next_event_at: self.saturation.lock().steps.get_scheduled_time(),
+                    }
+                },
+            })
+        }
+    }
+
+    fn poll_events_concurrent(
+        &self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        let mut saturation = self.saturation.lock();
+        let Saturation {
+            steps,
+            channel_statuses,
+        } = &mut *saturation;
+
+        let pinned_times = channel_statuses.get_pinned_times();
 
         let poll = loop {
-            let (poll, time) = match self
-                .steps
-                .get_before_or_at_events(time, &pinned_times)
+            let (poll, time) = match steps
+                .get_before_or_at_events(time, &pinned_times, &mut None)
                 .unwrap()
             {
                 BeforeStatusEvents::Ready {
@@ -176,10 +354,13 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Source for NoInputTransp
         Ok(poll)
     }
 
-    fn release_channel(&mut self, channel: usize) {
-        let current_state = self.channel_statuses.get_channel_status(channel);
+    fn release_channel_concurrent(&self, channel: usize) {
+        self.interpolations.lock().remove(&channel);
+
+        let mut saturation = self.saturation.lock();
+        let current_state = saturation.channel_statuses.get_channel_status(channel);
 
-        let _: Free<T> = match current_state {
+        let _: Free<T, NoInput, S> = match current_state {
             CallerChannelStatus::Free(f) => f,
             CallerChannelStatus::InterpolationFuture(i) => i.abandon(),
             CallerChannelStatus::OriginalStepFuture(o) => o.abandon(),
@@ -187,11 +368,218 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Source for NoInputTransp
         };
     }
 
-    fn advance(&mut self, time: Self::Time) {
-        self.steps.delete_before(time);
+    fn advance_concurrent(&self, time: Self::Time) {
+        self.saturation.lock().steps.delete_before(time);
+    }
+}
+
+impl<T, S> StateHashSource for NoInputTransposerSource<T, S>
+where
+    T: Transposer<InputStateManager = NoInputManager> + TransposerHash,
+    S: StorageFamily,
+    T::Time: Hash,
+    T::Period: Hash,
+    T::Scheduled: Hash,
+{
+    fn enable_state_hashing(&mut self) {
+        self.saturation.get_mut().steps.enable_state_hashing()
+    }
+
+    fn state_hashes(&self, range: (Bound<T::Time>, Bound<T::Time>)) -> Vec<(usize, T::Time, u64)> {
+        self.saturation.lock().steps.state_hashes(range)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Barrier};
+
+    use transposer::context::{HandleScheduleContext, InitContext, InterpolateContext};
+    use transposer::schedule_storage::{StdStorage, StorageFamily};
+    use transposer::step::NoInputManager;
+    use transposer::Transposer;
+
+    use super::{DefaultStorage, NoInputTransposerSource};
+    use crate::adapters::TransposeStorage;
+    use crate::source_poll::Interrupt;
+    use crate::sources::transposer::multi_input_transposer::test::cx;
+    use crate::traits::ConcurrentSource;
+    use crate::SourcePoll;
+
+    #[derive(Clone)]
+    struct CounterTransposer {
+        count: usize,
+    }
+
+    impl Transposer for CounterTransposer {
+        type Time = usize;
+
+        type Period = usize;
+
+        type OutputState = usize;
+
+        type Scheduled = ();
+
+        type OutputEvent = usize;
+
+        type InputStateManager = NoInputManager;
+
+        async fn init(&mut self, cx: &mut impl InitContext<Self>) {
+            cx.schedule_repeating(1, 2, ()).unwrap();
+        }
+
+        async fn handle_scheduled(
+            &mut self,
+            _payload: Self::Scheduled,
+            cx: &mut impl HandleScheduleContext<Self>,
+        ) {
+            self.count += 1;
+            cx.emit_event(self.count).await;
+        }
+
+        async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {
+            self.count
+        }
     }
 
-    fn max_channel(&self) -> std::num::NonZeroUsize {
-        std::num::NonZeroUsize::MAX
+    // poll until ready, returning the state and the emitted events.
+    fn poll_state<S: StorageFamily>(
+        source: &NoInputTransposerSource<CounterTransposer, S>,
+        time: usize,
+        channel: usize,
+    ) -> (usize, Vec<(usize, usize)>) {
+        let mut events = Vec::new();
+        loop {
+            match source.poll_concurrent(time, cx(channel)).ok().unwrap() {
+                SourcePoll::Ready {
+                    state, ..
+                } => return (state, events),
+                SourcePoll::Interrupt {
+                    time,
+                    interrupt: Interrupt::FinalizedEvent(e),
+                } => events.push((time, e)),
+                _ => panic!(),
+            }
+        }
+    }
+
+    #[test]
+    fn concurrent_channels_test() {
+        let source = NoInputTransposerSource::new(
+            CounterTransposer {
+                count: 0
+            },
+            0,
+            [0; 32],
+        );
+
+        assert_eq!(poll_state(&source, 6, 0), (3, vec![(1, 1), (3, 2), (5, 3)]));
+
+        // the steps are already saturated, so other channels only interpolate.
+        assert_eq!(poll_state(&source, 2, 1), (1, vec![]));
+        assert_eq!(poll_state(&source, 4, 2), (2, vec![]));
+        assert_eq!(poll_state(&source, 6, 1), (3, vec![]));
+
+        source.release_channel_concurrent(1);
+        source.advance_concurrent(4);
+        assert_eq!(poll_state(&source, 8, 0), (4, vec![(7, 4)]));
+    }
+
+    #[allow(unused)]
+    fn sources_are_send_and_sync() {
+        fn assert_send_and_sync<T: Send + Sync>() {}
+
+        assert_send_and_sync::<NoInputTransposerSource<CounterTransposer, DefaultStorage>>();
+        assert_send_and_sync::<NoInputTransposerSource<CounterTransposer, StdStorage>>();
+    }
+
+    #[test]
+    fn threaded_channels_test() {
+        let source = NoInputTransposerSource::new(
+            CounterTransposer {
+                count: 0
+            },
+            0,
+            [0; 32],
+        );
+
+        let states = std::thread::scope(|scope| {
+            let handles: Vec<_> = [(6, 0), (2, 1), (9, 2), (4, 3)]
+                .map(|(time, channel)| {
+                    let source = &source;
+                    scope.spawn(move || poll_state(source, time, channel).0)
+                })
+                .into();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(states, vec![3, 1, 5, 2]);
+    }
+
+    // every interpolation waits until `barrier` has as many of them as it was made for.
+    #[derive(Clone)]
+    struct BarrierTransposer {
+        barrier: Arc<Barrier>,
+    }
+
+    impl Transposer for BarrierTransposer {
+        type Time = usize;
+
+        type OutputState = ();
+
+        type Scheduled = ();
+
+        type OutputEvent = ();
+
+        type InputStateManager = NoInputManager;
+
+        async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {
+            self.barrier.wait();
+        }
+    }
+
+    #[test]
+    fn parallel_interpolations_test() {
+        let source = NoInputTransposerSource::new(
+            BarrierTransposer {
+                barrier: Arc::new(Barrier::new(2)),
+            },
+            0,
+            [0; 32],
+        );
+
+        // this only finishes if the two interpolations run at the same time.
+        std::thread::scope(|scope| {
+            for channel in [0, 1] {
+                let source = &source;
+                scope.spawn(move || {
+                    let poll = source.poll_concurrent(5, cx(channel)).ok().unwrap();
+                    assert!(matches!(poll, SourcePoll::Ready { .. }));
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn storage_families_agree_test() {
+        fn run<S: StorageFamily>() -> Vec<(usize, Vec<(usize, usize)>)> {
+            let source = NoInputTransposerSource::<_, S>::new_with_storage(
+                CounterTransposer {
+                    count: 0
+                },
+                0,
+                [0; 32],
+            );
+
+            [6, 2, 9, 4].map(|t| poll_state(&source, t, 0)).into()
+        }
+
+        let expected = run::<DefaultStorage>();
+        assert_eq!(run::<TransposeStorage>(), expected);
+        assert_eq!(run::<StdStorage>(), expected);
     }
 }
@@ -1,18 +1,24 @@
 use core::fmt::Debug;
+use core::hash::Hash;
+use core::ops::{Bound, RangeBounds};
 use std::collections::{BTreeSet, VecDeque};
 
-use transposer::step::{NoInput, NoInputManager, Step};
-use transposer::Transposer;
+use transposer::schedule_storage::StorageFamily;
+use transposer::step::{InputState, Step, StepInputs};
+use transposer::{Transposer, TransposerHash};
 
-pub struct Steps<T: Transposer<InputStateManager = NoInputManager>> {
-    steps:                 VecDeque<StepWrapper<T>>,
-    not_unsaturated:       BTreeSet<usize>,
-    num_deleted_steps:     usize,
-    deleted_before:        Option<T::Time>,
-    number_of_checkpoints: usize,
+use super::checkpoint_strategy::{Checkpoint, CheckpointStrategy, FixedCount};
+use super::input_buffer::InputBuffer;
+
+pub struct Steps<T: Transposer, Is: InputState<T>, S: StorageFamily> {
+    steps:               VecDeque<StepWrapper<T, Is, S>>,
+    not_unsaturated:     BTreeSet<usize>,
+    num_deleted_steps:   usize,
+    deleted_before:      Option<T::Time>,
+    checkpoint_strategy: Box<dyn CheckpointStrategy<T::Time>>,
 }
 
-impl<T: Transposer<InputStateManager = NoInputManager>> Steps<T> {
+impl<T: Transposer, Is: InputState<T>, S: StorageFamily> Steps<T, Is, S> {
     #[cfg(debug_assertions)]
     fn debug_assertions(&self) {
         assert_eq!(
@@ -33,21 +39,65 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Steps<T> {
             not_unsaturated,
             num_deleted_steps: 0,
             deleted_before: None,
-            number_of_checkpoints: 30,
+            checkpoint_strategy: Box::new(FixedCount::default()),
+        }
+    }
+
+    pub fn set_checkpoint_strategy(
+        &mut self,
+        strategy: impl CheckpointStrategy<T::Time> + 'static,
+    ) {
+        self.checkpoint_strategy = Box::new(strategy);
+    }
+
+    /// hash the state of every step when it is first saturated.
+    pub fn enable_state_hashing(&mut self)
+    where
+        T: TransposerHash,
+        T::Time: Hash,
+        T::Period: Hash,
+        T::Scheduled: Hash,
+    {
+        for wrapper in self.steps.iter_mut() {
+            wrapper.step.enable_state_hashing();
         }
     }
 
-    pub fn get_mut_by_sequence_number(&mut self, i: usize) -> Option<&mut Step<T, NoInput>> {
+    /// the sequence number, time and state hash of every hashed step in `range`, in order.
+    pub fn state_hashes(
+        &self,
+        range: (Bound<T::Time>, Bound<T::Time>),
+    ) -> Vec<(usize, T::Time, u64)> {
+        self.steps
+            .iter()
+            .enumerate()
+            .filter(|(_, wrapper)| range.contains(&wrapper.step.get_time()))
+            .filter_map(|(i, wrapper)| {
+                let hash = wrapper.step.state_hash()?;
+                Some((i + self.num_deleted_steps, wrapper.step.get_time(), hash))
+            })
+            .collect()
+    }
+
+    /// the approximate number of bytes held by all the saturated steps.
+    pub fn memory_usage(&self) -> usize {
+        self.steps
+            .iter()
+            .filter_map(|wrapper| wrapper.step.approximate_size())
+            .sum()
+    }
+
+    pub fn get_mut_by_sequence_number(&mut self, i: usize) -> Option<&mut Step<T, Is, S>> {
         let i = i.checked_sub(self.num_deleted_steps)?;
 
         self.steps.get_mut(i).map(|s| &mut s.step)
     }
 
-    pub fn get_last(&self) -> &Step<T, NoInput> {
+    pub fn get_last(&self) -> &Step<T, Is, S> {
         &self.steps.back().unwrap().step
     }
 
-    pub fn get_last_mut(&mut self) -> &mut Step<T, NoInput> {
+    pub fn get_last_mut(&mut self) -> &mut Step<T, Is, S> {
         &mut self.steps.back_mut().unwrap().step
     }
 
@@ -64,19 +114,14 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Steps<T> {
     fn saturate(&mut self, step_to_saturate: usize, pinned_times: &[T::Time]) {
         // println!("{:?}", step_to_saturate);
 
-        let to_desaturate = self.sequence_number_to_delete(pinned_times, step_to_saturate);
-
-        let take = match to_desaturate {
-            Some(to_desaturate) => {
-                let adjacent = to_desaturate == step_to_saturate - 1;
-                if !adjacent {
-                    self.desaturate(to_desaturate - self.num_deleted_steps);
-                }
-
-                adjacent
-            },
-            None => false,
-        };
+        let mut take = false;
+        for to_desaturate in self.sequence_numbers_to_delete(pinned_times, step_to_saturate) {
+            if to_desaturate == step_to_saturate - 1 {
+                take = true;
+            } else {
+                self.desaturate(to_desaturate - self.num_deleted_steps);
+            }
+        }
 
         self.saturate_adjacent(step_to_saturate - self.num_deleted_steps, take);
         self.ensure_first_step_is_not_unsaturated();
@@ -129,35 +174,25 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Steps<T> {
         }
     }
 
-    fn sequence_number_to_delete(
-        &self,
+    fn sequence_numbers_to_delete(
+        &mut self,
         pinned_times: &[T::Time],
         newly_saturated: usize,
-    ) -> Option<usize> {
-        // this cannot be:
+    ) -> Vec<usize> {
+        // the strategy cannot delete:
         // - the earliest not-unsaturated step, if the next step is after "deleted_before"
         // - the latest not-unsaturated step (either the second to last step if its unsaturated, or else the last step)
         // - the latest not-unsaturated step before or at each pinned time
         // or else the "make progress" gurantees of the source will not be upheld.
-        //
-        // of the remaining, the minimum i by:
-        // 1: the number of trailing zeroes of i
-        // 2: i
-        //
-        // the effect is we try to preserve equally spaced checkpoints, so we don't have to recalculate too much.
 
         let first = *self.not_unsaturated.first().unwrap();
         let first_time = self.steps.front().unwrap().step.get_time();
         if let Some(deleted_before) = self.deleted_before {
             if first_time < deleted_before && first + 1 == newly_saturated {
-                return Some(first)
+                return vec![first]
             }
         }
 
-        if self.number_of_checkpoints > self.not_unsaturated.len() {
-            return None
-        }
-
         let mut needed_steps = Vec::new();
         needed_steps.push(first);
 
@@ -174,19 +209,47 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Steps<T> {
         needed_steps.sort();
         needed_steps.dedup();
 
-        let candidates = self
+        let mut checkpoints: Vec<_> = self
             .not_unsaturated
             .iter()
-            .copied()
-            .filter(|i| needed_steps.binary_search(i).is_err());
+            .map(|i| self.checkpoint(*i, needed_steps.binary_search(i).is_err()))
+            .collect();
+        // the new step doesn't have a state yet, but it will be about as big as the one it is saturated from.
+        let next = Checkpoint {
+            size: self.checkpoint(newly_saturated - 1, false).size,
+            ..self.checkpoint(newly_saturated, false)
+        };
+
+        let mut to_delete = Vec::new();
+        while checkpoints.iter().any(|c| c.evictable) {
+            let i = match self
+                .checkpoint_strategy
+                .choose_eviction(&checkpoints, &next)
+            {
+                Some(i) => i,
+                None => break,
+            };
 
-        candidates.min_by_key(|i| (i.trailing_zeros(), *i))
+            let checkpoint = checkpoints.remove(i);
+            assert!(checkpoint.evictable);
+            to_delete.push(checkpoint.sequence_number);
+        }
+
+        to_delete
     }
 
-    fn get_step_and_prev_mut(
-        &mut self,
-        i: usize,
-    ) -> (&mut Step<T, NoInput>, &mut Step<T, NoInput>) {
+    fn checkpoint(&self, sequence_number: usize, evictable: bool) -> Checkpoint<T::Time> {
+        let step = &self.steps[sequence_number - self.num_deleted_steps].step;
+
+        Checkpoint {
+            sequence_number,
+            time: step.get_time(),
+            size: step.approximate_size().unwrap_or(0),
+            evictable,
+        }
+    }
+
+    fn get_step_and_prev_mut(&mut self, i: usize) -> (&mut Step<T, Is, S>, &mut Step<T, Is, S>) {
         // this is all a dance to get a mutable reference to
         // steps[i] and steps[i - 1] simultaneously with no unsafe
         let (front, back) = self.steps.as_mut_slices();
@@ -203,11 +266,22 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Steps<T> {
         )
     }
 
-    fn try_next_scheduled(&mut self) {
+    fn try_next(&mut self, next_inputs: &mut Option<StepInputs<T, S>>) {
+        // a step which has never been saturated can be rebuilt if inputs arrived before it.
+        if let Some(inputs) = next_inputs {
+            let last_step = &self.steps.back().unwrap().step;
+            if last_step.is_unsaturated()
+                && last_step.can_produce_events()
+                && inputs.time() <= last_step.get_time()
+            {
+                self.steps.pop_back();
+            }
+        }
+
         // ensure theres always a saturating or unsaturated step after the time polled.
         let last_step = &self.steps.back().unwrap().step;
         if last_step.is_saturated() {
-            if let Some(new_step) = last_step.next_scheduled_unsaturated().unwrap() {
+            if let Some(new_step) = last_step.next_unsaturated(next_inputs).unwrap() {
                 self.steps.push_back(StepWrapper {
                     step: new_step
                 })
@@ -219,43 +293,58 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Steps<T> {
         &mut self,
         time: T::Time,
         pinned_times: &[T::Time],
-    ) -> Result<BeforeStatusEvents<'_, T>, ()> {
+        next_inputs: &mut Option<StepInputs<T, S>>,
+    ) -> Result<BeforeStatusEvents<'_, T, Is, S>, ()> {
         #[cfg(debug_assertions)]
         self.debug_assertions();
 
-        self.try_next_scheduled();
+        self.try_next(next_inputs);
 
         let last_step_index = self.max_sequence_number();
         let last_step = &mut self.steps.back_mut().unwrap().step;
 
-        if time < last_step.get_time() || !last_step.can_produce_events() {
-            let next_time = (last_step.can_produce_events()).then_some(last_step.get_time());
+        if last_step.is_saturated() || time < last_step.get_time() {
+            let next_time = (!last_step.is_saturated()).then_some(last_step.get_time());
 
-            Ok(BeforeStatusEvents::Ready {
+            return Ok(BeforeStatusEvents::Ready {
                 next_time,
             })
-        } else {
-            if last_step.is_unsaturated() {
-                self.saturate(last_step_index, pinned_times);
+        }
+
+        if !last_step.can_produce_events() {
+            // the last step was left behind by a rollback,
+            // so it has to be recomputed before we can move past it.
+            let last_step_time = last_step.get_time();
+            return match self.get_before_or_at_internal(last_step_time, pinned_times, &mut None)? {
+                BeforeStatusInternal::Saturating(i) => Ok(BeforeStatusEvents::Saturating {
+                    step_index: i + self.num_deleted_steps,
+                    step:       &mut self.steps.get_mut(i).unwrap().step,
+                }),
+                _ => unreachable!(),
             }
-            let last_step = &mut self.steps.back_mut().unwrap().step;
-            Ok(BeforeStatusEvents::Saturating {
-                step:       last_step,
-                step_index: last_step_index,
-            })
         }
+
+        if last_step.is_unsaturated() {
+            self.saturate(last_step_index, pinned_times);
+        }
+        let last_step = &mut self.steps.back_mut().unwrap().step;
+        Ok(BeforeStatusEvents::Saturating {
+            step:       last_step,
+            step_index: last_step_index,
+        })
     }
 
     pub fn get_before_or_at(
         &mut self,
         time: T::Time,
         pinned_times: &[T::Time],
-    ) -> Result<BeforeStatus<'_, T>, ()> {
+        next_inputs: &mut Option<StepInputs<T, S>>,
+    ) -> Result<BeforeStatus<'_, T, Is, S>, ()> {
         #[cfg(debug_assertions)]
         self.debug_assertions();
 
         Ok(
-            match self.get_before_or_at_internal(time, pinned_times, false)? {
+            match self.get_before_or_at_internal(time, pinned_times, next_inputs)? {
                 BeforeStatusInternal::SaturatedReady(i) => {
                     let original_step = self.steps.back().unwrap();
                     let next_time = (!original_step.step.is_saturated())
@@ -275,7 +364,6 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Steps<T> {
                         step_index,
                     }
                 },
-                _ => panic!(),
             },
         )
     }
@@ -284,21 +372,12 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Steps<T> {
         &mut self,
         time: T::Time,
         pinned_times: &[T::Time],
-        events_only: bool,
+        next_inputs: &mut Option<StepInputs<T, S>>,
     ) -> Result<BeforeStatusInternal, ()> {
-        self.try_next_scheduled();
-
-        if events_only {
-            let last_step = &self.steps.back_mut().unwrap().step;
-            if time < last_step.get_time() || !last_step.can_produce_events() {
-                return Ok(BeforeStatusInternal::AllRepeat)
-            } else {
-                return Ok(BeforeStatusInternal::Saturating(self.max_sequence_number()))
-            }
-        }
+        self.try_next(next_inputs);
 
         // this is just mimicking partition_point, because vecdeque isn't actually contiguous
-        let vecdeque_index = match self
+        let mut vecdeque_index = match self
             .steps
             .binary_search_by_key(&time, |s| s.step.get_time())
         {
@@ -315,14 +394,17 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Steps<T> {
             return Ok(BeforeStatusInternal::Saturating(vecdeque_index))
         }
 
+        // walk back to the latest step which can be saturated from.
         loop {
-            let vecdeque_index = vecdeque_index.checked_sub(1).ok_or(())?;
+            vecdeque_index = vecdeque_index.checked_sub(1).ok_or(())?;
             let step = self.steps.get_mut(vecdeque_index).ok_or(())?;
 
             if step.step.is_saturated() {
-                let vecdeque_index = vecdeque_index + 1;
-                let step_to_saturate = vecdeque_index + self.num_deleted_steps;
+                let step_to_saturate = vecdeque_index + 1 + self.num_deleted_steps;
                 self.saturate(step_to_saturate, pinned_times);
+
+                // saturating can delete steps from the front, so the index is recomputed.
+                let vecdeque_index = step_to_saturate - self.num_deleted_steps;
                 return Ok(BeforeStatusInternal::Saturating(vecdeque_index))
             }
 
@@ -361,6 +443,38 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Steps<T> {
         self.earliest_poll_time()
     }
 
+    /// discard all the steps at or after `time`, always keeping the initial step.
+    /// the inputs of the discarded steps are returned to `input_buffer`.
+    ///
+    /// this returns whether any of the discarded steps were ever saturated,
+    /// meaning events or states derived from them may have been observed.
+    pub fn rollback(&mut self, time: T::Time, input_buffer: &mut InputBuffer<T, S>) -> bool {
+        let keep = self
+            .steps
+            .partition_point(|s| s.step.get_time() < time)
+            .max(1);
+
+        if keep == self.steps.len() {
+            return false
+        }
+
+        let observed = self
+            .steps
+            .range(keep..)
+            .any(|s| !s.step.is_unsaturated() || !s.step.can_produce_events());
+
+        for wrapper in self.steps.drain(keep..) {
+            if let Some(inputs) = wrapper.step.into_inputs() {
+                input_buffer.insert(inputs);
+            }
+        }
+        let _ = self
+            .not_unsaturated
+            .split_off(&(keep + self.num_deleted_steps));
+
+        observed
+    }
+
     fn earliest_poll_time(&self) -> Option<T::Time> {
         self.steps
             .iter()
@@ -368,23 +482,23 @@ impl<T: Transposer<InputStateManager = NoInputManager>> Steps<T> {
     }
 }
 
-pub enum BeforeStatus<'a, T: Transposer<InputStateManager = NoInputManager>> {
+pub enum BeforeStatus<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> {
     Saturated {
-        step:      &'a Step<T, NoInput>,
+        step:      &'a Step<T, Is, S>,
         next_time: Option<T::Time>,
     },
     Saturating {
-        step:       &'a mut Step<T, NoInput>,
+        step:       &'a mut Step<T, Is, S>,
         step_index: usize,
     },
 }
 
-pub enum BeforeStatusEvents<'a, T: Transposer<InputStateManager = NoInputManager>> {
+pub enum BeforeStatusEvents<'a, T: Transposer, Is: InputState<T>, S: StorageFamily> {
     Ready {
         next_time: Option<T::Time>,
     },
     Saturating {
-        step:       &'a mut Step<T, NoInput>,
+        step:       &'a mut Step<T, Is, S>,
         step_index: usize,
     },
 }
@@ -392,14 +506,13 @@ pub enum BeforeStatusEvents<'a, T: Transposer<InputStateManager = NoInputManager
 pub enum BeforeStatusInternal {
     SaturatedReady(usize),
     Saturating(usize),
-    AllRepeat,
 }
 
-struct StepWrapper<T: Transposer<InputStateManager = NoInputManager>> {
-    pub step: Step<T, NoInput>,
+struct StepWrapper<T: Transposer, Is: InputState<T>, S: StorageFamily> {
+    pub step: Step<T, Is, S>,
 }
 
-impl<T: Transposer<InputStateManager = NoInputManager>> Debug for StepWrapper<T>
+impl<T: Transposer, Is: InputState<T>, S: StorageFamily> Debug for StepWrapper<T, Is, S>
 where
     T::Time: Debug,
 {
@@ -414,7 +527,7 @@ where
     }
 }
 
-impl<T: Transposer<InputStateManager = NoInputManager>> StepWrapper<T> {
+impl<T: Transposer, Is: InputState<T>, S: StorageFamily> StepWrapper<T, Is, S> {
     pub fn new_init(transposer: T, start_time: T::Time, rng_seed: [u8; 32]) -> Self {
         Self {
             step: Step::new_init(transposer, start_time, rng_seed),
@@ -426,11 +539,13 @@ impl<T: Transposer<InputStateManager = NoInputManager>> StepWrapper<T> {
 mod test {
 
     use transposer::context::{HandleScheduleContext, InitContext, InterpolateContext};
-    use transposer::step::NoInputManager;
+    use transposer::schedule_storage::DefaultStorage;
+    use transposer::step::{NoInput, NoInputManager};
     use transposer::Transposer;
     #[cfg(test)]
     use util::dummy_waker::DummyWaker;
 
+    use crate::sources::transposer::checkpoint_strategy::{ByteBudget, ExponentialSpacing};
     use crate::sources::transposer::steps::{BeforeStatus, BeforeStatusEvents, Steps};
     #[derive(Clone)]
     pub(crate) struct CollatzTransposer {
@@ -457,14 +572,14 @@ mod test {
         // set up with macro
         type InputStateManager = NoInputManager;
 
-        async fn init(&mut self, cx: &mut dyn InitContext<'_, Self>) {
+        async fn init(&mut self, cx: &mut impl InitContext<Self>) {
             cx.schedule_event(cx.current_time(), ()).unwrap();
         }
 
         async fn handle_scheduled(
             &mut self,
             _payload: Self::Scheduled,
-            cx: &mut dyn HandleScheduleContext<'_, Self>,
+            cx: &mut impl HandleScheduleContext<Self>,
         ) {
             cx.emit_event(self.value).await;
 
@@ -477,20 +592,21 @@ mod test {
             cx.schedule_event(cx.current_time() + 1, ()).unwrap();
         }
 
-        async fn interpolate(
-            &self,
-            _cx: &mut dyn InterpolateContext<'_, Self>,
-        ) -> Self::OutputState {
+        async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {}
+
+        // pretend to be big, so byte budgets are easy to reason about.
+        fn heap_size(&self) -> usize {
+            1000
         }
     }
 
     #[test]
     fn basic_test() {
         let transposer = CollatzTransposer::new(27);
-        let mut steps = Steps::new(transposer, 0, [0; 32]);
+        let mut steps = Steps::<_, NoInput, DefaultStorage>::new(transposer, 0, [0; 32]);
         let dummy = DummyWaker::dummy();
         for _ in 0..200 {
-            let _ = match steps.get_before_or_at(100000, &[]).unwrap() {
+            let _ = match steps.get_before_or_at(100000, &[], &mut None).unwrap() {
                 BeforeStatus::Saturating {
                     step, ..
                 } => step.poll(&dummy).unwrap(),
@@ -512,7 +628,7 @@ mod test {
     );
 
         for _ in 0..200 {
-            let _ = match steps.get_before_or_at(100000, &[]).unwrap() {
+            let _ = match steps.get_before_or_at(100000, &[], &mut None).unwrap() {
                 BeforeStatus::Saturating {
                     step, ..
                 } => step.poll(&dummy).unwrap(),
@@ -529,7 +645,7 @@ mod test {
     #[test]
     fn polling_after_advance_same_time_test() {
         let transposer = CollatzTransposer::new(27);
-        let mut steps = Steps::new(transposer, 0, [0; 32]);
+        let mut steps = Steps::<_, NoInput, DefaultStorage>::new(transposer, 0, [0; 32]);
         let dummy = DummyWaker::dummy();
         for t in 0..200 {
             if t != 0 {
@@ -554,7 +670,7 @@ mod test {
                 assert_eq!(*steps.not_unsaturated.first().unwrap(), t - 1);
             }
             loop {
-                let _ = match steps.get_before_or_at(t, &[]).unwrap() {
+                let _ = match steps.get_before_or_at(t, &[], &mut None).unwrap() {
                     BeforeStatus::Saturated {
                         ..
                     } => break,
@@ -577,10 +693,13 @@ mod test {
     #[test]
     fn basic_test_events() {
         let transposer = CollatzTransposer::new(27);
-        let mut steps = Steps::new(transposer, 0, [0; 32]);
+        let mut steps = Steps::<_, NoInput, DefaultStorage>::new(transposer, 0, [0; 32]);
         let dummy = DummyWaker::dummy();
         for _ in 0..200 {
-            let _ = match steps.get_before_or_at_events(100000, &[]).unwrap() {
+            let _ = match steps
+                .get_before_or_at_events(100000, &[], &mut None)
+                .unwrap()
+            {
                 BeforeStatusEvents::Ready {
                     ..
                 } => panic!(),
@@ -602,7 +721,10 @@ mod test {
     );
 
         for _ in 0..200 {
-            let _ = match steps.get_before_or_at_events(100000, &[]).unwrap() {
+            let _ = match steps
+                .get_before_or_at_events(100000, &[], &mut None)
+                .unwrap()
+            {
                 BeforeStatusEvents::Ready {
                     ..
                 } => panic!(),
@@ -619,7 +741,7 @@ mod test {
     #[test]
     fn polling_after_advance_same_time_test_events() {
         let transposer = CollatzTransposer::new(27);
-        let mut steps = Steps::new(transposer, 0, [0; 32]);
+        let mut steps = Steps::<_, NoInput, DefaultStorage>::new(transposer, 0, [0; 32]);
         let dummy = DummyWaker::dummy();
         for t in 0..200 {
             if t != 0 {
@@ -644,7 +766,7 @@ mod test {
                 assert_eq!(*steps.not_unsaturated.first().unwrap(), t - 1);
             }
             loop {
-                let _ = match steps.get_before_or_at_events(t, &[]).unwrap() {
+                let _ = match steps.get_before_or_at_events(t, &[], &mut None).unwrap() {
                     BeforeStatusEvents::Ready {
                         ..
                     } => break,
@@ -664,4 +786,95 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn checkpoint_strategy_keeps_pinned_steps_test() {
+        let transposer = CollatzTransposer::new(27);
+        let mut steps = Steps::<_, NoInput, DefaultStorage>::new(transposer, 0, [0; 32]);
+        steps.set_checkpoint_strategy(ExponentialSpacing(5));
+        let dummy = DummyWaker::dummy();
+        for _ in 0..200 {
+            let _ = match steps.get_before_or_at(100000, &[37], &mut None).unwrap() {
+                BeforeStatus::Saturating {
+                    step, ..
+                } => step.poll(&dummy).unwrap(),
+                BeforeStatus::Saturated {
+                    ..
+                } => panic!(),
+            };
+            assert!(steps.not_unsaturated.len() <= 6);
+        }
+
+        // the first and the pinned steps are always kept.
+        let output = format!("{:?}", steps.not_unsaturated);
+        assert_eq!(output, "{0, 37, 82, 95, 99}");
+
+        // a budget too small for anything still keeps what is needed to make progress.
+        steps.set_checkpoint_strategy(ByteBudget(0));
+        for _ in 0..10 {
+            let _ = match steps.get_before_or_at(100000, &[37], &mut None).unwrap() {
+                BeforeStatus::Saturating {
+                    step, ..
+                } => step.poll(&dummy).unwrap(),
+                BeforeStatus::Saturated {
+                    ..
+                } => panic!(),
+            };
+        }
+
+        let output = format!("{:?}", steps.not_unsaturated);
+        assert_eq!(output, "{0, 37, 104}");
+    }
+
+    #[test]
+    fn byte_budget_test() {
+        let transposer = CollatzTransposer::new(27);
+        let mut steps = Steps::<_, NoInput, DefaultStorage>::new(transposer, 0, [0; 32]);
+        steps.set_checkpoint_strategy(ByteBudget(10_000));
+        let dummy = DummyWaker::dummy();
+        let mut max_usage = 0;
+        for _ in 0..200 {
+            let _ = match steps.get_before_or_at(100000, &[], &mut None).unwrap() {
+                BeforeStatus::Saturating {
+                    step, ..
+                } => step.poll(&dummy).unwrap(),
+                BeforeStatus::Saturated {
+                    ..
+                } => panic!(),
+            };
+            max_usage = max_usage.max(steps.memory_usage());
+        }
+
+        assert!(max_usage <= 10_000);
+        assert!(steps.memory_usage() > 5_000);
+        assert!(steps.not_unsaturated.len() < 10);
+    }
+
+    #[test]
+    fn polling_backwards_test() {
+        let transposer = CollatzTransposer::new(27);
+        let mut steps = Steps::<_, NoInput, DefaultStorage>::new(transposer, 0, [0; 32]);
+        let dummy = DummyWaker::dummy();
+
+        // every time is polled, so recomputing it has to walk back over several desaturated steps.
+        for t in (0..200).rev() {
+            let mut polls = 0;
+            loop {
+                let _ = match steps.get_before_or_at(t, &[], &mut None).unwrap() {
+                    BeforeStatus::Saturated {
+                        step, ..
+                    } => {
+                        assert_eq!(step.get_time(), t);
+                        break
+                    },
+                    BeforeStatus::Saturating {
+                        step, ..
+                    } => step.poll(&dummy).unwrap(),
+                };
+
+                polls += 1;
+                assert!(polls < 1000);
+            }
+        }
+    }
 }
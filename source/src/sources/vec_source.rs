@@ -0,0 +1,249 @@
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+use std::task::Waker;
+
+use crate::source_poll::{Interrupt, SourcePollErr, TrySourcePoll};
+use crate::traits::SourceContext;
+use crate::{Source, SourcePoll};
+
+/// One step of the script played by a [`VecSource`].
+pub enum Scripted<T, E> {
+    /// Emit this event once the source is polled at or after its time.
+    Event(T, E),
+
+    /// Emit a rollback to this time once the source is polled at or after it.
+    /// The events after it in the script should include whatever it discarded.
+    Rollback(T),
+
+    /// Return `Pending` from the next poll, waking the waker it was given right away.
+    Pending,
+}
+
+/// A source which plays a fixed script of events, for tests and fixtures.
+///
+/// The state at any time is given by `state_fn`.
+/// Events are emitted as [`FinalizedEvent`](Interrupt::FinalizedEvent)s, unless a later rollback in the script covers them.
+pub struct VecSource<T, E, F> {
+    script:   VecDeque<Scripted<T, E>>,
+    state_fn: F,
+    advanced: Option<T>,
+}
+
+impl<T: Ord + Copy, E, S, F: Fn(T) -> S> VecSource<T, E, F> {
+    /// A source of the given events, which must be sorted by time.
+    pub fn new(events: Vec<(T, E)>, state_fn: F) -> Self {
+        debug_assert!(events.is_sorted_by_key(|(t, _)| *t));
+
+        let script = events
+            .into_iter()
+            .map(|(time, event)| Scripted::Event(time, event))
+            .collect();
+
+        Self::scripted(script, state_fn)
+    }
+
+    /// A source which plays `script` in order.
+    pub fn scripted(script: Vec<Scripted<T, E>>, state_fn: F) -> Self {
+        Self {
+            script: script.into(),
+            state_fn,
+            advanced: None,
+        }
+    }
+
+    fn poll_internal(&mut self, time: T, waker: &Waker) -> TrySourcePoll<T, E, (), ()> {
+        if let Some(advanced) = self.advanced {
+            if time < advanced {
+                return Err(SourcePollErr::PollAfterAdvance {
+                    advanced,
+                })
+            }
+        }
+
+        let (t, interrupt) = match self.script.front() {
+            Some(Scripted::Pending) => {
+                self.script.pop_front();
+                waker.wake_by_ref();
+                return Ok(SourcePoll::Pending)
+            },
+            Some(Scripted::Event(t, _)) if *t <= time => {
+                let Some(Scripted::Event(t, event)) = self.script.pop_front() else {
+                    unreachable!()
+                };
+
+                let rolled_back = self.script.iter().any(|step| match step {
+                    Scripted::Rollback(r) => *r <= t,
+                    _ => false,
+                });
+
+                if rolled_back {
+                    (t, Interrupt::Event(event))
+                } else {
+                    (t, Interrupt::FinalizedEvent(event))
+                }
+            },
+            Some(Scripted::Rollback(t)) if *t <= time => {
+                let t = *t;
+                self.script.pop_front();
+                (t, Interrupt::Rollback)
+            },
+            _ => {
+                let next_event_at = self.script.iter().find_map(|step| match step {
+                    Scripted::Event(t, _) | Scripted::Rollback(t) => Some(*t),
+                    Scripted::Pending => None,
+                });
+
+                return Ok(SourcePoll::Ready {
+                    state: (),
+                    next_event_at,
+                })
+            },
+        };
+
+        Ok(SourcePoll::Interrupt {
+            time: t,
+            interrupt,
+        })
+    }
+}
+
+impl<T: Ord + Copy, E, S, F: Fn(T) -> S> Source for VecSource<T, E, F> {
+    type Time = T;
+
+    type Event = E;
+
+    type State = S;
+
+    type Error = ();
+
+    fn poll(
+        &mut self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        let poll = self.poll_internal(time, &cx.one_channel_waker)?;
+        Ok(poll.map_state(|()| (self.state_fn)(time)))
+    }
+
+    fn poll_events(
+        &mut self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error> {
+        self.poll_internal(time, &all_channel_waker)
+    }
+
+    fn release_channel(&mut self, _channel: usize) {}
+
+    fn advance(&mut self, time: Self::Time) {
+        self.advanced = self.advanced.max(Some(time));
+    }
+
+    fn max_channel(&self) -> NonZeroUsize {
+        NonZeroUsize::MAX
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::task::Wake;
+
+    use util::dummy_waker::DummyWaker;
+
+    use super::{Scripted, VecSource};
+    use crate::source_poll::Interrupt;
+    use crate::sources::transposer::multi_input_transposer::test::cx;
+    use crate::{Source, SourcePoll};
+
+    #[test]
+    fn events_and_states_test() {
+        let mut source = VecSource::new(vec![(1, 'a'), (3, 'b')], |t: usize| t * 10);
+
+        assert!(matches!(
+            source.poll(2, cx(0)).ok().unwrap(),
+            SourcePoll::Interrupt {
+                time:      1,
+                interrupt: Interrupt::FinalizedEvent('a'),
+            }
+        ));
+        assert!(matches!(
+            source.poll(2, cx(0)).ok().unwrap(),
+            SourcePoll::Ready {
+                state:         20,
+                next_event_at: Some(3),
+            }
+        ));
+
+        source.advance(2);
+        assert!(source.poll(1, cx(0)).is_err());
+    }
+
+    struct FlagWaker(AtomicBool);
+
+    impl Wake for FlagWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn scripted_test() {
+        let mut source = VecSource::scripted(
+            vec![
+                Scripted::Event(1, 'a'),
+                Scripted::Event(3, 'b'),
+                Scripted::Pending,
+                Scripted::Rollback(2),
+                Scripted::Event(3, 'c'),
+            ],
+            |_: usize| (),
+        );
+
+        // the first event isn't covered by the rollback, but the second one is.
+        assert!(matches!(
+            source.poll_events(5, DummyWaker::dummy()).ok().unwrap(),
+            SourcePoll::Interrupt {
+                time:      1,
+                interrupt: Interrupt::FinalizedEvent('a'),
+            }
+        ));
+        assert!(matches!(
+            source.poll_events(5, DummyWaker::dummy()).ok().unwrap(),
+            SourcePoll::Interrupt {
+                time:      3,
+                interrupt: Interrupt::Event('b'),
+            }
+        ));
+
+        let flag = Arc::new(FlagWaker(AtomicBool::new(false)));
+        assert!(matches!(
+            source.poll_events(5, flag.clone().into()),
+            Ok(SourcePoll::Pending)
+        ));
+        assert!(flag.0.load(Ordering::SeqCst));
+
+        assert!(matches!(
+            source.poll_events(5, DummyWaker::dummy()).ok().unwrap(),
+            SourcePoll::Interrupt {
+                time:      2,
+                interrupt: Interrupt::Rollback,
+            }
+        ));
+        assert!(matches!(
+            source.poll_events(5, DummyWaker::dummy()).ok().unwrap(),
+            SourcePoll::Interrupt {
+                time:      3,
+                interrupt: Interrupt::FinalizedEvent('c'),
+            }
+        ));
+        assert!(matches!(
+            source.poll_events(5, DummyWaker::dummy()).ok().unwrap(),
+            SourcePoll::Ready {
+                next_event_at: None,
+                ..
+            }
+        ));
+    }
+}
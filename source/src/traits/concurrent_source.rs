@@ -0,0 +1,40 @@
+use core::task::Waker;
+
+use super::{Source, SourceContext};
+use crate::source_poll::TrySourcePoll;
+
+/// A source which can be used through a shared reference.
+///
+/// Each method behaves exactly like the [`Source`] method of the same name.
+/// This allows several threads to query one source without wrapping it in a lock of their own,
+/// and lets implementers decide how much of the work can happen in parallel.
+pub trait ConcurrentSource: Source {
+    /// [`Source::poll`], through a shared reference.
+    fn poll_concurrent(
+        &self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error>;
+
+    /// [`Source::poll_forget`], through a shared reference.
+    fn poll_forget_concurrent(
+        &self,
+        time: Self::Time,
+        cx: SourceContext,
+    ) -> TrySourcePoll<Self::Time, Self::Event, Self::State, Self::Error> {
+        self.poll_concurrent(time, cx)
+    }
+
+    /// [`Source::poll_events`], through a shared reference.
+    fn poll_events_concurrent(
+        &self,
+        time: Self::Time,
+        all_channel_waker: Waker,
+    ) -> TrySourcePoll<Self::Time, Self::Event, (), Self::Error>;
+
+    /// [`Source::release_channel`], through a shared reference.
+    fn release_channel_concurrent(&self, channel: usize);
+
+    /// [`Source::advance`], through a shared reference.
+    fn advance_concurrent(&self, time: Self::Time);
+}
@@ -1,7 +1,11 @@
+mod concurrent_source;
 mod source;
 mod source_ext;
+mod state_hash_source;
 mod timestamp;
 
+pub use self::concurrent_source::ConcurrentSource;
 pub use self::source::{Source, SourceContext};
 pub use self::source_ext::SourceExt;
+pub use self::state_hash_source::StateHashSource;
 pub use self::timestamp::Timestamp;
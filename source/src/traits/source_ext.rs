@@ -1,58 +1,158 @@
 use std::time::Instant;
 
 use futures_core::Future;
+use transposer::schedule_storage::{DefaultStorage, StorageFamily};
+use transposer::single_input_state::SingleInputState;
+use transposer::step::{EraseInput, InputState};
+use transposer::{Transposer, TransposerInput, TransposerInputEventHandler};
 
-use super::Source;
+use super::{Source, StateHashSource, Timestamp};
+use crate::adapters::interrupt_iterator::InterruptIterator;
 use crate::adapters::interrupt_stream::InterruptStream;
-// use crate::adapters::MutexSource;
+#[cfg(feature = "serde")]
+use crate::adapters::Record;
+use crate::adapters::{
+    offload,
+    realtime,
+    Duplicate,
+    FilterMapEvents,
+    Join,
+    MapErr,
+    MapEvents,
+    MapState,
+    Multiplex,
+    MutexSource,
+    OffloadFuture,
+    OffloadSource,
+    RealtimeEvents,
+    RealtimeStates,
+    StateHashes,
+    Transpose,
+};
+use crate::sources::transposer::multi_input_transposer::TooFewChannels;
 
 impl<S> SourceExt for S where S: Source {}
 
 pub trait SourceExt: Source + Sized {
-    // / Adapter for converting a schedule_stream into one that yields the items in realtime, and a struct which can be queried yielding
-    // /
-    // / a reference must be given for the conversion from [`S::Time`](super::schedule_stream::ScheduleStream::Time) to [`Instant`](std::time::Instant).
-    // / for example if you use [`Duration`](core::time::Duration) as your time, a "start time" must be given
-    // / so that the duration can be added to something.
-    // /
-    // / if your timestamp is an [`Instant`](std::time::Instant), then your reference is of type `()` because instants
-    // / are already realtime and need no reference.
-    // /
-    // / Additionally, a sleep_fn must be provided. This should be simply `tokio::time::sleep_until` if using tokio, but is left generic to avoid requiring a runtime.
-    // fn realtime<SleepFut: Future, SleepFn: Fn(Instant) -> SleepFut>(
-    //     self,
-    //     reference: <Self::Time as Timestamp>::Reference,
-    //     sleep_fn: SleepFn,
-    // ) -> (RealtimeEvents<Self>, RealtimeStates<Self>)
-    // where
-    //     Self::Time: Timestamp,
-    // {
-    //     realtime(self, reference, sleep_fn)
-    // }
-
-    // /// Adapter for converting a source into another via a transposer.
-    // fn transpose<T>(self, initial: T, rng_seed: [u8; 32]) -> Transpose<Self, T>
-    // where
-    //     T::Time: Copy + Ord + Default + Unpin, // TODO remove once https://github.com/rust-lang/rust/issues/91985 is resolved.
-    //     T: Transposer<Time = Self::Time, Input = Self::Event, InputState = Self::State>,
-    //     T: Clone,
-    // {
-    //     Transpose::new(self, initial, rng_seed)
-    // }
-
-    // /// Adapter for offloading work to a future
-    // fn offload(self) -> (OffloadSource<Self>, OffloadFuture<Self>) {
-    //     offload(self)
-    // }
-
-    // /// Adapter for calling a limited-channel source on any number of channels
-    // fn multiplex(self) -> Multiplex<Self> {
-    //     Multiplex::new(self)
-    // }
-
-    // fn concurrent(self) -> MutexSource<Self> {
-    //     MutexSource::new(self)
-    // }
+    /// Adapter for driving a source by the wall clock, yielding a stream of its events and a sampler of its states.
+    ///
+    /// a reference must be given for the conversion from [`Self::Time`](Source::Time) to [`Instant`](std::time::Instant).
+    /// for example if you use [`Duration`](core::time::Duration) as your time, a "start time" must be given
+    /// so that the duration can be added to something.
+    ///
+    /// if your timestamp is an [`Instant`](std::time::Instant), then your reference is of type `()` because instants
+    /// are already realtime and need no reference.
+    ///
+    /// Additionally, a sleep_fn must be provided. This should be simply `tokio::time::sleep_until` if using tokio, but is left generic to avoid requiring a runtime.
+    fn realtime<SleepFut: Future<Output = ()>, SleepFn: Fn(Instant) -> SleepFut>(
+        self,
+        reference: <Self::Time as Timestamp>::Reference,
+        sleep_fn: SleepFn,
+    ) -> (
+        RealtimeEvents<Self, SleepFn, SleepFut>,
+        RealtimeStates<Self>,
+    )
+    where
+        Self::Time: Timestamp,
+    {
+        realtime(self, reference, sleep_fn)
+    }
+
+    /// Adapter for converting a source into another via a transposer.
+    ///
+    /// This fails if the source doesn't allow at least three channels, because one is reserved for computing steps.
+    fn transpose<T, I>(
+        self,
+        transposer: T,
+        start_time: Self::Time,
+        rng_seed: [u8; 32],
+    ) -> Result<Transpose<Self, T, I>, TooFewChannels>
+    where
+        T: Transposer<Time = Self::Time> + TransposerInputEventHandler<I>,
+        SingleInputState<I>: InputState<T>,
+        I: TransposerInput<Base = T, InputEvent = Self::Event, InputState = Self::State>,
+        <DefaultStorage as StorageFamily>::Erasure: EraseInput<I, DefaultStorage>,
+    {
+        Transpose::new(self, transposer, start_time, rng_seed)
+    }
+
+    /// Adapter for writing every interrupt of this source to a replay file.
+    ///
+    /// `start_time` and `rng_seed` should be the ones given to the transposer this source feeds.
+    /// States aren't recorded, so this is only available for sources with a state of `()`.
+    #[cfg(feature = "serde")]
+    fn record<W: std::io::Write>(
+        self,
+        writer: W,
+        start_time: Self::Time,
+        rng_seed: [u8; 32],
+    ) -> std::io::Result<Record<Self, W>>
+    where
+        Self: Source<State = ()>,
+        Self::Time: serde::Serialize,
+        Self::Event: serde::Serialize,
+    {
+        Record::new(self, writer, start_time, rng_seed)
+    }
+
+    /// Adapter for emitting the state hash of each step of a transposer alongside its events.
+    fn state_hashes(self) -> StateHashes<Self>
+    where
+        Self: StateHashSource,
+    {
+        StateHashes::new(self)
+    }
+
+    /// Adapter for feeding this source to any number of consumers, by cloning the result.
+    fn duplicate(self) -> Duplicate<Self>
+    where
+        Self::Event: Clone,
+    {
+        Duplicate::new(self)
+    }
+
+    /// Adapter for combining this source with another, pairing their states and merging their events
+    fn join<B: Source<Time = Self::Time>>(self, other: B) -> Join<Self, B> {
+        Join::new(self, other)
+    }
+
+    /// Adapter for mapping every event of this source
+    fn map_events<E, F: FnMut(Self::Event) -> E>(self, f: F) -> MapEvents<Self, F> {
+        MapEvents::new(self, f)
+    }
+
+    /// Adapter for mapping every state of this source
+    fn map_state<S, F: FnMut(Self::State) -> S>(self, f: F) -> MapState<Self, F> {
+        MapState::new(self, f)
+    }
+
+    /// Adapter for mapping the events of this source, discarding the ones mapped to `None`
+    fn filter_map_events<E, F: FnMut(Self::Event) -> Option<E>>(
+        self,
+        f: F,
+    ) -> FilterMapEvents<Self, F> {
+        FilterMapEvents::new(self, f)
+    }
+
+    /// Adapter for mapping the custom errors of this source
+    fn map_err<E, F: FnMut(Self::Error) -> E>(self, f: F) -> MapErr<Self, F> {
+        MapErr::new(self, f)
+    }
+
+    /// Adapter for offloading work to a future
+    fn offload(self) -> (OffloadSource<Self>, OffloadFuture<Self>) {
+        offload(self)
+    }
+
+    /// Adapter for calling a limited-channel source on any number of channels
+    fn multiplex(self) -> Multiplex<Self> {
+        Multiplex::new(self)
+    }
+
+    /// Adapter for polling through a shared reference, one caller at a time
+    fn concurrent(self) -> MutexSource<Self> {
+        MutexSource::new(self)
+    }
 
     fn interrupt_stream<Fut: Future<Output = ()>>(
         self,
@@ -63,4 +163,12 @@ pub trait SourceExt: Source + Sized {
     {
         InterruptStream::new(self, wait_fn)
     }
+
+    /// Adapter for iterating over the interrupts of a source as they happen, blocking the current thread in between.
+    fn interrupt_iterator(self) -> InterruptIterator<Self>
+    where
+        Self: Source<Time = Instant>,
+    {
+        InterruptIterator::new(self)
+    }
 }
@@ -0,0 +1,19 @@
+use core::ops::Bound;
+
+use super::Source;
+
+/// A source driven by a transposer, which can report the state hash of each of its steps.
+///
+/// This is used by [`StateHashes`](crate::adapters::StateHashes) to detect desyncs.
+pub trait StateHashSource: Source {
+    /// hash the state of every step from now on, when it is first saturated.
+    fn enable_state_hashing(&mut self);
+
+    /// the sequence number, time and state hash of every step in `range` which has been saturated, in order.
+    ///
+    /// sequence numbers count up from the initial step, and are reused by the steps which replace rolled back ones.
+    fn state_hashes(
+        &self,
+        range: (Bound<Self::Time>, Bound<Self::Time>),
+    ) -> Vec<(usize, Self::Time, u64)>;
+}
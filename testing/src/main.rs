@@ -26,6 +26,8 @@ impl CollatzTransposer {
 impl Transposer for CollatzTransposer {
     type Time = Instant;
 
+    type Period = Duration;
+
     type OutputState = ();
 
     type Scheduled = ();
@@ -35,14 +37,15 @@ impl Transposer for CollatzTransposer {
     // set up with macro
     type InputStateManager = NoInputManager;
 
-    async fn init(&mut self, cx: &mut dyn InitContext<'_, Self>) {
-        cx.schedule_event(cx.current_time(), ()).unwrap();
+    async fn init(&mut self, cx: &mut impl InitContext<Self>) {
+        cx.schedule_repeating(cx.current_time(), Duration::from_millis(100), ())
+            .unwrap();
     }
 
     async fn handle_scheduled(
         &mut self,
         _payload: Self::Scheduled,
-        cx: &mut dyn HandleScheduleContext<'_, Self>,
+        cx: &mut impl HandleScheduleContext<Self>,
     ) {
         cx.emit_event(self.value).await;
 
@@ -51,12 +54,9 @@ impl Transposer for CollatzTransposer {
         } else {
             self.value = self.value * 3 + 1;
         }
-
-        cx.schedule_event(cx.current_time() + Duration::from_millis(100), ())
-            .unwrap();
     }
 
-    async fn interpolate(&self, _cx: &mut dyn InterpolateContext<'_, Self>) -> Self::OutputState {}
+    async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {}
 }
 
 #[tokio::main]
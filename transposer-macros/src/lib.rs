@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, parse_macro_input, DeriveInput, Ident, LitInt, Token, Type};
+
+/// Declare the inputs of a transposer.
+///
+/// This goes on the transposer struct, and generates:
+///
+/// - a unit struct for each input, with the same visibility as the transposer.
+/// - the [`TransposerInput`] impl for each input, with the transposer as its `Base`.
+/// - a trait named by `retriever`, which has `StateRetriever<I>` as a supertrait for every input,
+///   and is implemented for everything that implements all of them. this is meant to be used as
+///   the `InputStateManager` of the transposer.
+///
+/// The `SORT` of each input is its position in the list, unless it is given explicitly.
+/// `SORT` values must be unique for a transposer, so duplicates are a compile error.
+///
+/// ```
+/// # use transposer::context::InterpolateContext;
+/// # use transposer::{Transposer, TransposerInputEventHandler};
+/// #[transposer::transposer_inputs(
+///     retriever = GameStateRetriever,
+///     input(Keyboard, event = char, state = ()),
+///     input(Clock, event = (), state = u64, sort = 10),
+/// )]
+/// #[derive(Clone)]
+/// struct Game;
+///
+/// impl Transposer for Game {
+///     type Time = u64;
+///     type OutputEvent = ();
+///     type OutputState = ();
+///     type Scheduled = ();
+///     type InputStateManager = dyn GameStateRetriever;
+///
+///     async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) {}
+/// }
+///
+/// impl TransposerInputEventHandler<Keyboard> for Game {}
+/// impl TransposerInputEventHandler<Clock> for Game {}
+/// ```
+///
+/// ```compile_fail
+/// # use transposer::context::InterpolateContext;
+/// # use transposer::{Transposer, TransposerInputEventHandler};
+/// #[transposer::transposer_inputs(
+///     retriever = GameStateRetriever,
+///     input(Keyboard, event = char, state = ()),
+///     input(Clock, event = (), state = u64, sort = 0),
+/// )]
+/// #[derive(Clone)]
+/// struct Game;
+/// #
+/// # impl Transposer for Game {
+/// #     type Time = u64;
+/// #     type OutputEvent = ();
+/// #     type OutputState = ();
+/// #     type Scheduled = ();
+/// #     type InputStateManager = dyn GameStateRetriever;
+/// #
+/// #     async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) {}
+/// # }
+/// #
+/// # impl TransposerInputEventHandler<Keyboard> for Game {}
+/// # impl TransposerInputEventHandler<Clock> for Game {}
+/// ```
+///
+/// [`TransposerInput`]: ../transposer/trait.TransposerInput.html
+#[proc_macro_attribute]
+pub fn transposer_inputs(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr with Punctuated::<Arg, Token![,]>::parse_terminated);
+    let base = parse_macro_input!(item as DeriveInput);
+
+    match expand(args, &base) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => {
+            let err = err.to_compile_error();
+            quote!(#base #err).into()
+        },
+    }
+}
+
+fn expand(
+    args: Punctuated<Arg, Token![,]>,
+    base: &DeriveInput,
+) -> syn::Result<proc_macro2::TokenStream> {
+    if !base.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &base.generics,
+            "transposer_inputs does not support generic transposers",
+        ))
+    }
+
+    let mut retriever = None;
+    let mut inputs = Vec::new();
+
+    for arg in args {
+        match arg {
+            Arg::Retriever(ident) => {
+                if retriever.is_some() {
+                    return Err(syn::Error::new(ident.span(), "duplicate retriever"))
+                }
+                retriever = Some(ident);
+            },
+            Arg::Input(input) => inputs.push(*input),
+        }
+    }
+
+    let retriever = retriever
+        .ok_or_else(|| syn::Error::new(Span::call_site(), "missing `retriever = <trait name>`"))?;
+
+    if inputs.is_empty() {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "at least one `input(...)` is required",
+        ))
+    }
+
+    let sorts = assign_sorts(&inputs)?;
+
+    let vis = &base.vis;
+    let base_ident = &base.ident;
+
+    let input_items = inputs.iter().zip(sorts).map(|(input, sort)| {
+        let InputArg {
+            name,
+            event,
+            state,
+            ..
+        } = input;
+
+        quote! {
+            #vis struct #name;
+
+            impl ::transposer::TransposerInput for #name {
+                type Base = #base_ident;
+                type InputEvent = #event;
+                type InputState = #state;
+
+                const SORT: u64 = #sort;
+            }
+        }
+    });
+
+    let names: Vec<_> = inputs.iter().map(|input| &input.name).collect();
+
+    Ok(quote! {
+        #base
+
+        #(#input_items)*
+
+        #vis trait #retriever: #(::transposer::StateRetriever<#names>)+* {}
+
+        impl<T: ?Sized> #retriever for T where T: #(::transposer::StateRetriever<#names>)+* {}
+    })
+}
+
+// the SORT of each input, rejecting duplicate names and SORTs.
+fn assign_sorts(inputs: &[InputArg]) -> syn::Result<Vec<u64>> {
+    let mut names: HashMap<String, &Ident> = HashMap::new();
+    let mut sorts: HashMap<u64, &Ident> = HashMap::new();
+    let mut errors: Option<syn::Error> = None;
+    let mut push_error = |err: syn::Error| match &mut errors {
+        Some(errors) => errors.combine(err),
+        None => errors = Some(err),
+    };
+
+    let mut result = Vec::with_capacity(inputs.len());
+
+    for (i, input) in inputs.iter().enumerate() {
+        if let Some(other) = names.insert(input.name.to_string(), &input.name) {
+            push_error(syn::Error::new(
+                input.name.span(),
+                format!("input `{}` is declared more than once", other),
+            ));
+        }
+
+        let (sort, span) = match &input.sort {
+            Some(lit) => (lit.base10_parse::<u64>()?, lit.span()),
+            None => (i as u64, input.name.span()),
+        };
+
+        if let Some(other) = sorts.insert(sort, &input.name) {
+            push_error(syn::Error::new(
+                span,
+                format!(
+                    "SORT {} of `{}` is already used by `{}`. SORT must be unique for each input",
+                    sort, input.name, other
+                ),
+            ));
+        }
+
+        result.push(sort);
+    }
+
+    match errors {
+        Some(errors) => Err(errors),
+        None => Ok(result),
+    }
+}
+
+enum Arg {
+    Retriever(Ident),
+    Input(Box<InputArg>),
+}
+
+struct InputArg {
+    name:  Ident,
+    event: Type,
+    state: Type,
+    sort:  Option<LitInt>,
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: Ident = input.parse()?;
+
+        if key == "retriever" {
+            input.parse::<Token![=]>()?;
+            return Ok(Arg::Retriever(input.parse()?))
+        }
+
+        if key == "input" {
+            let content;
+            parenthesized!(content in input);
+            return Ok(Arg::Input(Box::new(content.parse()?)))
+        }
+
+        Err(syn::Error::new(
+            key.span(),
+            "expected `retriever = <trait name>` or `input(...)`",
+        ))
+    }
+}
+
+impl Parse for InputArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        let mut event = None;
+        let mut state = None;
+        let mut sort = None;
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break
+            }
+
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+
+            if key == "event" && event.is_none() {
+                event = Some(input.parse()?);
+            } else if key == "state" && state.is_none() {
+                state = Some(input.parse()?);
+            } else if key == "sort" && sort.is_none() {
+                sort = Some(input.parse()?);
+            } else {
+                return Err(syn::Error::new(
+                    key.span(),
+                    "expected each of `event`, `state`, and optionally `sort` once",
+                ))
+            }
+        }
+
+        let missing = |field| {
+            syn::Error::new(
+                name.span(),
+                format!("input `{}` is missing `{} = <type>`", name, field),
+            )
+        };
+
+        Ok(InputArg {
+            event: event.ok_or_else(|| missing("event"))?,
+            state: state.ok_or_else(|| missing("state"))?,
+            name,
+            sort,
+        })
+    }
+}
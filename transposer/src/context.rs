@@ -1,46 +1,49 @@
-use core::future::Future;
-use core::pin::Pin;
-use std::ptr::NonNull;
+use core::future::{poll_fn, Future};
+use core::hash::{Hash, Hasher};
+use core::ops::Bound;
 
 use rand::RngCore;
 
 use super::expire_handle::ExpireHandle;
 use super::Transposer;
+use crate::step::StateHasher;
 use crate::{StateRetriever, TransposerInput};
 
-pub trait InitContext<'a, T: Transposer>:
+pub trait InitContext<T: Transposer>:
     CurrentTimeContext<T>
-    + InputStateContext<'a, T>
+    + InputStateContext<T>
     + ScheduleEventContext<T>
     + EmitEventContext<T>
     + RngContext
 {
 }
 
-pub trait HandleInputContext<'a, T: Transposer>:
+pub trait HandleInputContext<T: Transposer>:
     CurrentTimeContext<T>
     + LastUpdatedTimeContext<T>
-    + InputStateContext<'a, T>
+    + InputStateContext<T>
     + ScheduleEventContext<T>
     + ExpireEventContext<T>
+    + ScheduleQueryContext<T>
     + EmitEventContext<T>
     + RngContext
 {
 }
 
-pub trait HandleScheduleContext<'a, T: Transposer>:
+pub trait HandleScheduleContext<T: Transposer>:
     CurrentTimeContext<T>
     + LastUpdatedTimeContext<T>
-    + InputStateContext<'a, T>
+    + InputStateContext<T>
     + ScheduleEventContext<T>
     + ExpireEventContext<T>
+    + ScheduleQueryContext<T>
     + EmitEventContext<T>
     + RngContext
 {
 }
 
-pub trait InterpolateContext<'a, T: Transposer>:
-    CurrentTimeContext<T> + LastUpdatedTimeContext<T> + InputStateContext<'a, T>
+pub trait InterpolateContext<T: Transposer>:
+    CurrentTimeContext<T> + LastUpdatedTimeContext<T> + InputStateContext<T> + ScheduleQueryContext<T>
 {
 }
 
@@ -52,29 +55,29 @@ pub trait LastUpdatedTimeContext<T: Transposer> {
     fn last_updated_time(&self) -> T::Time;
 }
 
-pub trait InputStateContext<'a, T: Transposer> {
+pub trait InputStateContext<T: Transposer> {
     #[doc(hidden)]
-    fn get_input_state_manager(&mut self) -> &'a T::InputStateManager;
+    fn get_input_state_manager(&self) -> &T::InputStateManager;
 }
 
-pub trait InputStateContextExt<'a, T: Transposer>: InputStateContext<'a, T> {
-    async fn get_input_state<I: TransposerInput<Base = T>>(&mut self) -> &'a I::InputState
+pub trait InputStateContextExt<T: Transposer>: InputStateContext<T> {
+    fn get_input_state<'a, I: TransposerInput<Base = T>>(
+        &'a self,
+    ) -> impl 'a + Future<Output = &'a I::InputState>
     where
         T::InputStateManager: 'a + StateRetriever<I>;
 }
 
-impl<'a, T: Transposer, C: InputStateContext<'a, T> + ?Sized> InputStateContextExt<'a, T> for C {
-    async fn get_input_state<I: TransposerInput<Base = T>>(&mut self) -> &'a I::InputState
+impl<T: Transposer, C: InputStateContext<T> + ?Sized> InputStateContextExt<T> for C {
+    fn get_input_state<'a, I: TransposerInput<Base = T>>(
+        &'a self,
+    ) -> impl 'a + Future<Output = &'a I::InputState>
     where
         T::InputStateManager: 'a + StateRetriever<I>,
     {
-        let ptr: NonNull<_> = self
-            .get_input_state_manager()
-            .get_input_state()
-            .await
-            .unwrap();
-
-        unsafe { ptr.as_ref() }
+        // only the manager is held while waiting, so this is Send whenever the manager is Sync.
+        let manager = self.get_input_state_manager();
+        poll_fn(move |cx| manager.poll_input_state(cx))
     }
 }
 
@@ -90,12 +93,24 @@ pub trait ScheduleEventContext<T: Transposer> {
         time: T::Time,
         payload: T::Scheduled,
     ) -> Result<ExpireHandle, ScheduleEventError>;
+
+    /// schedule an event at `start`, and again every `period` after that until it is expired.
+    ///
+    /// the handle stays valid for every repetition, and only the next repetition is kept in the schedule.
+    /// it stops repeating once the next repetition can't be represented by `T::Time`.
+    fn schedule_repeating(
+        &mut self,
+        start: T::Time,
+        period: T::Period,
+        payload: T::Scheduled,
+    ) -> Result<ExpireHandle, ScheduleEventError>;
 }
 
 #[non_exhaustive]
 #[derive(Debug)]
 pub enum ScheduleEventError {
     NewEventBeforeCurrent,
+    NonPositivePeriod,
 }
 
 pub trait ExpireEventContext<T: Transposer> {
@@ -103,6 +118,25 @@ pub trait ExpireEventContext<T: Transposer> {
         &mut self,
         handle: ExpireHandle,
     ) -> Result<(T::Time, T::Scheduled), ExpireEventError>;
+
+    /// move the event to `time`, keeping its handle and payload.
+    ///
+    /// the event is ordered as if it was scheduled now. returns the time it was previously scheduled for.
+    fn reschedule_event(
+        &mut self,
+        handle: ExpireHandle,
+        time: T::Time,
+    ) -> Result<T::Time, RescheduleEventError>;
+
+    /// swap the payload of the event, keeping its handle and time. returns the previous payload.
+    fn replace_event_payload(
+        &mut self,
+        handle: ExpireHandle,
+        payload: T::Scheduled,
+    ) -> Result<T::Scheduled, ExpireEventError>;
+
+    /// the time and payload of the event, if it is still scheduled.
+    fn get_expireable_event(&self, handle: ExpireHandle) -> Option<(T::Time, &T::Scheduled)>;
 }
 
 #[non_exhaustive]
@@ -111,9 +145,64 @@ pub enum ExpireEventError {
     InvalidOrUsedHandle,
 }
 
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum RescheduleEventError {
+    InvalidOrUsedHandle,
+    NewEventBeforeCurrent,
+}
+
+/// Read only access to the events the transposer has scheduled, which have not been handled yet.
+pub trait ScheduleQueryContext<T: Transposer> {
+    fn next_scheduled_time(&self) -> Option<T::Time>;
+
+    /// the pending events with times in `range`, in the order they will be handled.
+    fn scheduled_events(
+        &self,
+        range: (Bound<T::Time>, Bound<T::Time>),
+    ) -> Box<dyn '_ + Iterator<Item = (T::Time, &'_ T::Scheduled)>>;
+
+    fn scheduled_event_count(&self) -> usize;
+}
+
 pub trait EmitEventContext<T: Transposer> {
     #[must_use]
-    fn emit_event(&mut self, payload: T::OutputEvent) -> Pin<Box<dyn '_ + Future<Output = ()>>>;
+    fn emit_event(&mut self, payload: T::OutputEvent) -> impl '_ + Future<Output = ()>;
+}
+
+/// How the rng handed out by [`RngContext::get_rng`] is produced, set with [`Transposer::RNG_MODE`].
+#[derive(Clone, Copy, Debug, Default)]
+pub enum RngMode<Time> {
+    /// One stream, seeded once and shared by every event in order.
+    ///
+    /// Changing how many random numbers one handler uses changes the values seen by every later handler.
+    #[default]
+    SingleStream,
+
+    /// A fresh stream for each event handled, derived from the seed and which event it is.
+    ///
+    /// The values seen by a handler don't depend on how many random numbers were used before it,
+    /// or on which other events were handled before it.
+    /// Each repetition of a repeating event gets its own stream,
+    /// as does each input event at the same time.
+    ///
+    /// The function turns the time of an input event into part of its identity, so it must be deterministic.
+    /// [`per_event`](RngMode::per_event) makes one from the [`Hash`] of the time.
+    PerEvent(fn(&Time) -> u64),
+}
+
+impl<Time> RngMode<Time> {
+    /// [`PerEvent`](RngMode::PerEvent), telling times apart by their [`Hash`], with a fixed hasher.
+    pub const fn per_event() -> Self
+    where
+        Time: Hash,
+    {
+        RngMode::PerEvent(|time| {
+            let mut state = StateHasher::default();
+            time.hash(&mut state);
+            state.finish()
+        })
+    }
 }
 
 pub trait RngContext {
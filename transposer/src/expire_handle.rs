@@ -1,5 +1,6 @@
 /// this is the handle that you use to expire scheduled events.
-#[derive(Hash, Eq, PartialEq, Clone, Copy)]
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExpireHandle(u64);
 
 impl ExpireHandle {
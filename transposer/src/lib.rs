@@ -1,14 +1,31 @@
 #![allow(incomplete_features)]
 #![feature(async_fn_in_trait)]
+#![feature(unsize)]
+#![feature(associated_type_defaults)]
+#![feature(type_alias_impl_trait)]
 #![deny(unsafe_op_in_unsafe_fn)]
 
-use std::ptr::NonNull;
+use std::hash::{Hash, Hasher};
+use std::task::{Context, Poll};
 
-use context::{HandleInputContext, HandleScheduleContext, InitContext, InterpolateContext};
+use context::{
+    HandleInputContext,
+    HandleScheduleContext,
+    InitContext,
+    InterpolateContext,
+    RngMode,
+};
+use period::{NoPeriod, RepeatPeriod};
+pub use transposer_macros::transposer_inputs;
+
+// lets the code generated by transposer_inputs refer to this crate as `::transposer`.
+extern crate self as transposer;
 
 pub mod context;
 // pub mod evaluate_to;
 pub mod expire_handle;
+pub mod multi_input_state;
+pub mod period;
 pub mod schedule_storage;
 pub mod single_input_state;
 pub mod step;
@@ -24,12 +41,26 @@ mod test;
 ///
 /// The name comes from the idea that we are converting a stream of events into another stream of events,
 /// perhaps in the way a stream of music notes can be *transposed* into another stream of music notes.
+///
+/// # Threads
+///
+/// [`Step`](step::Step) and [`Interpolation`](step::Interpolation) hold the futures of the async functions here,
+/// so they are `Send` when those futures are, along with the transposer, its associated types and the storage family.
+/// This is checked by the compiler, so a transposer which holds something that isn't `Send` across an `.await`
+/// just makes its steps not `Send`. The storage family also decides whether steps with input events can be `Send`,
+/// see [`StorageFamily::Erasure`](schedule_storage::StorageFamily::Erasure).
 pub trait Transposer: Clone {
     /// The type used as the 'time' for events. This must be Ord and Copy because it is frequently used for comparisons,
     /// and it must be [`Default`] because the default value is used for the timestamp of events emitted.
     /// by the init function.
     type Time: Copy + Ord + Unpin;
 
+    /// The type of the interval between repetitions of a repeating scheduled event.
+    ///
+    /// This only needs to be set to schedule repeating events, for example to `Self::Time` for integer times,
+    /// or to `Duration` for `Instant`.
+    type Period: RepeatPeriod<Self::Time> = NoPeriod;
+
     /// The type of the output payloads.
     ///
     /// The output events are of type `Event<Self::Time, RollbackPayload<Self::Output>>`
@@ -51,6 +82,11 @@ pub trait Transposer: Clone {
     /// The type used to request input state. This is only passed as a shared reference.
     type InputStateManager: ?Sized;
 
+    /// How the rng from [`get_rng`](context::RngContext::get_rng) is produced.
+    ///
+    /// Use [`RngMode::per_event`] when replays need to survive changes to how many random numbers a handler uses.
+    const RNG_MODE: RngMode<Self::Time> = RngMode::SingleStream;
+
     /// The function to initialize your transposer's events.
     ///
     /// You should initialize your transposer like any other struct.
@@ -59,7 +95,7 @@ pub trait Transposer: Clone {
     ///
     /// `cx` is a context object for performing additional operations.
     /// For more information on `cx` see the [`InitContext`] documentation.
-    async fn init(&mut self, _cx: &mut dyn InitContext<'_, Self>) {}
+    async fn init(&mut self, _cx: &mut impl InitContext<Self>) {}
 
     /// The function to respond to internally scheduled events.
     ///
@@ -70,7 +106,7 @@ pub trait Transposer: Clone {
     async fn handle_scheduled(
         &mut self,
         _payload: Self::Scheduled,
-        _cx: &mut dyn HandleScheduleContext<'_, Self>,
+        _cx: &mut impl HandleScheduleContext<Self>,
     ) {
     }
 
@@ -82,7 +118,17 @@ pub trait Transposer: Clone {
     /// `base_time` is the time of the `self` parameter
     /// `interpolated_time` is the time being requested `self`
     /// `cx is a context object for performing additional operations like requesting state.
-    async fn interpolate(&self, cx: &mut dyn InterpolateContext<'_, Self>) -> Self::OutputState;
+    async fn interpolate(&self, cx: &mut impl InterpolateContext<Self>) -> Self::OutputState;
+
+    /// An approximate number of bytes of heap memory held by this transposer, not counting `size_of::<Self>()`.
+    ///
+    /// This is used to decide how many saturated steps can be kept within a memory budget.
+    /// Memory shared with other clones can be counted in full, because any clone could be the last one holding it.
+    ///
+    /// The default reports no heap memory at all.
+    fn heap_size(&self) -> usize {
+        0
+    }
 }
 
 pub trait TransposerInput: 'static + Sized {
@@ -93,6 +139,8 @@ pub trait TransposerInput: 'static + Sized {
     /// This MUST be unique for each input that shares a base.
     ///
     /// in particular, two inputs with the same Base and SORT, and different InputEvents can result in UB.
+    ///
+    /// [`transposer_inputs`] assigns these, and rejects duplicates at compile time.
     const SORT: u64;
 }
 
@@ -109,7 +157,7 @@ pub trait TransposerInputEventHandler<I: TransposerInput<Base = Self>>: Transpos
     async fn handle_input(
         &mut self,
         _events: &I::InputEvent,
-        _cx: &mut dyn HandleInputContext<'_, Self>,
+        _cx: &mut impl HandleInputContext<Self>,
     ) {
     }
 
@@ -130,10 +178,26 @@ pub trait TransposerInputEventHandler<I: TransposerInput<Base = Self>>: Transpos
     }
 }
 
-/// # Safety
+/// Hashing of a transposer's state, used to detect when two runs of the same transposer have diverged.
 ///
-/// the `NonNull<I::InputState>` returned by the reciever should be considered a `&'_ I::InputState`
-/// (it would be &'_ but it's not really possible to implement that)
-pub unsafe trait StateRetriever<I: TransposerInput> {
-    fn get_input_state(&self) -> futures_channel::oneshot::Receiver<NonNull<I::InputState>>;
+/// This is implemented for every transposer which implements [`Hash`],
+/// and can be implemented by hand for transposers which can't, or which have fields which shouldn't be hashed.
+///
+/// The hash must only depend on state which could affect future outputs, and must be the same on every machine.
+pub trait TransposerHash {
+    fn hash_state<H: Hasher>(&self, state: &mut H);
+}
+
+impl<T: Hash> TransposerHash for T {
+    fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.hash(state)
+    }
+}
+
+/// Access to the state of the input `I`, which the [`InputStateManager`](Transposer::InputStateManager)
+/// of a transposer with the input `I` is made of.
+pub trait StateRetriever<I: TransposerInput> {
+    /// the state of `I`, or [`Poll::Pending`] if it hasn't been provided yet,
+    /// in which case the waker is woken once it has been.
+    fn poll_input_state(&self, cx: &mut Context<'_>) -> Poll<&I::InputState>;
 }
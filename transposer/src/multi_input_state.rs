@@ -0,0 +1,192 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::marker::{PhantomData, Unsize};
+use std::task::{Context, Poll};
+
+use parking_lot::RwLock;
+
+use crate::single_input_state::SingleInputState;
+use crate::step::{FulfillInputState, InputState};
+use crate::{StateRetriever, Transposer, TransposerInput};
+
+/// This is ONE VALID IMPLEMENTATION of input state for a transposer with any number of inputs.
+///
+/// Each input gets its own lazily created slot, which works just like a [`SingleInputState`].
+/// This can be used as the input state of any transposer whose `InputStateManager` is a trait object
+/// made of `StateRetriever`s for its inputs, like the ones generated by [`transposer_inputs`](crate::transposer_inputs).
+///
+/// The states of the inputs must be `Send + Sync`, so this is always `Send + Sync`,
+/// and the manager can be something like `dyn GameStateRetriever + Send + Sync`.
+pub struct MultiInputState<T: Transposer> {
+    // these slots are all SingleInputStates of different inputs. they are downcast before use.
+    slots:   RwLock<BTreeMap<u64, InputSlot>>,
+    phantom: PhantomData<fn() -> T>,
+}
+
+type AnyState = dyn Any + Send + Sync;
+
+struct InputSlot {
+    // a Box<SingleInputState<I>>
+    state:        Box<AnyState>,
+    requested:    bool,
+    is_requested: fn(&AnyState) -> bool,
+}
+
+impl InputSlot {
+    fn new<I: TransposerInput>() -> Self
+    where
+        I::InputState: Send + Sync,
+    {
+        Self {
+            state:        Box::new(SingleInputState::<I>::default()),
+            requested:    false,
+            is_requested: |state| Self::downcast::<I>(state).is_requested(),
+        }
+    }
+
+    fn downcast<I: TransposerInput>(state: &AnyState) -> &SingleInputState<I> {
+        state.downcast_ref().unwrap()
+    }
+
+    /// # Safety
+    ///
+    /// the returned reference must not outlive the slot.
+    unsafe fn get<'a, I: TransposerInput>(&self) -> &'a SingleInputState<I> {
+        let state: *const SingleInputState<I> = Self::downcast(&*self.state);
+
+        // SAFETY: the state is boxed, so it stays put for as long as the slot is alive.
+        unsafe { &*state }
+    }
+}
+
+impl<T: Transposer> MultiInputState<T> {
+    // SAFETY (for all the slot references handed out here): slots are never removed or replaced,
+    // and they are boxed, so they live as long as self does.
+    fn get_slot<I>(&self, requested: bool) -> &SingleInputState<I>
+    where
+        I: TransposerInput<Base = T>,
+        I::InputState: Send + Sync,
+    {
+        {
+            let slots = self.slots.read();
+            if let Some(slot) = slots.get(&I::SORT) {
+                if slot.requested || !requested {
+                    return unsafe { slot.get() }
+                }
+            }
+        }
+
+        let mut slots = self.slots.write();
+        let slot = slots.entry(I::SORT).or_insert_with(InputSlot::new::<I>);
+        slot.requested |= requested;
+        unsafe { slot.get() }
+    }
+
+    fn try_get_slot<I: TransposerInput<Base = T>>(&self) -> Option<&SingleInputState<I>> {
+        let slots = self.slots.read();
+        slots.get(&I::SORT).map(|slot| unsafe { slot.get() })
+    }
+
+    /// the `SORT` of each input whose state the transposer has asked for, whether or not it has been provided yet.
+    pub fn requested_inputs(&self) -> Vec<u64> {
+        let slots = self.slots.read();
+        slots
+            .iter()
+            .filter_map(|(sort, slot)| slot.requested.then_some(*sort))
+            .collect()
+    }
+
+    /// the `SORT` of each input the transposer is currently waiting on.
+    pub fn pending_inputs(&self) -> Vec<u64> {
+        let slots = self.slots.read();
+        slots
+            .iter()
+            .filter_map(|(sort, slot)| (slot.is_requested)(&*slot.state).then_some(*sort))
+            .collect()
+    }
+}
+
+impl<T, I> StateRetriever<I> for MultiInputState<T>
+where
+    T: Transposer,
+    I: TransposerInput<Base = T>,
+    I::InputState: Send + Sync,
+{
+    fn poll_input_state(&self, cx: &mut Context<'_>) -> Poll<&I::InputState> {
+        self.get_slot::<I>(true).poll_input_state(cx)
+    }
+}
+
+impl<T: Transposer> InputState<T> for MultiInputState<T>
+where
+    Self: Unsize<T::InputStateManager>,
+{
+    fn new() -> Self {
+        Self {
+            slots:   RwLock::new(BTreeMap::new()),
+            phantom: PhantomData,
+        }
+    }
+
+    fn get_provider(&self) -> &T::InputStateManager {
+        self
+    }
+}
+
+impl<T, I> FulfillInputState<I> for MultiInputState<T>
+where
+    T: Transposer,
+    I: TransposerInput<Base = T>,
+    I::InputState: Send + Sync,
+{
+    fn is_requested(&self) -> bool {
+        self.try_get_slot::<I>()
+            .is_some_and(SingleInputState::is_requested)
+    }
+
+    fn set_state(&self, state: I::InputState) -> Result<(), I::InputState> {
+        self.get_slot::<I>(false).set_state(state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use util::dummy_waker::DummyWaker;
+
+    use super::*;
+    use crate::test::test_transposer::{
+        TestTransposer,
+        TestTransposerInput1,
+        TestTransposerInput2,
+    };
+
+    #[test]
+    fn requested_inputs_test() {
+        let state = MultiInputState::<TestTransposer>::new();
+        let waker = DummyWaker::dummy();
+        let mut cx = Context::from_waker(&waker);
+
+        let poll = StateRetriever::<TestTransposerInput1>::poll_input_state(&state, &mut cx);
+        assert_eq!(poll, Poll::Pending);
+        assert_eq!(state.requested_inputs(), vec![1]);
+        assert_eq!(state.pending_inputs(), vec![1]);
+
+        // providing state nobody asked for doesn't count as a request.
+        FulfillInputState::<TestTransposerInput2>::set_state(&state, 20).unwrap();
+        FulfillInputState::<TestTransposerInput1>::set_state(&state, 10).unwrap();
+        assert_eq!(state.requested_inputs(), vec![1]);
+        assert_eq!(state.pending_inputs(), Vec::<u64>::new());
+
+        let poll = StateRetriever::<TestTransposerInput1>::poll_input_state(&state, &mut cx);
+        assert_eq!(poll, Poll::Ready(&10));
+
+        // full slots stay full.
+        assert_eq!(
+            FulfillInputState::<TestTransposerInput1>::set_state(&state, 11),
+            Err(11)
+        );
+        let poll = StateRetriever::<TestTransposerInput2>::poll_input_state(&state, &mut cx);
+        assert_eq!(poll, Poll::Ready(&20));
+        assert_eq!(state.requested_inputs(), vec![1, 2]);
+    }
+}
@@ -0,0 +1,51 @@
+use std::time::{Duration, Instant, SystemTime};
+
+/// The interval between repetitions of a repeating scheduled event, for a [`Transposer`](crate::Transposer)
+/// with this `Time`.
+///
+/// This is implemented for the integer types with themselves as the time,
+/// and for [`Duration`] with [`Duration`], [`Instant`] and [`SystemTime`] as the time.
+pub trait RepeatPeriod<Time>: Copy {
+    /// the time `self` after `time`, or `None` if it can't be represented.
+    ///
+    /// a repeating event stops repeating when this returns `None`.
+    fn checked_add_to(self, time: Time) -> Option<Time>;
+}
+
+/// The default [`Period`](crate::Transposer::Period), for transposers which don't schedule repeating events.
+///
+/// This has no values, so [`schedule_repeating`](crate::context::ScheduleEventContext::schedule_repeating)
+/// can't be called.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NoPeriod {}
+
+impl<Time> RepeatPeriod<Time> for NoPeriod {
+    fn checked_add_to(self, _time: Time) -> Option<Time> {
+        match self {}
+    }
+}
+
+macro_rules! impl_repeat_period {
+    ($($t:ty),*) => {$(
+        impl RepeatPeriod<$t> for $t {
+            fn checked_add_to(self, time: $t) -> Option<$t> {
+                time.checked_add(self)
+            }
+        }
+    )*};
+}
+
+impl_repeat_period!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, Duration);
+
+impl RepeatPeriod<Instant> for Duration {
+    fn checked_add_to(self, time: Instant) -> Option<Instant> {
+        time.checked_add(self)
+    }
+}
+
+impl RepeatPeriod<SystemTime> for Duration {
+    fn checked_add_to(self, time: SystemTime) -> Option<SystemTime> {
+        time.checked_add(self)
+    }
+}
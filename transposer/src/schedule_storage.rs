@@ -1,6 +1,9 @@
+use core::any::Any;
 use core::borrow::Borrow;
+use core::future::Future;
 use core::hash::Hash;
-use core::ops::Deref;
+use core::ops::{Bound, Deref};
+use core::pin::Pin;
 use std::rc::Rc;
 use std::sync::Arc;
 
@@ -12,6 +15,57 @@ pub trait StorageFamily: Copy + 'static {
     type Transposer<W>: RefCounted<W>;
 
     type LazyState<W: ?Sized>: RefCounted<W> + Clone;
+
+    /// How steps hold their input events, and the futures handling them, without knowing their types.
+    ///
+    /// With [`SendErasure`], input events can only be added to steps when they are `Send + Sync`,
+    /// and the futures of their handlers are `Send`, so the steps can still be `Send`.
+    /// With [`LocalErasure`], steps with input events are never `Send`.
+    type Erasure: Erasure;
+}
+
+/// A way of type erasing values, and futures which produce nothing.
+///
+/// This is implemented by [`SendErasure`] and [`LocalErasure`].
+pub trait Erasure: 'static {
+    /// `dyn Any`, with whichever auto traits are kept.
+    type Any: ?Sized + 'static;
+    type Future<'a>: Future<Output = ()> + 'a;
+
+    fn as_any(value: &Self::Any) -> &dyn Any;
+    fn as_any_mut(value: &mut Self::Any) -> &mut dyn Any;
+}
+
+/// Type erasure which keeps `Send` and `Sync`, so it can only erase things which are `Send` and `Sync`.
+pub struct SendErasure;
+
+impl Erasure for SendErasure {
+    type Any = dyn Any + Send + Sync;
+    type Future<'a> = Pin<Box<dyn 'a + Future<Output = ()> + Send>>;
+
+    fn as_any(value: &Self::Any) -> &dyn Any {
+        value
+    }
+
+    fn as_any_mut(value: &mut Self::Any) -> &mut dyn Any {
+        value
+    }
+}
+
+/// Type erasure which can erase anything, but the results are never `Send` or `Sync`.
+pub struct LocalErasure;
+
+impl Erasure for LocalErasure {
+    type Any = dyn Any;
+    type Future<'a> = Pin<Box<dyn 'a + Future<Output = ()>>>;
+
+    fn as_any(value: &Self::Any) -> &dyn Any {
+        value
+    }
+
+    fn as_any_mut(value: &mut Self::Any) -> &mut dyn Any {
+        value
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -22,6 +76,22 @@ impl StorageFamily for DefaultStorage {
     type HashMap<K: Hash + Eq + Clone, V: Clone> = im::HashMap<K, V>;
     type Transposer<W> = Arc<W>;
     type LazyState<W: ?Sized> = Arc<W>;
+    type Erasure = SendErasure;
+}
+
+/// A storage family using the maps from [`std::collections`].
+///
+/// Cloning these maps copies every entry, so this is only faster than [`DefaultStorage`]
+/// when steps are rarely cloned, like when few checkpoints are kept and rollbacks are rare.
+#[derive(Clone, Copy)]
+pub struct StdStorage;
+
+impl StorageFamily for StdStorage {
+    type OrdMap<K: Ord + Eq + Clone, V: Clone> = std::collections::BTreeMap<K, V>;
+    type HashMap<K: Hash + Eq + Clone, V: Clone> = std::collections::HashMap<K, V>;
+    type Transposer<W> = Arc<W>;
+    type LazyState<W: ?Sized> = Arc<W>;
+    type Erasure = SendErasure;
 }
 
 pub trait OrdMapStorage<K: Ord + Eq + Clone, V: Clone>: Clone {
@@ -40,6 +110,23 @@ pub trait OrdMapStorage<K: Ord + Eq + Clone, V: Clone>: Clone {
 
     fn get_first(&self) -> Option<(&K, &V)>;
     fn pop_first(&mut self) -> Option<(K, V)>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
+
+    /// the start of the range must not be after its end.
+    fn range<'a>(&'a self, range: (Bound<K>, Bound<K>)) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
 }
 
 pub trait HashMapStorage<K: Hash + Eq + Clone, V: Clone>: Clone {
@@ -55,6 +142,17 @@ pub trait HashMapStorage<K: Hash + Eq + Clone, V: Clone>: Clone {
     where
         BK: Hash + Eq + ?Sized,
         K: Borrow<BK>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a;
 }
 
 pub trait RefCounted<T: ?Sized>: Clone + Deref<Target = T> + Unpin {
@@ -107,6 +205,26 @@ impl<K: Ord + Eq + Clone, V: Clone> OrdMapStorage<K, V> for im::OrdMap<K, V> {
 
         min
     }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+    }
+
+    fn range<'a>(&'a self, range: (Bound<K>, Bound<K>)) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.range(range)
+    }
 }
 
 impl<K: Hash + Eq + Clone, V: Clone> HashMapStorage<K, V> for im::HashMap<K, V> {
@@ -133,6 +251,18 @@ impl<K: Hash + Eq + Clone, V: Clone> HashMapStorage<K, V> for im::HashMap<K, V>
     {
         self.remove(k)
     }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+    }
 }
 
 impl<K: Ord + Eq + Clone, V: Clone> OrdMapStorage<K, V> for im_rc::OrdMap<K, V> {
@@ -170,6 +300,26 @@ impl<K: Ord + Eq + Clone, V: Clone> OrdMapStorage<K, V> for im_rc::OrdMap<K, V>
 
         min
     }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+    }
+
+    fn range<'a>(&'a self, range: (Bound<K>, Bound<K>)) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.range(range)
+    }
 }
 
 impl<K: Hash + Eq + Clone, V: Clone> HashMapStorage<K, V> for im_rc::HashMap<K, V> {
@@ -196,6 +346,18 @@ impl<K: Hash + Eq + Clone, V: Clone> HashMapStorage<K, V> for im_rc::HashMap<K,
     {
         self.remove(k)
     }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+    }
 }
 
 impl<K: Ord + Eq + Clone, V: Clone> OrdMapStorage<K, V> for std::collections::BTreeMap<K, V> {
@@ -230,6 +392,26 @@ impl<K: Ord + Eq + Clone, V: Clone> OrdMapStorage<K, V> for std::collections::BT
     fn pop_first(&mut self) -> Option<(K, V)> {
         self.pop_first()
     }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+    }
+
+    fn range<'a>(&'a self, range: (Bound<K>, Bound<K>)) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.range(range)
+    }
 }
 
 impl<K: Hash + Eq + Clone, V: Clone> HashMapStorage<K, V> for std::collections::HashMap<K, V> {
@@ -256,6 +438,18 @@ impl<K: Hash + Eq + Clone, V: Clone> HashMapStorage<K, V> for std::collections::
     {
         self.remove(k)
     }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        self.iter()
+    }
 }
 
 impl<T: ?Sized> RefCounted<T> for Arc<T> {
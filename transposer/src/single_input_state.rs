@@ -1,93 +1,95 @@
-use std::ptr::NonNull;
+use std::marker::Unsize;
+use std::sync::OnceLock;
+use std::task::{Context, Poll, Waker};
 
-use futures_channel::oneshot::{channel, Receiver, Sender};
-use parking_lot::RwLock;
+use parking_lot::Mutex;
 
-use crate::step::InputState;
+use crate::step::{FulfillInputState, InputState};
 use crate::{StateRetriever, Transposer, TransposerInput};
 
 /// This is the manager that transposers should use when they have a single input.
-pub type SingleInputStateManager<I> = dyn StateRetriever<I>;
+///
+/// It is `Send + Sync`, so steps of the transposer can be `Send`.
+/// Transposers whose input state isn't `Send + Sync` can use `dyn StateRetriever<I>` instead.
+pub type SingleInputStateManager<I> = dyn StateRetriever<I> + Send + Sync;
 
 /// This is ONE VALID IMPLEMENTATION that backs the SingleInputStateManager.
 /// Transposer can depend on SingleInputStateManager, but the specific choice of runtime
 /// for your transposer only needs to be able to proivide a reference to the InputStateManager.
 pub struct SingleInputState<I: TransposerInput> {
-    inner: RwLock<SingleInputStateInner<I>>,
-}
-enum SingleInputStateInner<I: TransposerInput> {
-    Empty,
-    Requested(Vec<Sender<NonNull<I::InputState>>>),
-    Full(Box<I::InputState>),
+    // this is never emptied once it is set, so references to it last as long as self.
+    state:  OnceLock<I::InputState>,
+    // the wakers of everyone waiting on the state, which are woken once it is set.
+    wakers: Mutex<Vec<Waker>>,
 }
 
-// SAFETY: the NonNull returned from get_input_state will always last until the enum is dropped
-// because we never go from Full to Empty or Requested.
-unsafe impl<I: TransposerInput> StateRetriever<I> for SingleInputState<I> {
-    fn get_input_state(&self) -> Receiver<NonNull<I::InputState>> {
-        let (send, recv) = channel();
-        let read = self.inner.read();
-
-        match &*read {
-            SingleInputStateInner::Empty => {
-                drop(read);
-                let mut write = self.inner.write();
-                *write = SingleInputStateInner::Requested(vec![send]);
-            },
-            SingleInputStateInner::Requested(_) => {
-                drop(read);
-                let mut write = self.inner.write();
-                if let SingleInputStateInner::Requested(vec) = &mut *write {
-                    vec.push(send)
-                } else {
-                    unreachable!()
-                }
-            },
-            SingleInputStateInner::Full(input_state) => {
-                let _ = send.send((&**input_state).into());
-            },
+impl<I: TransposerInput> StateRetriever<I> for SingleInputState<I> {
+    fn poll_input_state(&self, cx: &mut Context<'_>) -> Poll<&I::InputState> {
+        if let Some(state) = self.state.get() {
+            return Poll::Ready(state)
+        }
+
+        let mut wakers = self.wakers.lock();
+
+        // the state might have been set while we were waiting for the lock.
+        if let Some(state) = self.state.get() {
+            return Poll::Ready(state)
+        }
+
+        if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
         }
 
-        recv
+        Poll::Pending
     }
 }
 
-impl<
-        T: Transposer<InputStateManager = SingleInputStateManager<I>>,
-        I: TransposerInput<Base = T>,
-    > InputState<I::Base> for SingleInputState<I>
+impl<T: Transposer, I: TransposerInput<Base = T>> InputState<T> for SingleInputState<I>
+where
+    Self: Unsize<T::InputStateManager>,
 {
     fn new() -> Self {
-        Self {
-            inner: RwLock::new(SingleInputStateInner::Empty),
-        }
+        Self::default()
     }
 
-    fn get_provider(&self) -> &<I::Base as Transposer>::InputStateManager {
+    fn get_provider(&self) -> &T::InputStateManager {
         self
     }
 }
 
+impl<I: TransposerInput> Default for SingleInputState<I> {
+    fn default() -> Self {
+        Self {
+            state:  OnceLock::new(),
+            wakers: Mutex::new(Vec::new()),
+        }
+    }
+}
+
 impl<I: TransposerInput> SingleInputState<I> {
+    /// whether the transposer is currently waiting on this state.
+    pub fn is_requested(&self) -> bool {
+        !self.wakers.lock().is_empty()
+    }
+
     pub fn set_state(&self, state: I::InputState) -> Result<(), I::InputState> {
-        let mut inner = self.inner.write();
-        let senders = match core::mem::replace(&mut *inner, SingleInputStateInner::Empty) {
-            SingleInputStateInner::Empty => Vec::new(),
-            SingleInputStateInner::Requested(vec) => vec,
-            SingleInputStateInner::Full(s) => {
-                *inner = SingleInputStateInner::Full(s);
-                return Err(state)
-            },
-        };
-        let state = Box::new(state);
-
-        for send in senders.into_iter() {
-            let ptr: NonNull<_> = (&*state).into();
-            let _ = send.send(ptr);
-        }
+        let mut wakers = self.wakers.lock();
+        self.state.set(state)?;
 
-        *inner = SingleInputStateInner::Full(state);
+        for waker in wakers.drain(..) {
+            waker.wake();
+        }
 
         Ok(())
     }
 }
+
+impl<I: TransposerInput> FulfillInputState<I> for SingleInputState<I> {
+    fn is_requested(&self) -> bool {
+        SingleInputState::is_requested(self)
+    }
+
+    fn set_state(&self, state: I::InputState) -> Result<(), I::InputState> {
+        SingleInputState::set_state(self, state)
+    }
+}
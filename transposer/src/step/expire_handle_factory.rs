@@ -1,6 +1,7 @@
 use crate::expire_handle::ExpireHandle;
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExpireHandleFactory(u64);
 
 impl ExpireHandleFactory {
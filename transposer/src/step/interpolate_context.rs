@@ -1,3 +1,5 @@
+use core::ops::Bound;
+
 // use super::lazy_state::LazyState;
 use super::transposer_metadata::TransposerMetaData;
 use crate::context::{
@@ -5,8 +7,9 @@ use crate::context::{
     InputStateContext,
     InterpolateContext,
     LastUpdatedTimeContext,
+    ScheduleQueryContext,
 };
-use crate::schedule_storage::StorageFamily;
+use crate::schedule_storage::{OrdMapStorage, StorageFamily};
 use crate::Transposer;
 
 pub struct StepInterpolateContext<'update, T: Transposer, S: StorageFamily> {
@@ -29,15 +32,15 @@ impl<'update, T: Transposer, S: StorageFamily> StepInterpolateContext<'update, T
     }
 }
 
-impl<'update, T: Transposer, S: StorageFamily> InterpolateContext<'update, T>
+impl<'update, T: Transposer, S: StorageFamily> InterpolateContext<T>
     for StepInterpolateContext<'update, T, S>
 {
 }
 
-impl<'update, T: Transposer, S: StorageFamily> InputStateContext<'update, T>
+impl<'update, T: Transposer, S: StorageFamily> InputStateContext<T>
     for StepInterpolateContext<'update, T, S>
 {
-    fn get_input_state_manager(&mut self) -> &'update T::InputStateManager {
+    fn get_input_state_manager(&self) -> &T::InputStateManager {
         self.input_state
     }
 }
@@ -57,3 +60,24 @@ impl<'update, T: Transposer, S: StorageFamily> LastUpdatedTimeContext<T>
         self.metadata.last_updated.time
     }
 }
+
+impl<'update, T: Transposer, S: StorageFamily> ScheduleQueryContext<T>
+    for StepInterpolateContext<'update, T, S>
+{
+    fn next_scheduled_time(&self) -> Option<T::Time> {
+        self.metadata
+            .get_next_scheduled_time()
+            .map(|time| time.time)
+    }
+
+    fn scheduled_events(
+        &self,
+        range: (Bound<T::Time>, Bound<T::Time>),
+    ) -> Box<dyn '_ + Iterator<Item = (T::Time, &'_ T::Scheduled)>> {
+        Box::new(self.metadata.scheduled_events(range))
+    }
+
+    fn scheduled_event_count(&self) -> usize {
+        self.metadata.schedule.len()
+    }
+}
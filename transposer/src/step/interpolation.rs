@@ -9,11 +9,24 @@ use crate::schedule_storage::{RefCounted, StorageFamily};
 use crate::Transposer;
 
 pub struct Interpolation<T: Transposer, Is: InputState<T>, S: StorageFamily> {
-    future:      Pin<Box<dyn Future<Output = T::OutputState>>>,
+    future:      Pin<Box<InterpolationFuture<T, Is, S>>>,
     input_state: S::LazyState<Is>,
 }
 
-async fn create_fut<T: Transposer, S: StorageFamily, Is: InputState<T>>(
+// this is named instead of boxed as a trait object, so the interpolation is Send whenever the future is.
+type InterpolationFuture<T: Transposer, Is: InputState<T>, S: StorageFamily> =
+    impl Future<Output = T::OutputState>;
+
+#[define_opaque(InterpolationFuture)]
+fn create_fut<T: Transposer, Is: InputState<T>, S: StorageFamily>(
+    interpolation_time: T::Time,
+    wrapped_transposer: S::Transposer<WrappedTransposer<T, S>>,
+    input_state: S::LazyState<Is>,
+) -> InterpolationFuture<T, Is, S> {
+    interpolate::<T, S, Is>(interpolation_time, wrapped_transposer, input_state)
+}
+
+async fn interpolate<T: Transposer, S: StorageFamily, Is: InputState<T>>(
     interpolation_time: T::Time,
     wrapped_transposer: S::Transposer<WrappedTransposer<T, S>>,
     input_state: S::LazyState<Is>,
@@ -37,12 +50,11 @@ impl<T: Transposer, Is: InputState<T>, S: StorageFamily> Interpolation<T, Is, S>
     ) -> Self {
         let input_state = S::LazyState::new(Box::new(Is::new()));
 
-        let future =
-            create_fut::<T, S, Is>(interpolation_time, wrapped_transposer, input_state.clone());
-
-        let future: Pin<Box<dyn '_ + Future<Output = T::OutputState>>> = Box::pin(future);
-        let future: Pin<Box<dyn 'static + Future<Output = T::OutputState>>> =
-            unsafe { core::mem::transmute(future) };
+        let future = Box::pin(create_fut::<T, Is, S>(
+            interpolation_time,
+            wrapped_transposer,
+            input_state.clone(),
+        ));
 
         Self {
             future,
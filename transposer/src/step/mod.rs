@@ -1,6 +1,9 @@
 mod expire_handle_factory;
 mod interpolate_context;
 mod interpolation;
+#[cfg(feature = "serde")]
+mod snapshot;
+mod state_hasher;
 mod step_inputs;
 mod sub_step_update_context;
 mod time;
@@ -11,6 +14,7 @@ mod wrapped_transposer;
 mod test;
 
 use core::future::Future;
+use core::hash::Hash;
 use core::pin::Pin;
 use core::task::{Context, Waker};
 use std::sync::Arc;
@@ -18,12 +22,15 @@ use std::sync::Arc;
 use futures_channel::{mpsc, oneshot};
 use futures_util::{FutureExt, StreamExt};
 pub use interpolation::Interpolation;
-use step_inputs::StepInputs;
+#[cfg(feature = "serde")]
+pub use snapshot::StepSnapshot;
+pub(crate) use state_hasher::StateHasher;
+pub use step_inputs::{EraseInput, StepInputs};
 use time::ScheduledTime;
 use wrapped_transposer::WrappedTransposer;
 
 use crate::schedule_storage::{DefaultStorage, RefCounted, StorageFamily};
-use crate::Transposer;
+use crate::{Transposer, TransposerHash, TransposerInput};
 
 enum StepData<T: Transposer, S: StorageFamily> {
     Init(T::Time),
@@ -31,13 +38,70 @@ enum StepData<T: Transposer, S: StorageFamily> {
     Scheduled(ScheduledTime<T::Time>),
 }
 
-type SaturationFuture<'a, T, S> =
-    Pin<Box<dyn 'a + Future<Output = <S as StorageFamily>::Transposer<WrappedTransposer<T, S>>>>>;
+type StateHashFunction<T, S> = fn(&WrappedTransposer<T, S>) -> u64;
 
-enum StepStatus<T: Transposer, S: StorageFamily> {
+type OutputSender<T> = mpsc::Sender<(<T as Transposer>::OutputEvent, oneshot::Sender<()>)>;
+
+// this is named instead of boxed as a trait object, so the step is Send whenever the future is.
+type SaturationFuture<T: Transposer, Is: InputState<T>, S: StorageFamily> =
+    impl Future<Output = <S as StorageFamily>::Transposer<WrappedTransposer<T, S>>>;
+
+// what a step is saturated from.
+enum SaturateFrom<T: Transposer, S: StorageFamily> {
+    Init { transposer: T, rng_seed: [u8; 32] },
+    Previous(S::Transposer<WrappedTransposer<T, S>>),
+}
+
+#[define_opaque(SaturationFuture)]
+fn saturation_future<T: Transposer, Is: InputState<T>, S: StorageFamily>(
+    from: SaturateFrom<T, S>,
+    step_data: Arc<StepData<T, S>>,
+    input_state: S::LazyState<Is>,
+    event_count: usize,
+    output_sender: OutputSender<T>,
+) -> SaturationFuture<T, Is, S> {
+    async move {
+        match (from, step_data.as_ref()) {
+            (
+                SaturateFrom::Init {
+                    transposer,
+                    rng_seed,
+                },
+                StepData::Init(start_time),
+            ) => {
+                WrappedTransposer::<T, S>::init(
+                    transposer,
+                    rng_seed,
+                    *start_time,
+                    input_state,
+                    event_count,
+                    output_sender,
+                )
+                .await
+            },
+            (SaturateFrom::Previous(mut wrapped_transposer), StepData::Input(i)) => {
+                wrapped_transposer
+                    .mutate()
+                    .handle_input(i, input_state, event_count, output_sender)
+                    .await;
+                wrapped_transposer
+            },
+            (SaturateFrom::Previous(mut wrapped_transposer), StepData::Scheduled(t)) => {
+                wrapped_transposer
+                    .mutate()
+                    .handle_scheduled(t.time, input_state, event_count, output_sender)
+                    .await;
+                wrapped_transposer
+            },
+            _ => unreachable!(),
+        }
+    }
+}
+
+enum StepStatus<T: Transposer, Is: InputState<T>, S: StorageFamily> {
     Unsaturated,
     Saturating {
-        future:          SaturationFuture<'static, T, S>,
+        future:          Pin<Box<SaturationFuture<T, Is, S>>>,
         output_reciever: mpsc::Receiver<(T::OutputEvent, oneshot::Sender<()>)>,
     },
     Saturated {
@@ -45,7 +109,7 @@ enum StepStatus<T: Transposer, S: StorageFamily> {
     },
 }
 
-impl<T: Transposer, S: StorageFamily> Default for StepStatus<T, S> {
+impl<T: Transposer, Is: InputState<T>, S: StorageFamily> Default for StepStatus<T, Is, S> {
     fn default() -> Self {
         Self::Unsaturated
     }
@@ -54,9 +118,11 @@ impl<T: Transposer, S: StorageFamily> Default for StepStatus<T, S> {
 pub struct Step<T: Transposer, Is: InputState<T>, S: StorageFamily = DefaultStorage> {
     data:               Arc<StepData<T, S>>,
     input_state:        S::LazyState<Is>,
-    status:             StepStatus<T, S>,
+    status:             StepStatus<T, Is, S>,
     event_count:        usize,
     can_produce_events: bool,
+    state_hasher:       Option<StateHashFunction<T, S>>,
+    state_hash:         Option<u64>,
 
     #[cfg(debug_assertions)]
     uuid_self: uuid::Uuid,
@@ -71,6 +137,15 @@ pub trait InputState<T: Transposer> {
     fn get_provider(&self) -> &T::InputStateManager;
 }
 
+/// Access to the state of a single input, for whatever is driving the step.
+pub trait FulfillInputState<I: TransposerInput> {
+    /// whether the transposer is currently waiting on the state of `I`.
+    fn is_requested(&self) -> bool;
+
+    /// provide the state of `I`, returning it back if it was already provided.
+    fn set_state(&self, state: I::InputState) -> Result<(), I::InputState>;
+}
+
 pub struct NoInput;
 pub struct NoInputManager;
 
@@ -84,58 +159,35 @@ impl<T: Transposer<InputStateManager = NoInputManager>> InputState<T> for NoInpu
     }
 }
 
-impl<T: Transposer, Is: InputState<T>, S: StorageFamily> Drop for Step<T, Is, S> {
-    fn drop(&mut self) {
-        let status = core::mem::replace(&mut self.status, StepStatus::Unsaturated);
-
-        match status {
-            StepStatus::Unsaturated => {},
-            StepStatus::Saturating {
-                future,
-                output_reciever: _,
-            } => {
-                let future: SaturationFuture<'static, T, S> = future;
-                // SAFETY: the future here can only hold things that the step is already generic over and contains.
-                // this means that this lifetime forging to 'static is ok.
-                let future: SaturationFuture<'_, T, S> = unsafe { core::mem::transmute(future) };
-
-                drop(future);
-            },
-            StepStatus::Saturated {
-                wrapped_transposer: _,
-            } => {},
-        }
-    }
-}
-
 impl<T: Transposer, Is: InputState<T>, S: StorageFamily> Step<T, Is, S> {
     pub fn new_init(transposer: T, start_time: T::Time, rng_seed: [u8; 32]) -> Self {
         let input_state = S::LazyState::new(Box::new(Is::new()));
+        let data = Arc::new(StepData::Init(start_time));
         let (output_sender, output_reciever) = mpsc::channel(1);
-        let future = WrappedTransposer::<T, S>::init::<Is>(
-            transposer,
-            rng_seed,
-            start_time,
+        let future = saturation_future(
+            SaturateFrom::Init {
+                transposer,
+                rng_seed,
+            },
+            data.clone(),
             input_state.clone(),
             0,
             output_sender,
         );
-        let future: SaturationFuture<'_, T, S> = Box::pin(future);
-        // SAFETY: the future here can only hold things that the step is already generic over and contains.
-        // this means that this lifetime forging to 'static is ok.
-        let future: SaturationFuture<'static, T, S> = unsafe { core::mem::transmute(future) };
 
         let status = StepStatus::Saturating {
-            future,
+            future: Box::pin(future),
             output_reciever,
         };
 
         Step {
-            data: Arc::new(StepData::Init(start_time)),
+            data,
             input_state,
             status,
             event_count: 0,
             can_produce_events: true,
+            state_hasher: None,
+            state_hash: None,
 
             #[cfg(debug_assertions)]
             uuid_self: uuid::Uuid::new_v4(),
@@ -144,6 +196,33 @@ impl<T: Transposer, Is: InputState<T>, S: StorageFamily> Step<T, Is, S> {
         }
     }
 
+    /// start a chain of steps from a snapshot, in place of [`Step::new_init`].
+    ///
+    /// the step is already saturated, so it never emits any events.
+    /// like the init step, it can't be saturated again once it is desaturated.
+    #[cfg(feature = "serde")]
+    pub fn new_from_snapshot(snapshot: StepSnapshot<T>) -> Self {
+        let time = snapshot.time;
+        let wrapped_transposer = snapshot.into_wrapped_transposer();
+
+        Step {
+            data:               Arc::new(StepData::Init(time)),
+            input_state:        S::LazyState::new(Box::new(Is::new())),
+            status:             StepStatus::Saturated {
+                wrapped_transposer: S::Transposer::new(Box::new(wrapped_transposer)),
+            },
+            event_count:        0,
+            can_produce_events: false,
+            state_hasher:       None,
+            state_hash:         None,
+
+            #[cfg(debug_assertions)]
+            uuid_self:                          uuid::Uuid::new_v4(),
+            #[cfg(debug_assertions)]
+            uuid_prev:                          None,
+        }
+    }
+
     pub fn next_unsaturated(
         &self,
         next_inputs: &mut Option<StepInputs<T, S>>,
@@ -176,6 +255,8 @@ impl<T: Transposer, Is: InputState<T>, S: StorageFamily> Step<T, Is, S> {
             status:             StepStatus::Unsaturated,
             event_count:        0,
             can_produce_events: true,
+            state_hasher:       self.state_hasher,
+            state_hash:         None,
 
             #[cfg(debug_assertions)]
             uuid_self:                          uuid::Uuid::new_v4(),
@@ -243,51 +324,22 @@ impl<T: Transposer, Is: InputState<T>, S: StorageFamily> Step<T, Is, S> {
         }
     }
 
-    fn saturate(&mut self, mut wrapped_transposer: S::Transposer<WrappedTransposer<T, S>>) {
+    fn saturate(&mut self, wrapped_transposer: S::Transposer<WrappedTransposer<T, S>>) {
+        if let StepData::Init(_) = self.data.as_ref() {
+            panic!()
+        }
+
         let (output_sender, output_reciever) = mpsc::channel(1);
+        let future = saturation_future(
+            SaturateFrom::Previous(wrapped_transposer),
+            self.data.clone(),
+            self.input_state.clone(),
+            self.event_count,
+            output_sender,
+        );
 
         self.status = StepStatus::Saturating {
-            future: match self.data.as_ref() {
-                StepData::Init(_) => panic!(),
-                StepData::Input(_) => {
-                    let input_state = self.input_state.clone();
-                    let event_count = self.event_count;
-                    let step_data = self.data.clone();
-                    let future: SaturationFuture<'_, T, S> = Box::pin(async move {
-                        let i = match step_data.as_ref() {
-                            StepData::Input(i) => i,
-                            _ => unreachable!(),
-                        };
-                        wrapped_transposer
-                            .mutate()
-                            .handle_input(i, input_state, event_count, output_sender)
-                            .await;
-                        wrapped_transposer
-                    });
-                    // SAFETY: the future here can only hold things that the step is already generic over and contains.
-                    // this means that this lifetime forging to 'static is ok.
-                    let future: SaturationFuture<'static, T, S> =
-                        unsafe { core::mem::transmute(future) };
-                    future
-                },
-                StepData::Scheduled(t) => {
-                    let t = t.time;
-                    let event_count = self.event_count;
-                    let input_state = self.input_state.clone();
-                    let future: SaturationFuture<'_, T, S> = Box::pin(async move {
-                        wrapped_transposer
-                            .mutate()
-                            .handle_scheduled(t, input_state, event_count, output_sender)
-                            .await;
-                        wrapped_transposer
-                    });
-                    // SAFETY: the future here can only hold things that the step is already generic over and contains.
-                    // this means that this lifetime forging to 'static is ok.
-                    let future: SaturationFuture<'static, T, S> =
-                        unsafe { core::mem::transmute(future) };
-                    future
-                },
-            },
+            future: Box::pin(future),
             output_reciever,
         };
     }
@@ -315,6 +367,9 @@ impl<T: Transposer, Is: InputState<T>, S: StorageFamily> Step<T, Is, S> {
 
         let output = match poll {
             std::task::Poll::Ready(wrapped_transposer) => {
+                if self.state_hash.is_none() {
+                    self.state_hash = self.state_hasher.map(|hasher| hasher(&wrapped_transposer));
+                }
                 self.status = StepStatus::Saturated {
                     wrapped_transposer,
                 };
@@ -349,6 +404,67 @@ impl<T: Transposer, Is: InputState<T>, S: StorageFamily> Step<T, Is, S> {
         Ok(Interpolation::new(time, wrapped_transposer))
     }
 
+    /// capture everything needed to resume from this step with [`Step::new_from_snapshot`].
+    #[cfg(feature = "serde")]
+    pub fn snapshot(&self) -> Result<StepSnapshot<T>, SnapshotErr> {
+        match &self.status {
+            StepStatus::Saturated {
+                wrapped_transposer,
+            } => Ok(StepSnapshot::new(self.get_time(), wrapped_transposer)),
+            _ => Err(SnapshotErr::NotSaturated),
+        }
+    }
+
+    /// discard the step, recovering the inputs it was created from, if any.
+    pub fn into_inputs(mut self) -> Option<StepInputs<T, S>> {
+        // the saturation future holds a reference to the data, so drop it first.
+        self.desaturate();
+
+        let placeholder = Arc::new(StepData::Init(self.get_time()));
+        match Arc::try_unwrap(core::mem::replace(&mut self.data, placeholder)) {
+            Ok(StepData::Input(inputs)) => Some(inputs),
+            _ => None,
+        }
+    }
+
+    /// hash the state of this step, and every step after it, when they are first saturated.
+    pub fn enable_state_hashing(&mut self)
+    where
+        T: TransposerHash,
+        T::Time: Hash,
+        T::Period: Hash,
+        T::Scheduled: Hash,
+    {
+        self.state_hasher = Some(WrappedTransposer::state_hash);
+
+        if let StepStatus::Saturated {
+            wrapped_transposer,
+        } = &self.status
+        {
+            self.state_hash
+                .get_or_insert_with(|| wrapped_transposer.state_hash());
+        }
+    }
+
+    /// the hash of the state this step saturated to, if state hashing is enabled.
+    ///
+    /// this is kept when the step is desaturated, because saturating again always results in the same state.
+    pub fn state_hash(&self) -> Option<u64> {
+        self.state_hash
+    }
+
+    /// an approximate number of bytes held by the saturated state of this step, if it is saturated.
+    ///
+    /// this is based on [`Transposer::heap_size`].
+    pub fn approximate_size(&self) -> Option<usize> {
+        match &self.status {
+            StepStatus::Saturated {
+                wrapped_transposer,
+            } => Some(wrapped_transposer.approximate_size()),
+            _ => None,
+        }
+    }
+
     pub fn get_input_state(&self) -> &Is {
         &self.input_state
     }
@@ -398,6 +514,12 @@ pub enum InterpolateErr {
     TimePast,
 }
 
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum SnapshotErr {
+    NotSaturated,
+}
+
 #[derive(Debug)]
 pub enum NextUnsaturatedErr {
     NotSaturated,
@@ -0,0 +1,94 @@
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use super::expire_handle_factory::ExpireHandleFactory;
+use super::time::{ScheduledTime, SubStepTime};
+use super::transposer_metadata::TransposerMetaData;
+use super::wrapped_transposer::WrappedTransposer;
+use crate::expire_handle::ExpireHandle;
+use crate::schedule_storage::{HashMapStorage, OrdMapStorage, StorageFamily};
+use crate::Transposer;
+
+/// Everything needed to resume a transposer from a saturated step, in a form that can be serialized.
+///
+/// Take one with [`Step::snapshot`](super::Step::snapshot),
+/// and resume from it with [`Step::new_from_snapshot`](super::Step::new_from_snapshot).
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Serialize, T::Time: Serialize, T::Period: Serialize, T::Scheduled: Serialize",
+    deserialize = "T: Deserialize<'de>, T::Time: Deserialize<'de>, T::Period: Deserialize<'de>, \
+                   T::Scheduled: Deserialize<'de>"
+))]
+pub struct StepSnapshot<T: Transposer> {
+    pub(crate) time: T::Time,
+
+    transposer:            T,
+    last_updated:          SubStepTime<T::Time>,
+    schedule:              Vec<(ScheduledTime<T::Time>, T::Scheduled)>,
+    expire_handles:        Vec<(ExpireHandle, ScheduledTime<T::Time>)>,
+    repeating:             Vec<(ExpireHandle, T::Period)>,
+    expire_handle_factory: ExpireHandleFactory,
+    rng:                   ChaCha12Rng,
+}
+
+impl<T: Transposer> StepSnapshot<T> {
+    pub(crate) fn new<S: StorageFamily>(time: T::Time, wrapped: &WrappedTransposer<T, S>) -> Self {
+        let metadata = &wrapped.metadata;
+
+        Self {
+            time,
+            transposer: wrapped.transposer.clone(),
+            last_updated: metadata.last_updated,
+            schedule: metadata
+                .schedule
+                .iter()
+                .map(|(k, v)| (*k, v.clone()))
+                .collect(),
+            expire_handles: metadata
+                .expire_handles_forward
+                .iter()
+                .map(|(k, v)| (*k, *v))
+                .collect(),
+            repeating: metadata.repeating.iter().map(|(k, v)| (*k, *v)).collect(),
+            expire_handle_factory: metadata.expire_handle_factory.clone(),
+            rng: metadata.rng.clone(),
+        }
+    }
+
+    pub(crate) fn into_wrapped_transposer<S: StorageFamily>(self) -> WrappedTransposer<T, S> {
+        let mut schedule =
+            <S::OrdMap<ScheduledTime<T::Time>, T::Scheduled> as OrdMapStorage<_, _>>::new();
+        let mut expire_handles_forward =
+            <S::HashMap<ExpireHandle, ScheduledTime<T::Time>> as HashMapStorage<_, _>>::new();
+        let mut expire_handles_backward =
+            <S::OrdMap<ScheduledTime<T::Time>, ExpireHandle> as OrdMapStorage<_, _>>::new();
+        let mut repeating = <S::HashMap<ExpireHandle, T::Period> as HashMapStorage<_, _>>::new();
+
+        for (time, payload) in self.schedule {
+            schedule.insert(time, payload);
+        }
+
+        // the backward map is just the forward map inverted.
+        for (handle, time) in self.expire_handles {
+            expire_handles_forward.insert(handle, time);
+            expire_handles_backward.insert(time, handle);
+        }
+
+        for (handle, period) in self.repeating {
+            repeating.insert(handle, period);
+        }
+
+        WrappedTransposer {
+            transposer: self.transposer,
+            metadata:   TransposerMetaData {
+                last_updated: self.last_updated,
+                schedule,
+                expire_handles_forward,
+                expire_handles_backward,
+                repeating,
+                expire_handle_factory: self.expire_handle_factory,
+                rng: self.rng,
+            },
+        }
+    }
+}
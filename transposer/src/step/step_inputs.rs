@@ -1,53 +1,131 @@
-use std::any::TypeId;
 use std::collections::BTreeMap;
-use std::pin::Pin;
 
 use futures_core::Future;
-use type_erased_vec::TypeErasedVec;
 
 use super::sub_step_update_context::SubStepUpdateContext;
-use crate::schedule_storage::StorageFamily;
+use crate::schedule_storage::{Erasure, LocalErasure, SendErasure, StorageFamily};
 use crate::{Transposer, TransposerInput, TransposerInputEventHandler};
 
 pub struct StepInputs<T: Transposer, S: StorageFamily> {
     pub time: T::Time,
 
-    // these btreesets are all of different values. they are transmuted before use.
+    // these entries all hold values of different types. they are downcast before use.
     inputs: BTreeMap<u64, StepInputsEntry<T, S>>,
 }
 
+type Erased<S> = <<S as StorageFamily>::Erasure as Erasure>::Any;
+
 type HandlerFunction<T, S> = for<'a> fn(
     time: <T as Transposer>::Time,
     &'a mut T,
-    &'a mut SubStepUpdateContext<'_, T, S>,
-    &'a TypeErasedVec,
-) -> Pin<Box<dyn 'a + Future<Output = ()>>>;
+    SubStepUpdateContext<'a, T, S>,
+    &'a Erased<S>,
+) -> <<S as StorageFamily>::Erasure as Erasure>::Future<'a>;
+
+type MergeFunction<T, S> = fn(time: <T as Transposer>::Time, &mut Erased<S>, &mut Erased<S>);
 
 struct StepInputsEntry<T: Transposer, S: StorageFamily> {
-    // keep this sorted
-    values:        TypeErasedVec,
-    input_type_id: TypeId,
-    handler:       HandlerFunction<T, S>,
+    // a Vec<I::InputEvent>, which is kept sorted.
+    values:  Box<Erased<S>>,
+    handler: HandlerFunction<T, S>,
+    merge:   MergeFunction<T, S>,
+}
+
+// this is named so the storage family can require it to be Send, see EraseInput.
+type HandleInputs<'a, I: TransposerInput, S: StorageFamily> = impl 'a + Future<Output = ()>;
+
+#[define_opaque(HandleInputs)]
+fn handle_inputs<'a, I: TransposerInput, S: StorageFamily>(
+    time: <I::Base as Transposer>::Time,
+    transposer: &'a mut I::Base,
+    mut cx: SubStepUpdateContext<'a, I::Base, S>,
+    events: &'a [I::InputEvent],
+) -> HandleInputs<'a, I, S> {
+    async move {
+        for (ordinal, i) in events.iter().enumerate() {
+            cx.start_input_event(I::SORT, ordinal);
+            transposer.handle_input(i, &mut cx).await;
+            cx.metadata.last_updated.time = time;
+            cx.metadata.last_updated.index += 1;
+        }
+    }
+}
+
+/// Type erasure which can erase the input events of `I`, and the futures handling them.
+///
+/// [`LocalErasure`] can erase the events of any input, and [`SendErasure`] can erase the events of inputs
+/// whose events are `Send + Sync`, and whose handlers return futures which are `Send`.
+pub trait EraseInput<I: TransposerInput, S: StorageFamily>: Erasure {
+    #[doc(hidden)]
+    fn erase_events(events: Box<Vec<I::InputEvent>>) -> Box<Self::Any>;
+
+    #[doc(hidden)]
+    fn handle<'a>(
+        time: <I::Base as Transposer>::Time,
+        transposer: &'a mut I::Base,
+        cx: SubStepUpdateContext<'a, I::Base, S>,
+        events: &'a [I::InputEvent],
+    ) -> Self::Future<'a>;
+}
+
+impl<I: TransposerInput, S: StorageFamily> EraseInput<I, S> for LocalErasure {
+    fn erase_events(events: Box<Vec<I::InputEvent>>) -> Box<Self::Any> {
+        events
+    }
+
+    fn handle<'a>(
+        time: <I::Base as Transposer>::Time,
+        transposer: &'a mut I::Base,
+        cx: SubStepUpdateContext<'a, I::Base, S>,
+        events: &'a [I::InputEvent],
+    ) -> Self::Future<'a> {
+        Box::pin(handle_inputs::<I, S>(time, transposer, cx, events))
+    }
+}
+
+impl<I: TransposerInput, S: StorageFamily> EraseInput<I, S> for SendErasure
+where
+    I::InputEvent: Send + Sync,
+    for<'a> HandleInputs<'a, I, S>: Send,
+{
+    fn erase_events(events: Box<Vec<I::InputEvent>>) -> Box<Self::Any> {
+        events
+    }
+
+    fn handle<'a>(
+        time: <I::Base as Transposer>::Time,
+        transposer: &'a mut I::Base,
+        cx: SubStepUpdateContext<'a, I::Base, S>,
+        events: &'a [I::InputEvent],
+    ) -> Self::Future<'a> {
+        Box::pin(handle_inputs::<I, S>(time, transposer, cx, events))
+    }
+}
+
+fn events<I: TransposerInput, S: StorageFamily>(values: &Erased<S>) -> &Vec<I::InputEvent> {
+    S::Erasure::as_any(values).downcast_ref().unwrap()
+}
+
+fn events_mut<I: TransposerInput, S: StorageFamily>(
+    values: &mut Erased<S>,
+) -> &mut Vec<I::InputEvent> {
+    S::Erasure::as_any_mut(values).downcast_mut().unwrap()
 }
 
 impl<T: Transposer, S: StorageFamily> StepInputsEntry<T, S> {
     fn new<I: TransposerInput<Base = T>>() -> Self
     where
         T: TransposerInputEventHandler<I>,
+        S::Erasure: EraseInput<I, S>,
     {
         Self {
-            values:        TypeErasedVec::new::<I::InputEvent>(),
-            input_type_id: TypeId::of::<I>(),
-            handler:       |time, t, cx, set| {
-                // SAFETY: this came from the assignment to values, which erased the I::InputEvent type
-                let set = unsafe { set.get::<I::InputEvent>() };
-                Box::pin(async move {
-                    for i in set.iter() {
-                        T::handle_input(t, i, cx).await;
-                        cx.metadata.last_updated.time = time;
-                        cx.metadata.last_updated.index += 1;
-                    }
-                })
+            values:  S::Erasure::erase_events(Box::default()),
+            handler: |time, t, cx, values| S::Erasure::handle(time, t, cx, events::<I, S>(values)),
+            merge:   |time, values, other| {
+                let values = events_mut::<I, S>(values);
+                for input in events_mut::<I, S>(other).drain(..) {
+                    insert_sorted::<T, I>(values, time, input);
+                }
             },
         }
     }
@@ -56,30 +134,42 @@ impl<T: Transposer, S: StorageFamily> StepInputsEntry<T, S> {
     where
         T: TransposerInputEventHandler<I>,
     {
-        if TypeId::of::<I>() != self.input_type_id {
-            panic!()
-        }
+        insert_sorted::<T, I>(events_mut::<I, S>(&mut self.values), time, input);
+    }
 
-        // SAFETY: this matches the type because I has a TypeId that matches the one that created it.
-        let mut set = unsafe { self.values.get_mut() };
+    fn merge(&mut self, time: T::Time, mut other: Self) {
+        (self.merge)(time, &mut self.values, &mut other.values);
+    }
+}
 
-        let i =
-            set.partition_point(|existing| T::sort_input_events(time, &input, existing).is_lt());
+fn insert_sorted<T, I>(set: &mut Vec<I::InputEvent>, time: T::Time, input: I::InputEvent)
+where
+    T: TransposerInputEventHandler<I>,
+    I: TransposerInput<Base = T>,
+{
+    let i = set.partition_point(|existing| T::sort_input_events(time, &input, existing).is_lt());
 
-        set.insert(i, input);
-    }
+    set.insert(i, input);
 }
 
 impl<T: Transposer, S: StorageFamily> StepInputs<T, S> {
+    pub fn new(time: T::Time) -> Self {
+        Self {
+            time,
+            inputs: BTreeMap::new(),
+        }
+    }
+
     pub async fn handle(&self, transposer: &mut T, cx: &mut SubStepUpdateContext<'_, T, S>) {
         for (_, i) in self.inputs.iter() {
-            (i.handler)(self.time, transposer, cx, &i.values).await;
+            (i.handler)(self.time, transposer, cx.reborrow(), &i.values).await;
         }
     }
 
     pub fn add_event<I: TransposerInput<Base = T>>(&mut self, event: I::InputEvent)
     where
         T: TransposerInputEventHandler<I>,
+        S::Erasure: EraseInput<I, S>,
     {
         let step_inputs_entry = match self.inputs.entry(I::SORT) {
             std::collections::btree_map::Entry::Vacant(v) => v.insert(StepInputsEntry::new()),
@@ -92,4 +182,29 @@ impl<T: Transposer, S: StorageFamily> StepInputs<T, S> {
     pub fn time(&self) -> T::Time {
         self.time
     }
+
+    /// combine the inputs from `other`, which must be at the same time.
+    pub fn merge(&mut self, other: Self) {
+        debug_assert!(self.time == other.time);
+
+        for (sort, entry) in other.inputs {
+            match self.inputs.entry(sort) {
+                std::collections::btree_map::Entry::Vacant(v) => {
+                    v.insert(entry);
+                },
+                std::collections::btree_map::Entry::Occupied(mut o) => {
+                    o.get_mut().merge(self.time, entry)
+                },
+            }
+        }
+    }
+
+    /// discard all the events for the input with this `SORT`.
+    pub fn remove_input(&mut self, sort: u64) {
+        self.inputs.remove(&sort);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
 }
@@ -1,72 +1,147 @@
 use core::future::Future;
-use core::pin::Pin;
+use core::ops::Bound;
 
-use super::time::SubStepTime;
-use super::transposer_metadata::TransposerMetaData;
+use super::time::{ScheduledTime, SubStepTime};
+use super::transposer_metadata::{event_identity, TransposerMetaData};
 use crate::context::*;
 use crate::expire_handle::ExpireHandle;
-use crate::schedule_storage::StorageFamily;
+use crate::period::RepeatPeriod;
+use crate::schedule_storage::{OrdMapStorage, StorageFamily};
 use crate::Transposer;
 
-/// This is the interface through which you can do a variety of functions in your transposer.
-///
-/// the primary features are scheduling and expiring events,
-/// though there are more methods to interact with the engine.
-pub struct SubStepUpdateContext<'update, T: Transposer, S: StorageFamily> {
-    time:         SubStepTime<T::Time>,
-    // these are pointers because this is stored next to the targets.
-    pub metadata: &'update mut TransposerMetaData<T, S>,
+type OutputSender<T> = futures_channel::mpsc::Sender<(
+    <T as Transposer>::OutputEvent,
+    futures_channel::oneshot::Sender<()>,
+)>;
 
-    // pub time:               SubStepTime<T::Time>,
+/// The state of a sub step, which lasts across every context borrowing it.
+pub struct SubStepState<T: Transposer> {
+    time:                   SubStepTime<T::Time>,
     pub outputs_to_swallow: usize,
     current_emission_index: usize,
 
+    // the identity of the event being handled, and how many events it has scheduled, for the per event rng.
+    event_identity:       u64,
+    event_emission_index: usize,
+
     // values to output
-    pub output_sender:
-        futures_channel::mpsc::Sender<(T::OutputEvent, futures_channel::oneshot::Sender<()>)>,
+    pub output_sender: OutputSender<T>,
+}
 
-    input_state: &'update T::InputStateManager,
+impl<T: Transposer> SubStepState<T> {
+    pub fn new(
+        time: SubStepTime<T::Time>,
+        outputs_to_swallow: usize,
+        output_sender: OutputSender<T>,
+    ) -> Self {
+        Self {
+            time,
+            outputs_to_swallow,
+            current_emission_index: 0,
+            event_identity: 0,
+            event_emission_index: 0,
+            output_sender,
+        }
+    }
+}
+
+/// This is the interface through which you can do a variety of functions in your transposer.
+///
+/// the primary features are scheduling and expiring events,
+/// though there are more methods to interact with the engine.
+pub struct SubStepUpdateContext<'update, T: Transposer, S: StorageFamily> {
+    pub metadata: &'update mut TransposerMetaData<T, S>,
+    state:        &'update mut SubStepState<T>,
+    input_state:  &'update T::InputStateManager,
 }
 
-impl<'update, T: Transposer, S: StorageFamily> InitContext<'update, T>
+impl<'update, T: Transposer, S: StorageFamily> InitContext<T>
     for SubStepUpdateContext<'update, T, S>
 {
 }
-impl<'update, T: Transposer, S: StorageFamily> HandleInputContext<'update, T>
+impl<'update, T: Transposer, S: StorageFamily> HandleInputContext<T>
     for SubStepUpdateContext<'update, T, S>
 {
 }
-impl<'update, T: Transposer, S: StorageFamily> HandleScheduleContext<'update, T>
+impl<'update, T: Transposer, S: StorageFamily> HandleScheduleContext<T>
     for SubStepUpdateContext<'update, T, S>
 {
 }
 impl<'update, T: Transposer, S: StorageFamily> SubStepUpdateContext<'update, T, S> {
-    // SAFETY: need to gurantee the metadata pointer outlives this object.
     pub fn new(
-        time: SubStepTime<T::Time>,
         metadata: &'update mut TransposerMetaData<T, S>,
+        state: &'update mut SubStepState<T>,
         input_state: &'update T::InputStateManager,
-        outputs_to_swallow: usize,
-        output_sender: futures_channel::mpsc::Sender<(
-            T::OutputEvent,
-            futures_channel::oneshot::Sender<()>,
-        )>,
     ) -> Self {
         Self {
-            time,
             metadata,
+            state,
             input_state,
-            current_emission_index: 0,
-            outputs_to_swallow,
-            output_sender,
         }
     }
+
+    /// a context for the same sub step, which only lasts as long as this borrow.
+    pub fn reborrow(&mut self) -> SubStepUpdateContext<'_, T, S> {
+        SubStepUpdateContext {
+            metadata:    &mut *self.metadata,
+            state:       &mut *self.state,
+            input_state: self.input_state,
+        }
+    }
+
+    /// start handling the init event.
+    pub fn start_init_event(&mut self) {
+        self.start_event(|_| [0, 0, 0, 0]);
+    }
+
+    /// start handling the `ordinal`th event of the input with this `sort`, in this sub step.
+    pub fn start_input_event(&mut self, sort: u64, ordinal: usize) {
+        let time = self.state.time.time;
+        self.start_event(|time_identity| [1, time_identity(&time), sort, ordinal as u64]);
+    }
+
+    /// start handling this scheduled event.
+    pub fn start_scheduled_event(&mut self, time: ScheduledTime<T::Time>) {
+        self.start_event(|_| [2, time.identity, time.repetition as u64, 0]);
+    }
+
+    // with RngMode::PerEvent, every event gets its own rng stream.
+    // the identity of the stream only depends on the event itself, and not on the events handled before it.
+    fn start_event(&mut self, parts: impl FnOnce(fn(&T::Time) -> u64) -> [u64; 4]) {
+        if let RngMode::PerEvent(time_identity) = T::RNG_MODE {
+            self.state.event_identity = event_identity(parts(time_identity));
+            self.state.event_emission_index = 0;
+            self.metadata.start_event_rng(self.state.event_identity);
+        }
+    }
+
+    fn spawn_scheduled(&mut self, time: T::Time) -> ScheduledTime<T::Time> {
+        // an event scheduled by the current one is identified by its parent, and its position among its siblings.
+        let identity = match T::RNG_MODE {
+            RngMode::SingleStream => 0,
+            RngMode::PerEvent(_) => event_identity([
+                3,
+                self.state.event_identity,
+                self.state.event_emission_index as u64,
+                0,
+            ]),
+        };
+        self.state.event_emission_index += 1;
+
+        let time =
+            self.state
+                .time
+                .spawn_scheduled(time, self.state.current_emission_index, identity);
+        self.state.current_emission_index += 1;
+
+        time
+    }
 }
 
-impl<'update, T: Transposer, S: StorageFamily> InputStateContext<'update, T>
+impl<'update, T: Transposer, S: StorageFamily> InputStateContext<T>
     for SubStepUpdateContext<'update, T, S>
 {
-    fn get_input_state_manager(&mut self) -> &'update T::InputStateManager {
+    fn get_input_state_manager(&self) -> &T::InputStateManager {
         self.input_state
     }
 }
@@ -79,14 +154,13 @@ impl<'update, T: Transposer, S: StorageFamily> ScheduleEventContext<T>
         time: T::Time,
         payload: T::Scheduled,
     ) -> Result<(), ScheduleEventError> {
-        if time < self.time.time {
+        if time < self.state.time.time {
             return Err(ScheduleEventError::NewEventBeforeCurrent)
         }
 
-        let time = self.time.spawn_scheduled(time, self.current_emission_index);
+        let time = self.spawn_scheduled(time);
 
         self.metadata.schedule_event(time, payload);
-        self.current_emission_index += 1;
 
         Ok(())
     }
@@ -96,14 +170,39 @@ impl<'update, T: Transposer, S: StorageFamily> ScheduleEventContext<T>
         time: T::Time,
         payload: T::Scheduled,
     ) -> Result<ExpireHandle, ScheduleEventError> {
-        if time < self.time.time {
+        if time < self.state.time.time {
             return Err(ScheduleEventError::NewEventBeforeCurrent)
         }
 
-        let time = self.time.spawn_scheduled(time, self.current_emission_index);
+        let time = self.spawn_scheduled(time);
 
         let handle = self.metadata.schedule_event_expireable(time, payload);
-        self.current_emission_index += 1;
+
+        Ok(handle)
+    }
+
+    fn schedule_repeating(
+        &mut self,
+        start: T::Time,
+        period: T::Period,
+        payload: T::Scheduled,
+    ) -> Result<ExpireHandle, ScheduleEventError> {
+        if start < self.state.time.time {
+            return Err(ScheduleEventError::NewEventBeforeCurrent)
+        }
+
+        // if the second repetition can't be represented, this is just scheduled once.
+        if let Some(next) = period.checked_add_to(start) {
+            if next <= start {
+                return Err(ScheduleEventError::NonPositivePeriod)
+            }
+        }
+
+        let time = self.spawn_scheduled(start);
+
+        let handle = self
+            .metadata
+            .schedule_event_repeating(time, period, payload);
 
         Ok(handle)
     }
@@ -118,6 +217,59 @@ impl<'update, T: Transposer, S: StorageFamily> ExpireEventContext<T>
     ) -> Result<(T::Time, T::Scheduled), ExpireEventError> {
         self.metadata.expire_event(handle)
     }
+
+    fn reschedule_event(
+        &mut self,
+        handle: ExpireHandle,
+        time: T::Time,
+    ) -> Result<T::Time, RescheduleEventError> {
+        if time < self.state.time.time {
+            return Err(RescheduleEventError::NewEventBeforeCurrent)
+        }
+
+        // check the handle first, so a failed reschedule doesn't use up an emission index.
+        if self.metadata.get_expireable_event(handle).is_none() {
+            return Err(RescheduleEventError::InvalidOrUsedHandle)
+        }
+
+        let time = self.spawn_scheduled(time);
+
+        // the handle was checked above
+        Ok(self.metadata.reschedule_event(handle, time).unwrap())
+    }
+
+    fn replace_event_payload(
+        &mut self,
+        handle: ExpireHandle,
+        payload: T::Scheduled,
+    ) -> Result<T::Scheduled, ExpireEventError> {
+        self.metadata.replace_event_payload(handle, payload)
+    }
+
+    fn get_expireable_event(&self, handle: ExpireHandle) -> Option<(T::Time, &T::Scheduled)> {
+        self.metadata.get_expireable_event(handle)
+    }
+}
+
+impl<'update, T: Transposer, S: StorageFamily> ScheduleQueryContext<T>
+    for SubStepUpdateContext<'update, T, S>
+{
+    fn next_scheduled_time(&self) -> Option<T::Time> {
+        self.metadata
+            .get_next_scheduled_time()
+            .map(|time| time.time)
+    }
+
+    fn scheduled_events(
+        &self,
+        range: (Bound<T::Time>, Bound<T::Time>),
+    ) -> Box<dyn '_ + Iterator<Item = (T::Time, &'_ T::Scheduled)>> {
+        Box::new(self.metadata.scheduled_events(range))
+    }
+
+    fn scheduled_event_count(&self) -> usize {
+        self.metadata.schedule.len()
+    }
 }
 
 impl<'update, T: Transposer, S: StorageFamily> EmitEventContext<T>
@@ -126,19 +278,22 @@ impl<'update, T: Transposer, S: StorageFamily> EmitEventContext<T>
     fn emit_event(
         &mut self,
         payload: <T as Transposer>::OutputEvent,
-    ) -> Pin<Box<dyn '_ + Future<Output = ()>>> {
+    ) -> impl '_ + Future<Output = ()> {
         // if we need to swallow events still
-        if self.outputs_to_swallow > 0 {
-            self.outputs_to_swallow -= 1;
-            return Box::pin(core::future::ready(()))
-        }
+        let recv = if self.state.outputs_to_swallow > 0 {
+            self.state.outputs_to_swallow -= 1;
+            None
+        } else {
+            let (send, recv) = futures_channel::oneshot::channel();
+            self.state.output_sender.try_send((payload, send)).unwrap();
+            Some(recv)
+        };
 
-        let (send, recv) = futures_channel::oneshot::channel();
-        self.output_sender.try_send((payload, send)).unwrap();
-
-        Box::pin(async move {
-            recv.await.unwrap();
-        })
+        async move {
+            if let Some(recv) = recv {
+                recv.await.unwrap();
+            }
+        }
     }
 }
 
@@ -152,7 +307,7 @@ impl<'update, T: Transposer, S: StorageFamily> CurrentTimeContext<T>
     for SubStepUpdateContext<'update, T, S>
 {
     fn current_time(&self) -> <T as Transposer>::Time {
-        self.time.time
+        self.state.time.time
     }
 }
 
@@ -1,14 +1,56 @@
+use core::ops::Bound;
 use core::pin::Pin;
 
 use matches::assert_matches;
 use rand::Rng;
 use util::dummy_waker::DummyWaker;
 
-use super::{NoInput, NoInputManager, StepPoll};
-use crate::context::{HandleScheduleContext, InitContext, InterpolateContext};
+use super::{Interpolation, NoInput, NoInputManager, StepPoll};
+use crate::context::{
+    HandleScheduleContext,
+    InitContext,
+    InterpolateContext,
+    RescheduleEventError,
+    RngMode,
+    ScheduleEventError,
+};
+use crate::expire_handle::ExpireHandle;
+use crate::multi_input_state::MultiInputState;
+use crate::schedule_storage::{DefaultStorage, StdStorage};
 use crate::step::Step;
+use crate::test::test_transposer::TestTransposer as InputTransposer;
 use crate::Transposer;
 
+// saturate the steps following `step` in order, up to those at `until`,
+// returning the emitted events with the times of their steps.
+fn run_steps<T: Transposer<InputStateManager = NoInputManager>>(
+    step: &mut Step<T, NoInput>,
+    until: T::Time,
+) -> Vec<(T::Time, T::OutputEvent)> {
+    let waker = DummyWaker::dummy();
+    let mut events = Vec::new();
+
+    while let Some(mut next) = step.next_scheduled_unsaturated().unwrap() {
+        if next.get_time() > until {
+            break
+        }
+
+        next.saturate_take(step).unwrap();
+
+        loop {
+            match next.poll(&waker).unwrap() {
+                StepPoll::Emitted(e) => events.push((next.get_time(), e)),
+                StepPoll::Ready => break,
+                StepPoll::Pending => panic!(),
+            }
+        }
+
+        *step = next;
+    }
+
+    events
+}
+
 #[derive(Clone, Debug)]
 struct TestTransposer {
     counter: u32,
@@ -25,7 +67,7 @@ impl Transposer for TestTransposer {
 
     type InputStateManager = NoInputManager;
 
-    async fn init(&mut self, cx: &mut dyn InitContext<'_, Self>) {
+    async fn init(&mut self, cx: &mut impl InitContext<Self>) {
         self.counter = 0;
         cx.schedule_event(1, ()).unwrap();
     }
@@ -33,7 +75,7 @@ impl Transposer for TestTransposer {
     async fn handle_scheduled(
         &mut self,
         _payload: Self::Scheduled,
-        cx: &mut dyn HandleScheduleContext<'_, Self>,
+        cx: &mut impl HandleScheduleContext<Self>,
     ) {
         cx.schedule_event(cx.current_time() + 1, ()).unwrap();
 
@@ -41,11 +83,58 @@ impl Transposer for TestTransposer {
         cx.emit_event(()).await;
     }
 
-    async fn interpolate(&self, _cx: &mut dyn InterpolateContext<'_, Self>) -> Self::OutputState {
+    async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {
         self.counter
     }
 }
 
+#[allow(unused)]
+fn steps_are_send() {
+    fn assert_send<T: Send>() {}
+
+    assert_send::<Step<TestTransposer, NoInput, DefaultStorage>>();
+    assert_send::<Step<TestTransposer, NoInput, StdStorage>>();
+    assert_send::<Interpolation<TestTransposer, NoInput, StdStorage>>();
+
+    // the input events of these are erased by the storage family.
+    assert_send::<Step<InputTransposer, MultiInputState<InputTransposer>, DefaultStorage>>();
+    assert_send::<Interpolation<InputTransposer, MultiInputState<InputTransposer>, StdStorage>>();
+}
+
+#[test]
+fn saturate_on_other_threads() {
+    let transposer = TestTransposer {
+        counter: 17
+    };
+
+    let mut step = Step::<_, NoInput, StdStorage>::new_init(transposer, 0, [0; 32]);
+
+    // each step is saturated on a different thread than the one it was created on.
+    for _ in 0..10 {
+        step = std::thread::spawn(move || {
+            let waker = DummyWaker::dummy();
+            while !matches!(step.poll(&waker), Ok(StepPoll::Ready)) {}
+
+            let mut next = step.next_scheduled_unsaturated().unwrap().unwrap();
+            next.saturate_take(&mut step).unwrap();
+            next
+        })
+        .join()
+        .unwrap();
+    }
+
+    let interpolated = std::thread::spawn(move || {
+        let waker = DummyWaker::dummy();
+        while !matches!(step.poll(&waker), Ok(StepPoll::Ready)) {}
+        let interpolation = step.interpolate(100).unwrap();
+        futures_executor::block_on(interpolation)
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(interpolated, 10);
+}
+
 #[test]
 fn next_scheduled_unsaturated_take() {
     let transposer = TestTransposer {
@@ -131,3 +220,578 @@ fn next_scheduled_unsaturated_desaturate() {
 
     step1.desaturate();
 }
+
+#[derive(Clone, Debug)]
+struct RescheduleTransposer {
+    handle: Option<ExpireHandle>,
+}
+
+impl Transposer for RescheduleTransposer {
+    type Time = u32;
+
+    type OutputState = ();
+
+    type Scheduled = char;
+
+    type OutputEvent = char;
+
+    type InputStateManager = NoInputManager;
+
+    async fn init(&mut self, cx: &mut impl InitContext<Self>) {
+        cx.schedule_event(2, 'x').unwrap();
+        self.handle = Some(cx.schedule_event_expireable(10, 'a').unwrap());
+    }
+
+    async fn handle_scheduled(
+        &mut self,
+        payload: Self::Scheduled,
+        cx: &mut impl HandleScheduleContext<Self>,
+    ) {
+        cx.emit_event(payload).await;
+
+        let handle = self.handle.unwrap();
+
+        if payload == 'x' {
+            assert_eq!(cx.get_expireable_event(handle), Some((10, &'a')));
+
+            // the rescheduled event goes after events already scheduled for the same time.
+            cx.schedule_event(3, 'c').unwrap();
+            assert_eq!(cx.reschedule_event(handle, 3).unwrap(), 10);
+            assert_eq!(cx.replace_event_payload(handle, 'b').unwrap(), 'a');
+            assert_eq!(cx.get_expireable_event(handle), Some((3, &'b')));
+
+            assert_matches!(
+                cx.reschedule_event(handle, 1),
+                Err(RescheduleEventError::NewEventBeforeCurrent)
+            );
+        }
+
+        if payload == 'b' {
+            assert_eq!(cx.get_expireable_event(handle), None);
+            assert_matches!(
+                cx.reschedule_event(handle, 5),
+                Err(RescheduleEventError::InvalidOrUsedHandle)
+            );
+        }
+    }
+
+    async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {}
+}
+
+#[test]
+fn expire_handle_reschedule() {
+    let transposer = RescheduleTransposer {
+        handle: None
+    };
+    let rng_seed = rand::thread_rng().gen();
+
+    let mut step = Step::<_, NoInput>::new_init(transposer, 0, rng_seed);
+    let waker = DummyWaker::dummy();
+    assert_matches!(step.poll(&waker), Ok(StepPoll::Ready));
+
+    let events = run_steps(&mut step, u32::MAX);
+
+    assert_eq!(events, vec![(2, 'x'), (3, 'c'), (3, 'b')]);
+}
+
+#[derive(Clone, Debug)]
+struct ScheduleQueryTransposer;
+
+impl Transposer for ScheduleQueryTransposer {
+    type Time = u32;
+
+    // (next scheduled time, number of scheduled events, scheduled events in [5, 12))
+    type OutputState = (Option<u32>, usize, Vec<(u32, char)>);
+
+    type Scheduled = char;
+
+    // (next scheduled time, number of scheduled events) while handling an event
+    type OutputEvent = (Option<u32>, usize);
+
+    type InputStateManager = NoInputManager;
+
+    async fn init(&mut self, cx: &mut impl InitContext<Self>) {
+        cx.schedule_event(12, 'd').unwrap();
+        cx.schedule_event(5, 'a').unwrap();
+        cx.schedule_event(8, 'c').unwrap();
+        cx.schedule_event(5, 'b').unwrap();
+    }
+
+    async fn handle_scheduled(
+        &mut self,
+        _payload: Self::Scheduled,
+        cx: &mut impl HandleScheduleContext<Self>,
+    ) {
+        let next = cx.next_scheduled_time();
+        let count = cx.scheduled_event_count();
+        cx.emit_event((next, count)).await;
+    }
+
+    async fn interpolate(&self, cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {
+        assert_eq!(
+            cx.scheduled_events((Bound::Excluded(5), Bound::Excluded(5)))
+                .count(),
+            0
+        );
+
+        let events = cx
+            .scheduled_events((Bound::Included(5), Bound::Excluded(12)))
+            .map(|(time, payload)| (time, *payload))
+            .collect();
+
+        (cx.next_scheduled_time(), cx.scheduled_event_count(), events)
+    }
+}
+
+#[test]
+fn schedule_query() {
+    let rng_seed = rand::thread_rng().gen();
+
+    let mut init = Step::<_, NoInput>::new_init(ScheduleQueryTransposer, 0, rng_seed);
+    let waker = DummyWaker::dummy();
+    assert_matches!(init.poll(&waker), Ok(StepPoll::Ready));
+
+    let interpolated = futures_executor::block_on(init.interpolate(3).unwrap());
+    assert_eq!(
+        interpolated,
+        (Some(5), 4, vec![(5, 'a'), (5, 'b'), (8, 'c')])
+    );
+
+    // both events at 5 are handled in the same step.
+    let mut next = init.next_scheduled_unsaturated().unwrap().unwrap();
+    next.saturate_take(&mut init).unwrap();
+    assert_matches!(next.poll(&waker), Ok(StepPoll::Emitted((Some(5), 3))));
+    assert_matches!(next.poll(&waker), Ok(StepPoll::Emitted((Some(8), 2))));
+    assert_matches!(next.poll(&waker), Ok(StepPoll::Ready));
+
+    let interpolated = futures_executor::block_on(next.interpolate(6).unwrap());
+    assert_eq!(interpolated, (Some(8), 2, vec![(8, 'c')]));
+}
+
+#[derive(Clone, Debug)]
+struct RepeatingTransposer {
+    handle: Option<ExpireHandle>,
+}
+
+impl Transposer for RepeatingTransposer {
+    type Time = u32;
+
+    type Period = u32;
+
+    type OutputState = ();
+
+    type Scheduled = char;
+
+    type OutputEvent = char;
+
+    type InputStateManager = NoInputManager;
+
+    async fn init(&mut self, cx: &mut impl InitContext<Self>) {
+        assert_matches!(
+            cx.schedule_repeating(3, 0, 'z'),
+            Err(ScheduleEventError::NonPositivePeriod)
+        );
+
+        self.handle = Some(cx.schedule_repeating(2, 3, 'r').unwrap());
+        cx.schedule_event(5, 'x').unwrap();
+    }
+
+    async fn handle_scheduled(
+        &mut self,
+        payload: Self::Scheduled,
+        cx: &mut impl HandleScheduleContext<Self>,
+    ) {
+        let time = cx.current_time();
+        cx.emit_event(payload).await;
+
+        let handle = self.handle.unwrap();
+
+        if payload == 'x' {
+            // only the next repetition is in the schedule.
+            assert_eq!(cx.scheduled_event_count(), 1);
+            assert_eq!(cx.get_expireable_event(handle), Some((8, &'r')));
+        }
+
+        if time >= 11 {
+            assert_eq!(cx.expire_event(handle).unwrap(), (14, 'r'));
+        }
+    }
+
+    async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {}
+}
+
+#[test]
+fn schedule_repeating() {
+    let transposer = RepeatingTransposer {
+        handle: None
+    };
+    let rng_seed = rand::thread_rng().gen();
+
+    let mut step = Step::<_, NoInput>::new_init(transposer, 0, rng_seed);
+    let waker = DummyWaker::dummy();
+    assert_matches!(step.poll(&waker), Ok(StepPoll::Ready));
+
+    let events = run_steps(&mut step, u32::MAX);
+
+    // repetitions keep the order of the original event.
+    assert_eq!(events, vec![
+        (2, 'r'),
+        (5, 'r'),
+        (5, 'x'),
+        (8, 'r'),
+        (11, 'r')
+    ]);
+}
+
+#[derive(Clone, Debug)]
+struct OverflowTransposer;
+
+impl Transposer for OverflowTransposer {
+    type Time = u8;
+
+    type Period = u8;
+
+    type OutputState = ();
+
+    type Scheduled = ();
+
+    type OutputEvent = ();
+
+    type InputStateManager = NoInputManager;
+
+    async fn init(&mut self, cx: &mut impl InitContext<Self>) {
+        cx.schedule_repeating(250, 2, ()).unwrap();
+        cx.schedule_repeating(255, 1, ()).unwrap();
+    }
+
+    async fn handle_scheduled(
+        &mut self,
+        _payload: Self::Scheduled,
+        cx: &mut impl HandleScheduleContext<Self>,
+    ) {
+        cx.emit_event(()).await;
+    }
+
+    async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {}
+}
+
+#[test]
+fn schedule_repeating_overflow() {
+    let rng_seed = rand::thread_rng().gen();
+
+    let mut step = Step::<_, NoInput>::new_init(OverflowTransposer, 0, rng_seed);
+    let waker = DummyWaker::dummy();
+    assert_matches!(step.poll(&waker), Ok(StepPoll::Ready));
+
+    let events = run_steps(&mut step, u8::MAX);
+
+    // repetitions stop once the next one would be past the end of time.
+    let times: Vec<_> = events.into_iter().map(|(time, ())| time).collect();
+    assert_eq!(times, vec![250, 252, 254, 255]);
+}
+
+#[derive(Clone, Debug)]
+struct HistoryTransposer {
+    history: Vec<u32>,
+}
+
+impl Transposer for HistoryTransposer {
+    type Time = u32;
+
+    type OutputState = ();
+
+    type Scheduled = ();
+
+    type OutputEvent = ();
+
+    type InputStateManager = NoInputManager;
+
+    async fn init(&mut self, cx: &mut impl InitContext<Self>) {
+        cx.schedule_event(1, ()).unwrap();
+    }
+
+    async fn handle_scheduled(
+        &mut self,
+        _payload: Self::Scheduled,
+        cx: &mut impl HandleScheduleContext<Self>,
+    ) {
+        self.history.extend(0..100);
+        cx.schedule_event(cx.current_time() + 1, ()).unwrap();
+    }
+
+    async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {}
+
+    fn heap_size(&self) -> usize {
+        self.history.capacity() * size_of::<u32>()
+    }
+}
+
+#[test]
+fn approximate_size() {
+    let transposer = HistoryTransposer {
+        history: Vec::new(),
+    };
+    let rng_seed = rand::thread_rng().gen();
+
+    let mut step = Step::<_, NoInput>::new_init(transposer, 0, rng_seed);
+    let waker = DummyWaker::dummy();
+    assert_matches!(step.poll(&waker), Ok(StepPoll::Ready));
+    let initial_size = step.approximate_size().unwrap();
+
+    let mut next = step.next_scheduled_unsaturated().unwrap().unwrap();
+    assert_eq!(next.approximate_size(), None);
+
+    next.saturate_clone(&step).unwrap();
+    assert_matches!(next.poll(&waker), Ok(StepPoll::Ready));
+
+    // the history grew by 100 entries.
+    assert!(next.approximate_size().unwrap() >= initial_size + 400);
+}
+
+#[derive(Clone, Debug)]
+struct RngTransposer<const PER_EVENT: bool> {
+    // how many extra random numbers to use at time 2.
+    extra: usize,
+}
+
+impl<const PER_EVENT: bool> Transposer for RngTransposer<PER_EVENT> {
+    type Time = u32;
+
+    type Period = u32;
+
+    type OutputState = ();
+
+    type Scheduled = ();
+
+    type OutputEvent = u64;
+
+    type InputStateManager = NoInputManager;
+
+    const RNG_MODE: RngMode<u32> = if PER_EVENT {
+        RngMode::per_event()
+    } else {
+        RngMode::SingleStream
+    };
+
+    async fn init(&mut self, cx: &mut impl InitContext<Self>) {
+        cx.schedule_repeating(1, 1, ()).unwrap();
+    }
+
+    async fn handle_scheduled(
+        &mut self,
+        _payload: Self::Scheduled,
+        cx: &mut impl HandleScheduleContext<Self>,
+    ) {
+        if cx.current_time() == 2 {
+            for _ in 0..self.extra {
+                let _: u64 = cx.get_rng().gen();
+            }
+        }
+
+        let value = cx.get_rng().gen();
+        cx.emit_event(value).await;
+    }
+
+    async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {}
+}
+
+fn rng_values<const PER_EVENT: bool>(extra: usize) -> Vec<u64> {
+    let transposer = RngTransposer::<PER_EVENT> {
+        extra,
+    };
+
+    let mut step = Step::<_, NoInput>::new_init(transposer, 0, [7; 32]);
+    let waker = DummyWaker::dummy();
+    assert_matches!(step.poll(&waker), Ok(StepPoll::Ready));
+
+    let values = run_steps(&mut step, 5);
+    assert_eq!(values.len(), 5);
+
+    values.into_iter().map(|(_, value)| value).collect()
+}
+
+#[test]
+fn rng_per_event() {
+    let values = rng_values::<true>(0);
+    let greedy_values = rng_values::<true>(3);
+
+    // only the event which used more random numbers sees different values.
+    assert_eq!(values[0], greedy_values[0]);
+    assert_ne!(values[1], greedy_values[1]);
+    assert_eq!(values[2..], greedy_values[2..]);
+
+    // every repetition of the event gets its own values.
+    let mut distinct = values.clone();
+    distinct.sort();
+    distinct.dedup();
+    assert_eq!(distinct.len(), values.len());
+}
+
+#[test]
+fn rng_single_stream() {
+    let values = rng_values::<false>(0);
+    let greedy_values = rng_values::<false>(3);
+
+    // every event from the one which used more random numbers on sees values shifted along the stream.
+    assert_eq!(values[0], greedy_values[0]);
+    assert_eq!(values[4], greedy_values[1]);
+    assert_ne!(values[2..], greedy_values[2..]);
+}
+
+#[derive(Clone, Debug)]
+struct RngChainTransposer {
+    // whether init also schedules an unrelated event, before the chain starts.
+    unrelated: bool,
+}
+
+impl Transposer for RngChainTransposer {
+    type Time = u32;
+
+    type OutputState = ();
+
+    // whether the event is part of the chain.
+    type Scheduled = bool;
+
+    type OutputEvent = u64;
+
+    type InputStateManager = NoInputManager;
+
+    const RNG_MODE: RngMode<u32> = RngMode::per_event();
+
+    async fn init(&mut self, cx: &mut impl InitContext<Self>) {
+        cx.schedule_event(2, true).unwrap();
+
+        if self.unrelated {
+            cx.schedule_event(1, false).unwrap();
+        }
+    }
+
+    async fn handle_scheduled(
+        &mut self,
+        chain: Self::Scheduled,
+        cx: &mut impl HandleScheduleContext<Self>,
+    ) {
+        let value = cx.get_rng().gen();
+
+        if chain {
+            let time = cx.current_time();
+            cx.emit_event(value).await;
+            cx.schedule_event(time + 1, true).unwrap();
+        }
+    }
+
+    async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {}
+}
+
+fn rng_chain_values(unrelated: bool) -> Vec<(u32, u64)> {
+    let transposer = RngChainTransposer {
+        unrelated,
+    };
+
+    let mut step = Step::<_, NoInput>::new_init(transposer, 0, [7; 32]);
+    let waker = DummyWaker::dummy();
+    assert_matches!(step.poll(&waker), Ok(StepPoll::Ready));
+
+    run_steps(&mut step, 6)
+}
+
+#[test]
+fn rng_per_event_ignores_unrelated_events() {
+    let values = rng_chain_values(false);
+
+    // the unrelated event moves every later step along by one, but the chain sees the same values.
+    assert_eq!(rng_chain_values(true), values);
+    assert_eq!(
+        values.iter().map(|(time, _)| *time).collect::<Vec<_>>(),
+        vec![2, 3, 4, 5, 6]
+    );
+}
+
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct SnapshotTransposer {
+    handle: Option<crate::expire_handle::ExpireHandle>,
+}
+
+#[cfg(feature = "serde")]
+impl Transposer for SnapshotTransposer {
+    type Time = u32;
+
+    type OutputState = Option<crate::expire_handle::ExpireHandle>;
+
+    type Scheduled = u32;
+
+    // (payload, random number)
+    type OutputEvent = (u32, u64);
+
+    type InputStateManager = NoInputManager;
+
+    async fn init(&mut self, cx: &mut impl InitContext<Self>) {
+        cx.schedule_event(1, 0).unwrap();
+        self.handle = Some(cx.schedule_event_expireable(2, 1).unwrap());
+    }
+
+    async fn handle_scheduled(
+        &mut self,
+        payload: Self::Scheduled,
+        cx: &mut impl HandleScheduleContext<Self>,
+    ) {
+        let time = cx.current_time();
+        let value = cx.get_rng().gen();
+        cx.emit_event((payload, value)).await;
+
+        if payload == 0 {
+            cx.schedule_event(time + 1, 0).unwrap();
+
+            if time % 3 == 0 {
+                if let Some(handle) = self.handle.take() {
+                    let _ = cx.expire_event(handle);
+                }
+            }
+
+            if self.handle.is_none() {
+                self.handle = Some(cx.schedule_event_expireable(time + 2, 1).unwrap());
+            }
+        } else {
+            self.handle = None;
+        }
+    }
+
+    async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {
+        self.handle
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn snapshot_resume() {
+    type SnapshotStep = Step<SnapshotTransposer, NoInput>;
+
+    fn run(mut step: SnapshotStep, until: u32) -> (SnapshotStep, Vec<(u32, (u32, u64))>) {
+        let events = run_steps(&mut step, until);
+        (step, events)
+    }
+
+    let transposer = SnapshotTransposer {
+        handle: None
+    };
+    let rng_seed = rand::thread_rng().gen();
+
+    let mut init = Step::<_, NoInput>::new_init(transposer, 0, rng_seed);
+    assert_matches!(init.poll(&DummyWaker::dummy()), Ok(StepPoll::Ready));
+
+    let (middle, _) = run(init, 10);
+    let snapshot = serde_json::to_string(&middle.snapshot().unwrap()).unwrap();
+
+    let (expected_end, expected_events) = run(middle, 30);
+
+    let restored = Step::<_, NoInput>::new_from_snapshot(serde_json::from_str(&snapshot).unwrap());
+    let (end, events) = run(restored, 30);
+
+    assert_eq!(events, expected_events);
+    assert_eq!(end.get_time(), expected_end.get_time());
+
+    let state = futures_executor::block_on(end.interpolate(100).unwrap());
+    let expected_state = futures_executor::block_on(expected_end.interpolate(100).unwrap());
+    assert_eq!(state, expected_state);
+}
@@ -1,4 +1,7 @@
+use core::hash::{Hash, Hasher};
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubStepTime<T: Ord + Copy> {
     // the canonical order that this time occured
     pub index: usize,
@@ -8,10 +11,17 @@ pub struct SubStepTime<T: Ord + Copy> {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScheduledTime<T: Ord + Copy> {
     pub time:           T,
     pub parent_index:   usize,
     pub emission_index: usize,
+
+    // how many times a repeating event has repeated before this.
+    pub repetition: usize,
+
+    // the identity of the event for the rng, which unlike the indices doesn't depend on the events before it.
+    pub identity: u64,
 }
 
 impl<T: Ord + Copy> SubStepTime<T> {
@@ -26,11 +36,18 @@ impl<T: Ord + Copy> SubStepTime<T> {
         }
     }
 
-    pub fn spawn_scheduled(&self, time: T, emission_index: usize) -> ScheduledTime<T> {
+    pub fn spawn_scheduled(
+        &self,
+        time: T,
+        emission_index: usize,
+        identity: u64,
+    ) -> ScheduledTime<T> {
         ScheduledTime {
             time,
             parent_index: self.index(),
             emission_index,
+            repetition: 0,
+            identity,
         }
     }
 
@@ -38,3 +55,21 @@ impl<T: Ord + Copy> SubStepTime<T> {
         self.time
     }
 }
+
+// indices are hashed as u64, so state hashes are the same on 32 and 64 bit targets.
+impl<T: Ord + Copy + Hash> Hash for SubStepTime<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.index as u64).hash(state);
+        self.time.hash(state);
+    }
+}
+
+impl<T: Ord + Copy + Hash> Hash for ScheduledTime<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.time.hash(state);
+        (self.parent_index as u64).hash(state);
+        (self.emission_index as u64).hash(state);
+        (self.repetition as u64).hash(state);
+        self.identity.hash(state);
+    }
+}
@@ -1,11 +1,14 @@
+use core::hash::{Hash, Hasher};
+use core::ops::Bound;
+
 use rand::SeedableRng;
-use rand_chacha::rand_core::block::BlockRng;
-use rand_chacha::ChaCha12Core;
+use rand_chacha::ChaCha12Rng;
 
 use super::expire_handle_factory::ExpireHandleFactory;
 use super::time::{ScheduledTime, SubStepTime};
 use crate::context::ExpireEventError;
 use crate::expire_handle::ExpireHandle;
+use crate::period::RepeatPeriod;
 use crate::schedule_storage::{HashMapStorage, OrdMapStorage, StorageFamily};
 use crate::Transposer;
 
@@ -19,9 +22,12 @@ pub struct TransposerMetaData<T: Transposer, S: StorageFamily> {
     pub expire_handles_forward:  S::HashMap<ExpireHandle, ScheduledTime<T::Time>>,
     pub expire_handles_backward: S::OrdMap<ScheduledTime<T::Time>, ExpireHandle>,
 
+    // only the next repetition of a repeating event is in the schedule.
+    pub repeating: S::HashMap<ExpireHandle, T::Period>,
+
     pub expire_handle_factory: ExpireHandleFactory,
 
-    pub rng: BlockRng<ChaCha12Core>,
+    pub rng: ChaCha12Rng,
 }
 
 impl<T: Transposer, S: StorageFamily> TransposerMetaData<T, S> {
@@ -34,6 +40,7 @@ impl<T: Transposer, S: StorageFamily> TransposerMetaData<T, S> {
             <S::HashMap<ExpireHandle, ScheduledTime<T::Time>> as HashMapStorage<_, _>>::new();
         let expire_handles_backward =
             <S::OrdMap<ScheduledTime<T::Time>, ExpireHandle> as OrdMapStorage<_, _>>::new();
+        let repeating = <S::HashMap<ExpireHandle, T::Period> as HashMapStorage<_, _>>::new();
 
         Self {
             last_updated: SubStepTime {
@@ -43,8 +50,9 @@ impl<T: Transposer, S: StorageFamily> TransposerMetaData<T, S> {
             schedule,
             expire_handles_forward,
             expire_handles_backward,
+            repeating,
             expire_handle_factory: ExpireHandleFactory::default(),
-            rng: BlockRng::new(ChaCha12Core::from_seed(rng_seed)),
+            rng: ChaCha12Rng::from_seed(rng_seed),
         }
     }
 
@@ -66,6 +74,18 @@ impl<T: Transposer, S: StorageFamily> TransposerMetaData<T, S> {
         handle
     }
 
+    pub fn schedule_event_repeating(
+        &mut self,
+        time: ScheduledTime<T::Time>,
+        period: T::Period,
+        payload: T::Scheduled,
+    ) -> ExpireHandle {
+        let handle = self.schedule_event_expireable(time, payload);
+        self.repeating.insert(handle, period);
+
+        handle
+    }
+
     pub fn expire_event(
         &mut self,
         handle: ExpireHandle,
@@ -79,6 +99,7 @@ impl<T: Transposer, S: StorageFamily> TransposerMetaData<T, S> {
                 let payload = payload.unwrap();
                 self.expire_handles_backward.remove(time);
                 self.expire_handles_forward.remove(&handle);
+                self.repeating.remove(&handle);
 
                 Ok((t, payload))
             },
@@ -86,14 +107,131 @@ impl<T: Transposer, S: StorageFamily> TransposerMetaData<T, S> {
         }
     }
 
+    pub fn reschedule_event(
+        &mut self,
+        handle: ExpireHandle,
+        new_time: ScheduledTime<T::Time>,
+    ) -> Result<T::Time, ExpireEventError> {
+        match self.expire_handles_forward.remove(&handle) {
+            Some(time) => {
+                // maps are kept in sync
+                let payload = self.schedule.remove(&time).unwrap();
+                self.expire_handles_backward.remove(&time);
+
+                self.schedule.insert(new_time, payload);
+                self.expire_handles_forward.insert(handle, new_time);
+                self.expire_handles_backward.insert(new_time, handle);
+
+                Ok(time.time)
+            },
+            None => Err(ExpireEventError::InvalidOrUsedHandle),
+        }
+    }
+
+    pub fn replace_event_payload(
+        &mut self,
+        handle: ExpireHandle,
+        payload: T::Scheduled,
+    ) -> Result<T::Scheduled, ExpireEventError> {
+        match self.expire_handles_forward.get(&handle) {
+            Some(time) => {
+                let time = *time;
+
+                // maps are kept in sync
+                let old = self.schedule.remove(&time).unwrap();
+                self.schedule.insert(time, payload);
+
+                Ok(old)
+            },
+            None => Err(ExpireEventError::InvalidOrUsedHandle),
+        }
+    }
+
+    pub fn get_expireable_event(&self, handle: ExpireHandle) -> Option<(T::Time, &T::Scheduled)> {
+        let time = self.expire_handles_forward.get(&handle)?;
+
+        // maps are kept in sync
+        Some((time.time, self.schedule.get(time).unwrap()))
+    }
+
     pub fn get_next_scheduled_time(&self) -> Option<&ScheduledTime<T::Time>> {
         self.schedule.get_first().map(|(k, _)| k)
     }
 
+    /// the pending events with times in `range`, in the order they will be handled.
+    pub fn scheduled_events(
+        &self,
+        range: (Bound<T::Time>, Bound<T::Time>),
+    ) -> impl '_ + Iterator<Item = (T::Time, &'_ T::Scheduled)> {
+        let is_empty = match range {
+            (Bound::Included(start), Bound::Included(end)) => start > end,
+            (Bound::Included(start) | Bound::Excluded(start), Bound::Excluded(end))
+            | (Bound::Excluded(start), Bound::Included(end)) => start >= end,
+            _ => false,
+        };
+
+        // scheduled times at the same time are ordered by their indices, so cover all of them.
+        let first = |time| ScheduledTime {
+            time,
+            parent_index: 0,
+            emission_index: 0,
+            repetition: 0,
+            identity: 0,
+        };
+        let last = |time| ScheduledTime {
+            time,
+            parent_index: usize::MAX,
+            emission_index: usize::MAX,
+            repetition: usize::MAX,
+            identity: u64::MAX,
+        };
+
+        let start = match range.0 {
+            Bound::Included(time) => Bound::Included(first(time)),
+            Bound::Excluded(time) => Bound::Excluded(last(time)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let end = match range.1 {
+            Bound::Included(time) => Bound::Included(last(time)),
+            Bound::Excluded(time) => Bound::Excluded(first(time)),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+
+        (!is_empty)
+            .then(|| self.schedule.range((start, end)))
+            .into_iter()
+            .flatten()
+            .map(|(time, payload)| (time.time, payload))
+    }
+
     pub fn pop_first_event(&mut self) -> Option<(ScheduledTime<T::Time>, T::Scheduled)> {
         if let Some((k, v)) = self.schedule.pop_first() {
             if let Some(h) = self.expire_handles_backward.remove(&k) {
-                self.expire_handles_forward.remove(&h);
+                // a repeating event stops repeating once its next time can't be represented.
+                let next_time = self
+                    .repeating
+                    .get(&h)
+                    .and_then(|period| period.checked_add_to(k.time));
+
+                match next_time {
+                    Some(time) => {
+                        // the next repetition keeps the indices of the original, so its order is deterministic.
+                        // its repetition count tells it apart from the original for the rng.
+                        let next = ScheduledTime {
+                            time,
+                            repetition: k.repetition + 1,
+                            ..k
+                        };
+
+                        self.schedule.insert(next, v.clone());
+                        self.expire_handles_forward.insert(h, next);
+                        self.expire_handles_backward.insert(next, h);
+                    },
+                    None => {
+                        self.expire_handles_forward.remove(&h);
+                        self.repeating.remove(&h);
+                    },
+                }
             }
 
             Some((k, v))
@@ -101,4 +239,62 @@ impl<T: Transposer, S: StorageFamily> TransposerMetaData<T, S> {
             None
         }
     }
+
+    /// restart the rng on the stream for the event with this identity.
+    pub fn start_event_rng(&mut self, identity: u64) {
+        self.rng.set_stream(identity);
+        self.rng.set_word_pos(0);
+    }
+
+    /// an approximate number of bytes of heap memory held by the schedule and handles.
+    pub fn heap_size(&self) -> usize {
+        self.schedule.len() * size_of::<(ScheduledTime<T::Time>, T::Scheduled)>()
+            + self.expire_handles_forward.len()
+                * size_of::<(ExpireHandle, ScheduledTime<T::Time>)>()
+            + self.expire_handles_backward.len()
+                * size_of::<(ScheduledTime<T::Time>, ExpireHandle)>()
+            + self.repeating.len() * size_of::<(ExpireHandle, T::Period)>()
+    }
+
+    /// feed everything that affects future steps into `state`.
+    pub fn hash<H: Hasher>(&self, state: &mut H)
+    where
+        T::Time: Hash,
+        T::Period: Hash,
+        T::Scheduled: Hash,
+    {
+        self.last_updated.hash(state);
+
+        for (time, payload) in self.schedule.iter() {
+            time.hash(state);
+            payload.hash(state);
+        }
+
+        // the forward map is the same as the backward one, but hash maps have no fixed order.
+        for (time, handle) in self.expire_handles_backward.iter() {
+            time.hash(state);
+            handle.hash(state);
+            self.repeating.get(handle).hash(state);
+        }
+
+        self.expire_handle_factory.hash(state);
+
+        self.rng.get_seed().hash(state);
+        self.rng.get_stream().hash(state);
+        self.rng.get_word_pos().hash(state);
+    }
+}
+
+// each part of an event's identity is mixed into every bit of the result, so identities which differ anywhere
+// give unrelated rng streams.
+pub fn event_identity(parts: [u64; 4]) -> u64 {
+    parts.into_iter().fold(0, |hash, x| splitmix64(hash ^ x))
+}
+
+// one step of splitmix64, which mixes every bit of the input into every bit of the output.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
@@ -1,10 +1,13 @@
-use super::sub_step_update_context::SubStepUpdateContext;
+use core::hash::{Hash, Hasher};
+
+use super::state_hasher::StateHasher;
+use super::sub_step_update_context::{SubStepState, SubStepUpdateContext};
 use super::time::SubStepTime;
 use super::transposer_metadata::TransposerMetaData;
 use crate::schedule_storage::{RefCounted, StorageFamily};
 use crate::step::step_inputs::StepInputs;
 use crate::step::InputState;
-use crate::Transposer;
+use crate::{Transposer, TransposerHash};
 
 #[derive(Clone)]
 pub struct WrappedTransposer<T: Transposer, S: StorageFamily> {
@@ -27,21 +30,22 @@ impl<T: Transposer, S: StorageFamily> WrappedTransposer<T, S> {
     ) -> S::Transposer<Self> {
         let mut metadata = TransposerMetaData::new(rng_seed, start_time);
         let input_state_provider = input_state.get_provider();
-        let mut context = SubStepUpdateContext::new(
+        let mut state = SubStepState::new(
             SubStepTime::new_init(start_time),
-            &mut metadata,
-            input_state_provider,
             outputs_to_swallow,
             output_sender,
         );
+        let mut context =
+            SubStepUpdateContext::new(&mut metadata, &mut state, input_state_provider);
 
+        context.start_init_event();
         transposer.init(&mut context).await;
 
-        let SubStepUpdateContext {
+        let SubStepState {
             outputs_to_swallow,
             output_sender,
             ..
-        } = context;
+        } = state;
 
         let mut new = Self {
             transposer,
@@ -54,6 +58,28 @@ impl<T: Transposer, S: StorageFamily> WrappedTransposer<T, S> {
         S::Transposer::new(Box::new(new))
     }
 
+    /// a hash of the transposer and everything else that affects future steps.
+    ///
+    /// this uses [`StateHasher`], which is fully specified, so it is the same on every machine, target and toolchain
+    /// as long as the hashes of `T` and its associated types are.
+    pub fn state_hash(&self) -> u64
+    where
+        T: TransposerHash,
+        T::Time: Hash,
+        T::Period: Hash,
+        T::Scheduled: Hash,
+    {
+        let mut state = StateHasher::default();
+        self.transposer.hash_state(&mut state);
+        self.metadata.hash(&mut state);
+        state.finish()
+    }
+
+    /// an approximate number of bytes used by the transposer and its metadata, including the heap.
+    pub fn approximate_size(&self) -> usize {
+        size_of::<Self>() + self.transposer.heap_size() + self.metadata.heap_size()
+    }
+
     /// handle an input, and all scheduled events that occur at the same time.
     pub async fn handle_input<Is: InputState<T>>(
         &mut self,
@@ -72,21 +98,17 @@ impl<T: Transposer, S: StorageFamily> WrappedTransposer<T, S> {
             time:  input.time,
         };
 
-        let mut context = SubStepUpdateContext::new(
-            time,
-            &mut self.metadata,
-            input_state_provider,
-            outputs_to_swallow,
-            output_sender,
-        );
+        let mut state = SubStepState::new(time, outputs_to_swallow, output_sender);
+        let mut context =
+            SubStepUpdateContext::new(&mut self.metadata, &mut state, input_state_provider);
 
         input.handle(&mut self.transposer, &mut context).await;
 
-        let SubStepUpdateContext {
+        let SubStepState {
             output_sender,
             outputs_to_swallow,
             ..
-        } = context;
+        } = state;
 
         self.metadata.last_updated = time;
 
@@ -112,16 +134,13 @@ impl<T: Transposer, S: StorageFamily> WrappedTransposer<T, S> {
             time,
         };
 
-        let mut context = SubStepUpdateContext::new(
-            time,
-            &mut self.metadata,
-            input_state_provider,
-            outputs_to_swallow,
-            output_sender,
-        );
+        let mut state = SubStepState::new(time, outputs_to_swallow, output_sender);
+        let mut context =
+            SubStepUpdateContext::new(&mut self.metadata, &mut state, input_state_provider);
 
         while context.metadata.get_next_scheduled_time().map(|s| s.time) == Some(time.time) {
-            let (_, e) = context.metadata.pop_first_event().unwrap();
+            let (scheduled_time, e) = context.metadata.pop_first_event().unwrap();
+            context.start_scheduled_event(scheduled_time);
             self.transposer.handle_scheduled(e, &mut context).await;
             context.metadata.last_updated = time;
             time.index += 1;
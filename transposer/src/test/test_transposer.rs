@@ -7,7 +7,7 @@ use crate::context::{
     InputStateContextExt,
     InterpolateContext,
 };
-use crate::{StateRetriever, Transposer, TransposerInput, TransposerInputEventHandler};
+use crate::{transposer_inputs, Transposer, TransposerInput, TransposerInputEventHandler};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub(crate) enum HandleRecord {
@@ -15,6 +15,11 @@ pub(crate) enum HandleRecord {
     Scheduled(usize, usize),
 }
 
+#[transposer_inputs(
+    retriever = TestTransposerStateRetriever,
+    input(TestTransposerInput1, event = usize, state = usize, sort = 1),
+    input(TestTransposerInput2, event = usize, state = usize, sort = 2),
+)]
 #[derive(Clone)]
 pub(crate) struct TestTransposer {
     init_events: Vec<(usize, usize)>,
@@ -33,43 +38,6 @@ impl TestTransposer {
     }
 }
 
-pub(crate) struct TestTransposerInput1;
-pub(crate) struct TestTransposerInput2;
-
-impl TransposerInput for TestTransposerInput1 {
-    // set up with macro
-    type Base = TestTransposer;
-
-    type InputEvent = usize;
-
-    type InputState = usize;
-
-    const SORT: u64 = 1;
-}
-
-impl TransposerInput for TestTransposerInput2 {
-    // set up with macro
-    type Base = TestTransposer;
-
-    type InputEvent = usize;
-
-    type InputState = usize;
-
-    const SORT: u64 = 2;
-}
-
-// set up with macro
-pub(crate) trait TestTransposerStateRetriever:
-    StateRetriever<TestTransposerInput1> + StateRetriever<TestTransposerInput2>
-{
-}
-
-// set up with macro
-impl<T> TestTransposerStateRetriever for T where
-    T: StateRetriever<TestTransposerInput1> + StateRetriever<TestTransposerInput2>
-{
-}
-
 // the default handler impl for inputs
 impl TransposerInputEventHandler<TestTransposerInput1> for TestTransposer {
     fn can_handle(
@@ -89,10 +57,9 @@ impl Transposer for TestTransposer {
 
     type OutputEvent = usize;
 
-    // set up with macro
-    type InputStateManager = dyn TestTransposerStateRetriever;
+    type InputStateManager = dyn TestTransposerStateRetriever + Send + Sync;
 
-    async fn init(&mut self, cx: &mut dyn InitContext<'_, Self>) {
+    async fn init(&mut self, cx: &mut impl InitContext<Self>) {
         for (time, payload) in self.init_events.drain(..) {
             let _ = cx.schedule_event(time, payload);
         }
@@ -101,7 +68,7 @@ impl Transposer for TestTransposer {
     async fn handle_scheduled(
         &mut self,
         payload: Self::Scheduled,
-        cx: &mut dyn HandleScheduleContext<'_, Self>,
+        cx: &mut impl HandleScheduleContext<Self>,
     ) {
         let record = HandleRecord::Scheduled(cx.current_time(), payload);
         self.handle_record.push_back((record, cx.get_rng().gen()));
@@ -110,13 +77,13 @@ impl Transposer for TestTransposer {
         cx.emit_event(*state).await;
     }
 
-    async fn interpolate(&self, _cx: &mut dyn InterpolateContext<'_, Self>) -> Self::OutputState {
+    async fn interpolate(&self, _cx: &mut impl InterpolateContext<Self>) -> Self::OutputState {
         self.handle_record.clone().into_iter().collect()
     }
 }
 
 impl TransposerInputEventHandler<TestTransposerInput2> for TestTransposer {
-    async fn handle_input(&mut self, input: &usize, cx: &mut dyn HandleInputContext<'_, Self>) {
+    async fn handle_input(&mut self, input: &usize, cx: &mut impl HandleInputContext<Self>) {
         let record = HandleRecord::Input(cx.current_time(), *input);
         self.handle_record.push_back((record, cx.get_rng().gen()));
 